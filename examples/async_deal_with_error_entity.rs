@@ -4,7 +4,7 @@ use transistor::types::error::CruxError;
 use transistor::types::CruxId;
 
 async fn entity_timed() -> Result<edn_rs::Edn, CruxError> {
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").async_client();
     let timed = "2014-11-28T21:00:09-09:00"
         .parse::<DateTime<FixedOffset>>()
         .unwrap();
@@ -13,7 +13,7 @@ async fn entity_timed() -> Result<edn_rs::Edn, CruxError> {
         .entity_timed(CruxId::new("unknown-id"), None, Some(timed))
         .await;
 
-    return edn_body;
+    edn_body
 }
 
 #[tokio::main]
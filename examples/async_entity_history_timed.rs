@@ -18,7 +18,7 @@ async fn entity_history_timed() -> EntityHistoryResponse {
         last_name: "Manuel".to_string(),
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").async_client();
     let timed = "2014-11-28T21:00:09-09:00"
         .parse::<DateTime<FixedOffset>>()
         .unwrap();
@@ -36,7 +36,7 @@ async fn entity_history_timed() -> EntityHistoryResponse {
         .append_put_timed(person2, timed);
 
     let _ = Crux::new("localhost", "3000")
-        .http_client()
+        .async_client()
         .tx_log(actions)
         .await
         .unwrap();
@@ -53,7 +53,7 @@ async fn entity_history_timed() -> EntityHistoryResponse {
         .await
         .unwrap();
 
-    return entity_history;
+    entity_history
 }
 
 #[tokio::main]
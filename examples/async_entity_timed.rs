@@ -17,7 +17,7 @@ async fn entity_timed() -> edn_rs::Edn {
         last_name: "Manuel".to_string(),
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").async_client();
     let timed = "2014-11-28T21:00:09-09:00"
         .parse::<DateTime<FixedOffset>>()
         .unwrap();
@@ -27,7 +27,7 @@ async fn entity_timed() -> edn_rs::Edn {
         .append_put_timed(person2, timed);
 
     let _ = Crux::new("localhost", "3000")
-        .http_client()
+        .async_client()
         .tx_log(actions)
         .await
         .unwrap();
@@ -37,7 +37,7 @@ async fn entity_timed() -> edn_rs::Edn {
         .await
         .unwrap();
 
-    return edn_body;
+    edn_body
 }
 
 #[tokio::main]
@@ -1,7 +1,6 @@
 use chrono::prelude::*;
 use edn_derive::Serialize;
 use transistor::client::Crux;
-use transistor::edn_rs::EdnError;
 use transistor::types::response::EntityTxResponse;
 use transistor::types::Actions;
 use transistor::types::CruxId;
@@ -19,17 +18,17 @@ async fn entity_tx_timed() -> EntityTxResponse {
         last_name: "Manuel".to_string(),
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").async_client();
     let timed = "2014-11-28T21:00:09-09:00"
         .parse::<DateTime<FixedOffset>>()
         .unwrap();
 
     let actions = Actions::new()
-        .append_put_timed(person1.clone(), timed.clone())
-        .append_put_timed(person2, timed.clone());
+        .append_put_timed(person1.clone(), timed)
+        .append_put_timed(person2, timed);
 
     let _ = Crux::new("localhost", "3000")
-        .http_client()
+        .async_client()
         .tx_log(actions)
         .await
         .unwrap();
@@ -39,7 +38,7 @@ async fn entity_tx_timed() -> EntityTxResponse {
         .await
         .unwrap();
 
-    return entity_tx_body;
+    entity_tx_body
 }
 
 #[tokio::main]
@@ -23,7 +23,7 @@ async fn query() -> Result<(), CruxError> {
         is_sql: true,
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").async_client();
     let actions = Actions::new()
         .append_put(crux)
         .append_put(psql)
@@ -37,7 +37,7 @@ async fn query() -> Result<(), CruxError> {
         .unwrap()
         .build();
 
-    let is_sql = client.query(query_is_sql.unwrap()).await.unwrap();
+    let _is_sql = client.query(query_is_sql.unwrap()).await.unwrap();
     // QueryAsyncResponse({[":mysql", "MySQL"], [":postgres", "Postgres"]}) BTreeSet
 
     let query_is_no_sql = Query::find(vec!["?p1", "?n", "?s"])
@@ -48,7 +48,7 @@ async fn query() -> Result<(), CruxError> {
         .build()
         .unwrap();
 
-    let is_no_sql = client.query(query_is_no_sql).await.unwrap();
+    let _is_no_sql = client.query(query_is_no_sql).await.unwrap();
     // {["{:crux.db/id: Key(\":cassandra\"), :is-sql: Bool(false), :name: Str(\"Cassandra\"), }", "Cassandra", "false"],
     //  ["{:crux.db/id: Key(\":crux\"), :is-sql: Bool(false), :name: Str(\"Crux Datalog\"), }", "Crux Datalog", "false"]}
 
@@ -57,7 +57,7 @@ async fn query() -> Result<(), CruxError> {
 
 #[tokio::main]
 async fn main() {
-    let _ = query().await.unwrap();
+    query().await.unwrap();
 }
 
 #[tokio::test]
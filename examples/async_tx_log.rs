@@ -20,12 +20,12 @@ async fn tx_log() -> TxLogResponse {
     let actions = Actions::new().append_put(person1).append_put(person2);
 
     let body = Crux::new("localhost", "3000")
-        .http_client()
+        .async_client()
         .tx_log(actions)
         .await
         .unwrap();
 
-    return body;
+    body
 }
 
 #[tokio::main]
@@ -3,11 +3,11 @@ use transistor::types::response::TxLogsResponse;
 
 async fn tx_logs() -> TxLogsResponse {
     let body = Crux::new("localhost", "3000")
-        .http_client()
+        .async_client()
         .tx_logs()
         .await
         .unwrap();
-    return body;
+    body
 }
 
 #[tokio::main]
@@ -4,7 +4,6 @@ use transistor::types::error::CruxError;
 use transistor::types::response::EntityTxResponse;
 use transistor::types::CruxId;
 
-#[cfg(not(feature = "async"))]
 fn entity_tx() -> Result<EntityTxResponse, CruxError> {
     let person = Person {
         crux__db___id: CruxId::new("error-id"),
@@ -13,13 +12,11 @@ fn entity_tx() -> Result<EntityTxResponse, CruxError> {
     };
     // { :crux.db/id :hello-entity, :first-name \"Hello\", :last-name \"World\", }
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
 
-    let tx_body = client.entity_tx(person.crux__db___id);
-    return tx_body;
+    client.entity_tx(person.crux__db___id)
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let entity_tx = entity_tx();
     println!("Tx Body = {:#?}", entity_tx);
@@ -31,7 +28,6 @@ fn main() {
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_entity_tx() {
     let entity_tx = entity_tx();
 
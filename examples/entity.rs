@@ -4,7 +4,6 @@ use transistor::edn_rs::{Deserialize, EdnError};
 use transistor::types::Actions;
 use transistor::types::CruxId;
 
-#[cfg(not(feature = "async"))]
 fn entity() -> edn_rs::Edn {
     let person = Person {
         crux__db___id: CruxId::new("hello-entity"),
@@ -13,19 +12,16 @@ fn entity() -> edn_rs::Edn {
     };
     // { :crux.db/id :hello-entity, :first-name \"Hello\", :last-name \"World\", }
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let put_person = Actions::new().append_put(person.clone());
 
     let _ = client.tx_log(put_person).unwrap();
     // {:crux.tx/tx-id 7, :crux.tx/tx-time #inst \"2020-07-16T21:50:39.309-00:00\"}
 
-    let edn_body = client.entity(person.crux__db___id).unwrap();
-
-    return edn_body;
+    client.entity(person.crux__db___id).unwrap()
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_entity() {
     let edn_body = entity();
     let person = edn_rs::from_edn::<Person>(&edn_body);
@@ -38,7 +34,6 @@ fn test_entity() {
     assert_eq!(person.unwrap(), expected);
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let edn_body = entity();
     println!("\n Edn Body = {:#?}", edn_body.clone());
@@ -4,7 +4,6 @@ use transistor::types::http::{Actions, Order};
 use transistor::types::response::EntityTxResponse;
 use transistor::types::CruxId;
 
-#[cfg(not(feature = "async"))]
 fn entity_tx() -> EntityTxResponse {
     let person = Person {
         crux__db___id: CruxId::new("hello-history"),
@@ -14,17 +13,15 @@ fn entity_tx() -> EntityTxResponse {
 
     let put_person = Actions::new().append_put(person.clone());
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let _ = client.tx_log(put_person).unwrap();
 
-    let tx_body = client.entity_tx(person.crux__db___id).unwrap();
-    return tx_body;
+    client.entity_tx(person.crux__db___id).unwrap()
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_entity_history_with_docs() {
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let tx_body = entity_tx();
     let docs = client
         .entity_history(tx_body.db___id.clone(), Order::Asc, true)
@@ -33,9 +30,8 @@ fn test_entity_history_with_docs() {
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_entity_history_without_docs() {
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let tx_body = entity_tx();
     let docs = client
         .entity_history(tx_body.db___id.clone(), Order::Asc, false)
@@ -43,9 +39,8 @@ fn test_entity_history_without_docs() {
     assert!(docs.history[0].db__doc.is_none())
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let tx_body = entity_tx();
     let _ = client.entity_history(tx_body.db___id.clone(), Order::Asc, true);
     // EntityHistoryResponse { history: [
@@ -4,7 +4,6 @@ use transistor::types::response::EntityTxResponse;
 use transistor::types::Actions;
 use transistor::types::CruxId;
 
-#[cfg(not(feature = "async"))]
 fn entity_tx() -> EntityTxResponse {
     let person = Person {
         crux__db___id: CruxId::new("hello-entity"),
@@ -13,17 +12,15 @@ fn entity_tx() -> EntityTxResponse {
     };
     // { :crux.db/id :hello-entity, :first-name \"Hello\", :last-name \"World\", }
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let put_person = Actions::new().append_put(person.clone());
 
     let _ = client.tx_log(put_person).unwrap();
     // {:crux.tx/tx-id 7, :crux.tx/tx-time #inst \"2020-07-16T21:50:39.309-00:00\"}
 
-    let tx_body = client.entity_tx(person.crux__db___id).unwrap();
-    return tx_body;
+    client.entity_tx(person.crux__db___id).unwrap()
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let entity_tx = entity_tx();
     println!("Tx Body = {:#?}", entity_tx);
@@ -37,7 +34,6 @@ fn main() {
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_entity_tx() {
     let entity_tx = entity_tx();
 
@@ -4,7 +4,6 @@ use transistor::types::response::TxLogResponse;
 use transistor::types::Actions;
 use transistor::types::CruxId;
 
-#[cfg(not(feature = "async"))]
 fn evict() -> TxLogResponse {
     let person = Person {
         crux__db___id: CruxId::new("hello-evict"),
@@ -13,7 +12,7 @@ fn evict() -> TxLogResponse {
     };
     // { :crux.db/id :hello-evict, :first-name \"Hello\", :last-name \"World\", }
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
 
     let actions = Actions::new().append_put(person.clone());
     // [[:crux.tx/put { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", }]]"
@@ -22,18 +21,16 @@ fn evict() -> TxLogResponse {
     //  {:crux.tx/tx-id 7, :crux.tx/tx-time #inst \"2020-07-16T21:50:39.309-00:00\"}
 
     let actions = Actions::new().append_evict(person.crux__db___id);
-    let evict_body = client.tx_log(actions).unwrap();
-    return evict_body;
+
+    client.tx_log(actions).unwrap()
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let evict_body = evict();
     println!("\n Evict Body = {:?}", evict_body);
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_evict() {
     let evict = evict();
     assert!(evict.tx___tx_id > 0);
@@ -6,7 +6,6 @@ use transistor::types::{
     {query::Query, CruxId},
 };
 
-#[cfg(not(feature = "async"))]
 fn limit_offset() -> Result<(), CruxError> {
     let crux = Database {
         crux__db___id: CruxId::new("crux"),
@@ -38,7 +37,7 @@ fn limit_offset() -> Result<(), CruxError> {
         is_sql: true,
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let actions = Actions::new()
         .append_put(crux)
         .append_put(psql)
@@ -65,13 +64,11 @@ fn limit_offset() -> Result<(), CruxError> {
     Ok(())
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let _ = limit_offset();
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_limit_offset() {
     limit_offset().unwrap();
 }
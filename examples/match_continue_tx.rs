@@ -6,7 +6,6 @@ use transistor::types::{
     {query::Query, CruxId},
 };
 
-#[cfg(not(feature = "async"))]
 fn match_continue() -> Result<(), CruxError> {
     let crux = Database {
         crux__db___id: CruxId::new("crux"),
@@ -14,7 +13,7 @@ fn match_continue() -> Result<(), CruxError> {
         is_sql: false,
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let actions = Actions::new().append_put(crux.clone());
 
     let _ = client.tx_log(actions)?;
@@ -26,7 +25,7 @@ fn match_continue() -> Result<(), CruxError> {
     let query_response = client.query(query)?;
 
     let id = CruxId::new(&query_response.iter().next().unwrap()[0]);
-    let _ = client.entity(id).unwrap();
+    let _edn = client.entity(id).unwrap();
     // Map(Map({":crux.db/id": Key(":crux"), ":is-sql": Bool(false), ":name": Str("Crux Datalog")}))
 
     let actions = Actions::new()
@@ -43,19 +42,17 @@ fn match_continue() -> Result<(), CruxError> {
     let query_response = client.query(query)?;
 
     let id = CruxId::new(&query_response.iter().next().unwrap()[0]);
-    let _ = client.entity(id).unwrap();
+    let _edn = client.entity(id).unwrap();
     // Map(Map({":crux.db/id": Key(":crux"), ":is-sql": Bool(false), ":name": Str("banana")}))
 
     Ok(())
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let _ = match_continue();
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_match_continue() {
     match_continue().unwrap();
 }
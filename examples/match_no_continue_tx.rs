@@ -6,7 +6,6 @@ use transistor::types::{
     {query::Query, CruxId},
 };
 
-#[cfg(not(feature = "async"))]
 fn match_break() -> Result<(), CruxError> {
     let mut crux = Database {
         crux__db___id: CruxId::new("crux"),
@@ -14,7 +13,7 @@ fn match_break() -> Result<(), CruxError> {
         is_sql: false,
     };
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let actions = Actions::new().append_put(crux.clone());
 
     let _ = client.tx_log(actions)?;
@@ -26,7 +25,7 @@ fn match_break() -> Result<(), CruxError> {
     let query_response = client.query(query)?;
 
     let id = CruxId::new(&query_response.iter().next().unwrap()[0]);
-    let _ = client.entity(id).unwrap();
+    let _edn = client.entity(id).unwrap();
     // Map(Map({":crux.db/id": Key(":crux"), ":is-sql": Bool(false), ":name": Str("Crux Datalog")}))
 
     crux.name = "banana".to_string();
@@ -44,19 +43,17 @@ fn match_break() -> Result<(), CruxError> {
     let query_response = client.query(query)?;
 
     let id = CruxId::new(&query_response.iter().next().unwrap()[0]);
-    let _ = client.entity(id).unwrap();
+    let _edn = client.entity(id).unwrap();
     // Map(Map({":crux.db/id": Key(":crux"), ":is-sql": Bool(false), ":name": Str("Crux Datalog")}))
 
     Ok(())
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let _ = match_break();
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_match_break() {
     match_break().unwrap();
 }
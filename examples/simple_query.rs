@@ -6,7 +6,6 @@ use transistor::types::{
     {query::Query, CruxId},
 };
 
-#[cfg(not(feature = "async"))]
 fn query() -> Result<(), CruxError> {
     let crux = Database {
         crux__db___id: CruxId::new("crux"),
@@ -29,7 +28,7 @@ fn query() -> Result<(), CruxError> {
     };
     // edn_rs::to_string(mysql) { :crux.db/id :mysql, :name \"MySQL\", :is-sql true, }
 
-    let client = Crux::new("localhost", "3000").http_client();
+    let client = Crux::new("localhost", "3000").blocking_client();
     let actions = Actions::new()
         .append_put(crux)
         .append_put(psql)
@@ -71,13 +70,11 @@ fn query() -> Result<(), CruxError> {
     Ok(())
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let _ = query();
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_query() {
     query().unwrap()
 }
@@ -4,7 +4,6 @@ use transistor::types::response::TxLogResponse;
 use transistor::types::Actions;
 use transistor::types::CruxId;
 
-#[cfg(not(feature = "async"))]
 fn tx_log() -> TxLogResponse {
     let person1 = Person {
         crux__db___id: CruxId::new("jorge-3"),
@@ -24,22 +23,18 @@ fn tx_log() -> TxLogResponse {
     // "[[:crux.tx/put { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", }],
     //   [:crux.tx/put { :crux.db/id :manuel-1, :first-name \"Diego\", :last-name \"Manuel\", }]]"
 
-    let body = Crux::new("localhost", "3000")
-        .http_client()
+    Crux::new("localhost", "3000")
+        .blocking_client()
         .tx_log(actions)
-        .unwrap();
-
-    return body;
+        .unwrap()
 }
 
 #[test]
-#[cfg(not(feature = "async"))]
 fn test_tx_log() {
     let tx_log = tx_log();
     assert!(tx_log.tx___tx_id > 0)
 }
 
-#[cfg(not(feature = "async"))]
 fn main() {
     let body = tx_log();
     println!("Body = {:?}", body);
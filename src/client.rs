@@ -1,14 +1,30 @@
-use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 
-use crate::http::HttpClient;
+use crate::http::middleware::Chain;
+#[cfg(feature = "async")]
+use crate::http::{AsyncCruxTransport, AsyncHttpClient, AsyncReqwestTransport};
+use crate::http::{
+    BlockingHttpClient, CruxTransport, HttpClient, Middleware, ReqwestTransport, WireFormat,
+};
+#[cfg(any(test, feature = "testing"))]
+use crate::testing::{RecordingTransport, ReplayTransport};
+#[cfg(any(test, feature = "testing"))]
+use crate::types::error::CruxError;
+#[cfg(any(test, feature = "testing"))]
+use std::path::Path;
+use std::sync::Arc;
 
 /// Struct to define parameters to connect to Crux
 /// `host` and `port` are required.
 /// `authorization` in `HeaderMap` is optional.
+/// `format` is the `WireFormat` used by the clients, `WireFormat::Edn` by default.
+/// `middleware` is the chain of `Middleware` hooks run by the clients, empty by default.
 pub struct Crux {
     host: String,
     port: String,
     headers: HeaderMap,
+    format: WireFormat,
+    middleware: Chain,
 }
 
 impl Crux {
@@ -21,6 +37,8 @@ impl Crux {
             host: host.to_string(),
             port: port.to_string(),
             headers,
+            format: WireFormat::Edn,
+            middleware: Chain::default(),
         }
     }
 
@@ -31,6 +49,24 @@ impl Crux {
         self
     }
 
+    /// Function to select the [`WireFormat`](../http/wire/enum.WireFormat.html) used to talk to Crux.
+    /// Sets `CONTENT_TYPE` and `ACCEPT` headers, requests are encoded and responses decoded in this format.
+    pub fn with_format(mut self, format: WireFormat) -> Self {
+        self.headers
+            .insert(CONTENT_TYPE, format.content_type().parse().unwrap());
+        self.headers
+            .insert(ACCEPT, format.content_type().parse().unwrap());
+        self.format = format;
+        self
+    }
+
+    /// Function to add a [`Middleware`](../http/middleware/trait.Middleware.html) to the clients, hooks run in the order they were added.
+    /// See [`HeaderInjector`](../http/middleware/struct.HeaderInjector.html) and [`BodyLogger`](../http/middleware/struct.BodyLogger.html).
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
     #[cfg(not(test))]
     fn uri(&self) -> String {
         format!("http://{}:{}", self.host, self.port)
@@ -42,37 +78,92 @@ impl Crux {
         server_url()
     }
 
-    /// To query database on Docker/standalone via http it is necessary to use `HttpClient`
+    /// To query database on Docker/standalone via http it is necessary to use `HttpClient`.
+    /// `HttpClient` is a `BlockingHttpClient` by default and an `AsyncHttpClient` when feature `async` is enabled.
+    /// To use both in the same binary prefer `blocking_client` and `async_client`.
     #[cfg(not(feature = "async"))]
     pub fn http_client(&mut self) -> HttpClient {
-        HttpClient {
-            client: reqwest::blocking::Client::new(),
-            uri: self.uri().clone(),
+        self.blocking_client()
+    }
+
+    /// To query database on Docker/standalone via http it is necessary to use `HttpClient`.
+    /// `HttpClient` is a `BlockingHttpClient` by default and an `AsyncHttpClient` when feature `async` is enabled.
+    /// To use both in the same binary prefer `blocking_client` and `async_client`.
+    #[cfg(feature = "async")]
+    pub fn http_client(&mut self) -> HttpClient {
+        self.async_client()
+    }
+
+    /// Synchronous client, `BlockingHttpClient`, available regardless of feature `async`.
+    pub fn blocking_client(&mut self) -> BlockingHttpClient {
+        let transport = ReqwestTransport::new(&self.uri());
+        self.blocking_client_with(transport)
+    }
+
+    /// Synchronous client that sends its requests through a custom [`CruxTransport`](../http/transport/trait.CruxTransport.html).
+    /// The transport is responsible for the node address, so `host:port` are not used.
+    pub fn blocking_client_with<T: CruxTransport>(
+        &mut self,
+        transport: T,
+    ) -> BlockingHttpClient<T> {
+        BlockingHttpClient {
+            transport,
             headers: self.headers.clone(),
+            format: self.format,
+            middleware: self.middleware.clone(),
         }
     }
 
+    /// Asynchronous client, `AsyncHttpClient`. Feature `async` is required.
     #[cfg(feature = "async")]
-    pub fn http_client(&mut self) -> HttpClient {
-        HttpClient {
-            client: reqwest::Client::new(),
-            uri: self.uri().clone(),
+    pub fn async_client(&mut self) -> AsyncHttpClient {
+        let transport = AsyncReqwestTransport::new(&self.uri());
+        self.async_client_with(transport)
+    }
+
+    /// Asynchronous client that sends its requests through a custom [`AsyncCruxTransport`](../http/transport/trait.AsyncCruxTransport.html).
+    /// The transport is responsible for the node address, so `host:port` are not used.
+    #[cfg(feature = "async")]
+    pub fn async_client_with<T: AsyncCruxTransport>(&mut self, transport: T) -> AsyncHttpClient<T> {
+        AsyncHttpClient {
+            transport,
             headers: self.headers.clone(),
+            format: self.format,
+            middleware: self.middleware.clone(),
         }
     }
 
-    /// A mock of `HttpClient` using `mockito = "0.26"`.
+    /// `BlockingHttpClient` that records every request/response pair sent to `host:port` into the `fixture` file.
+    /// The fixture can be served later by `replay_client`. Feature `testing` is required.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn recording_client<P: AsRef<Path>>(
+        &mut self,
+        fixture: P,
+    ) -> BlockingHttpClient<RecordingTransport<ReqwestTransport>> {
+        let transport = RecordingTransport::new(ReqwestTransport::new(&self.uri()), fixture);
+        self.blocking_client_with(transport)
+    }
+
+    /// `BlockingHttpClient` that answers with the responses recorded in `fixture` by `recording_client`, no Crux node is needed.
+    /// Feature `testing` is required.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn replay_client<P: AsRef<Path>>(
+        &mut self,
+        fixture: P,
+    ) -> Result<BlockingHttpClient<ReplayTransport>, CruxError> {
+        let transport = ReplayTransport::from_file(fixture)?;
+        Ok(self.blocking_client_with(transport))
+    }
+
+    /// A mock of `BlockingHttpClient` using `mockito = "0.26"`.
     #[cfg(feature = "mock")]
-    pub fn http_mock(&mut self) -> HttpClient {
+    pub fn http_mock(&mut self) -> BlockingHttpClient {
         use mockito::server_url;
 
         self.headers
             .insert(CONTENT_TYPE, "application/edn".parse().unwrap());
-        HttpClient {
-            client: reqwest::blocking::Client::new(),
-            uri: server_url(),
-            headers: self.headers.clone(),
-        }
+        let transport = ReqwestTransport::new(&server_url());
+        self.blocking_client_with(transport)
     }
 }
 
@@ -90,11 +181,25 @@ mod test {
             host: String::from("host"),
             port: String::from("port"),
             headers,
+            format: WireFormat::Edn,
+            middleware: Chain::default(),
         };
 
         assert_eq!(actual.host, expected.host);
         assert_eq!(actual.port, expected.port);
         assert_eq!(actual.headers, expected.headers);
+        assert_eq!(actual.format, expected.format);
+    }
+
+    #[test]
+    fn with_format() {
+        let crux = Crux::new("host", "port").with_format(WireFormat::Edn);
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, "application/edn".parse().unwrap());
+        headers.insert(ACCEPT, "application/edn".parse().unwrap());
+
+        assert_eq!(crux.headers, headers);
+        assert_eq!(crux.format, WireFormat::Edn);
     }
 
     #[test]
@@ -115,21 +220,47 @@ mod test {
     }
 
     #[test]
-    fn http_client() {
+    fn blocking_client() {
         let mut headers = HeaderMap::new();
         headers.insert(AUTHORIZATION, "auth".parse().unwrap());
         headers.insert(CONTENT_TYPE, "application/edn".parse().unwrap());
 
         let actual = Crux::new("127.0.0.1", "1234")
             .with_authorization("auth")
-            .http_client();
-        let expected = HttpClient {
-            client: reqwest::blocking::Client::new(),
-            uri: "http://127.0.0.1:1234".to_string(),
-            headers: headers,
+            .blocking_client();
+        let expected = BlockingHttpClient {
+            transport: ReqwestTransport::new("http://127.0.0.1:1234"),
+            headers,
+            format: WireFormat::Edn,
+            middleware: Chain::default(),
         };
 
-        assert_eq!(actual.uri, expected.uri);
+        assert_eq!(actual.transport.uri, expected.transport.uri);
         assert_eq!(actual.headers, expected.headers);
     }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_client() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, "auth".parse().unwrap());
+        headers.insert(CONTENT_TYPE, "application/edn".parse().unwrap());
+
+        let mut crux = Crux::new("127.0.0.1", "1234").with_authorization("auth");
+        let blocking = crux.blocking_client();
+        let actual = crux.async_client();
+
+        assert_eq!(actual.transport.uri, blocking.transport.uri);
+        assert_eq!(actual.headers, headers);
+    }
+
+    #[test]
+    fn replay_client_without_fixture() {
+        let err = Crux::new("127.0.0.1", "1234")
+            .replay_client("tests/fixtures/missing.edn")
+            .err()
+            .unwrap();
+
+        assert!(format!("{}", err).starts_with("Unable to read tests/fixtures/missing.edn"));
+    }
 }
@@ -0,0 +1,423 @@
+use crate::http::endpoints;
+use crate::http::middleware::Chain;
+use crate::http::telemetry::Call;
+use crate::http::transport::{
+    AsyncCruxTransport, AsyncReqwestTransport, CruxRequest, CruxResponse,
+};
+use crate::http::{WireFormat, UPDATE_ENTITY_RETRIES};
+use crate::types::{
+    error::CruxError,
+    http::{Actions, BatchLimit, Order, TimeHistory},
+    query::Query,
+    response::{
+        BitemporalGrid, EntityHistoryResponse, EntityTxResponse, QueryAsyncResponse, QueryInfo,
+        TxLogResponse, TxLogsResponse,
+    },
+    CruxId,
+};
+use chrono::prelude::*;
+use edn_rs::{Edn, Serialize};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+
+type TxLogFuture<'a> = Pin<Box<dyn Future<Output = Result<TxLogResponse, CruxError>> + 'a>>;
+
+// Polls all `futures` concurrently on the current task, outputs are in the same order as `futures`.
+async fn join_all<'a>(mut futures: Vec<TxLogFuture<'a>>) -> Vec<Result<TxLogResponse, CruxError>> {
+    let mut outputs = futures.iter().map(|_| None).collect::<Vec<_>>();
+    std::future::poll_fn(|cx| {
+        let mut pending = false;
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(result) => *output = Some(result),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+/// `AsyncHttpClient` has the `AsyncCruxTransport`, `AsyncReqwestTransport` by default, and the `HeaderMap` with
+/// all the possible headers. Default header is `Content-Type: "application/edn"`, see [`WireFormat`](wire/enum.WireFormat.html) for other formats. Asynchronous request.
+pub struct AsyncHttpClient<T: AsyncCruxTransport = AsyncReqwestTransport> {
+    pub(crate) transport: T,
+    pub(crate) headers: HeaderMap,
+    pub(crate) format: WireFormat,
+    pub(crate) middleware: Chain,
+}
+
+impl<T: AsyncCruxTransport> AsyncHttpClient<T> {
+    pub(crate) async fn send(&self, mut request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        request.headers.extend(self.headers.clone());
+        request.body = request
+            .body
+            .map(|body| self.format.encode(&body))
+            .transpose()?;
+        let sent = self.middleware.before_send(&mut request)?;
+        let call = Call::start(&request, self.format);
+        let response = call.instrument(self.transport.send(request)).await;
+        call.finish(&response, self.format);
+        self.middleware.after_receive(sent, response)
+    }
+
+    /// Function `tx_log` requests endpoint `/tx-log` via `POST` which allow you to send actions `Action`
+    /// to CruxDB.
+    /// The "write" endpoint, to post transactions.
+    /// With a `ClusterTransport`, a transaction is only sent to another node when the first one could not be reached, other
+    /// transport errors are returned since the transaction may have been committed, see `tx_committed`.
+    pub async fn tx_log(&self, actions: Actions) -> Result<TxLogResponse, CruxError> {
+        let resp = self.send(endpoints::tx_log(actions)?).await?;
+        endpoints::parse_tx_log(resp, self.format)
+    }
+
+    /// Function `tx_log_batched` splits `actions` into transactions within `limit`, see [`BatchLimit`](../types/http/enum.BatchLimit.html), and sends up to `concurrency` of them at a time with `tx_log`.
+    /// Concurrent batches can be committed in any order, but their `TxLogResponse`s are returned in batch order. If `await_tx` is true, it also waits for the last committed transaction to be indexed with `await_tx`.
+    /// `actions` are validated before any batch is sent. Batches are not atomic with each other: if one fails, no further batches are sent and `CruxError::TxLogBatchError` has the index of the failed batch and the `TxLogResponse`s of the committed ones,
+    /// including batches after it that were sent concurrently and committed.
+    pub async fn tx_log_batched(
+        &self,
+        actions: Actions,
+        limit: BatchLimit,
+        concurrency: usize,
+        await_tx: bool,
+    ) -> Result<Vec<TxLogResponse>, CruxError> {
+        actions.validate()?;
+        let mut batches = actions.batches(limit)?.into_iter();
+        let total = batches.len();
+        let mut txs = Vec::with_capacity(total);
+        while batches.len() > 0 {
+            let sending = batches
+                .by_ref()
+                .take(concurrency.max(1))
+                .map(|batch| Box::pin(self.tx_log(batch)) as TxLogFuture<'_>)
+                .collect();
+            let mut failed = None;
+            for (index, result) in (txs.len()..).zip(join_all(sending).await) {
+                match result {
+                    Ok(tx) => txs.push((index, tx)),
+                    Err(e) if failed.is_none() => failed = Some((index, e)),
+                    Err(_) => (),
+                }
+            }
+            if let Some((index, e)) = failed {
+                return Err(endpoints::batch_error(index, total, txs, e));
+            }
+        }
+
+        let txs: Vec<TxLogResponse> = txs.into_iter().map(|(_, tx)| tx).collect();
+        if let (true, Some(last)) = (await_tx, txs.iter().max_by_key(|tx| tx.tx___tx_id)) {
+            self.await_tx(last.tx___tx_id, None).await?;
+        }
+        Ok(txs)
+    }
+
+    /// Function `await_tx` requests endpoint `/await-tx` via `GET`, which waits until the transaction `tx_id` is indexed, up to `timeout` or the node's default timeout.
+    pub async fn await_tx(
+        &self,
+        tx_id: usize,
+        timeout: Option<Duration>,
+    ) -> Result<TxLogResponse, CruxError> {
+        let resp = self.send(endpoints::await_tx(tx_id, timeout)).await?;
+        endpoints::parse_await_tx(resp, self.format)
+    }
+
+    /// Function `tx_committed` requests endpoint `/tx-committed` via `GET`. Returns `false` if the transaction `tx_id` was discarded because a `Match` failed.
+    /// The transaction must already be indexed, see `await_tx`.
+    pub async fn tx_committed(&self, tx_id: usize) -> Result<bool, CruxError> {
+        let resp = self.send(endpoints::tx_committed(tx_id)).await?;
+        endpoints::parse_tx_committed(resp, self.format)
+    }
+
+    /// Function `update_entity` is `update_entity_with_retries` with `UPDATE_ENTITY_RETRIES` retries.
+    pub async fn update_entity<D, F>(
+        &self,
+        id: CruxId,
+        update: F,
+    ) -> Result<TxLogResponse, CruxError>
+    where
+        D: Serialize,
+        F: FnMut(Edn) -> D,
+    {
+        self.update_entity_with_retries(id, UPDATE_ENTITY_RETRIES, update)
+            .await
+    }
+
+    /// Function `update_entity_with_retries` is a compare-and-set of entity `id`. It reads the document with `entity`, sends a transaction with a `Match` of it
+    /// and a `Put` of `update(document)`, waits for it with `await_tx` and checks it with `tx_committed`.
+    /// If the entity changed in between, it starts again with the new document, up to `retries` times, and then returns `CruxError::Conflict`.
+    /// Returns the `TxLogResponse` of the committed transaction. `update` may be called once per attempt.
+    pub async fn update_entity_with_retries<D, F>(
+        &self,
+        id: CruxId,
+        retries: usize,
+        mut update: F,
+    ) -> Result<TxLogResponse, CruxError>
+    where
+        D: Serialize,
+        F: FnMut(Edn) -> D,
+    {
+        let crux_id = edn_rs::to_string(id.clone());
+        for _ in 0..=retries {
+            let resp = self
+                .send(endpoints::entity("entity", &crux_id, None, None))
+                .await?;
+            let document = endpoints::match_document(&resp.body, self.format);
+            let current = endpoints::parse_entity(resp, self.format, "entity", &crux_id)?;
+            let new = update(current);
+            let tx = self
+                .tx_log(endpoints::update_entity(id.clone(), document?, new))
+                .await?;
+            self.await_tx(tx.tx___tx_id, None).await?;
+            if self.tx_committed(tx.tx___tx_id).await? {
+                return Ok(tx);
+            }
+        }
+        Err(endpoints::update_conflict(&id, retries + 1))
+    }
+
+    /// Function `tx_logs` requests endpoint `/tx-log` via `GET` and returns a list of all transactions
+    pub async fn tx_logs(&self) -> Result<TxLogsResponse, CruxError> {
+        let resp = self.send(endpoints::tx_logs()).await?;
+        endpoints::parse_tx_logs(resp, self.format)
+    }
+
+    /// Function `entity` requests endpoint `/entity` via `POST` which retrieves the last document
+    /// in CruxDB.
+    /// Field with `CruxId` is required.
+    /// Response is a `reqwest::Result<edn_rs::Edn>` with the last Entity with that ID.
+    pub async fn entity(&self, id: CruxId) -> Result<Edn, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self
+            .send(endpoints::entity("entity", &crux_id, None, None))
+            .await?;
+        endpoints::parse_entity(resp, self.format, "entity", &crux_id)
+    }
+
+    /// Function `entity_timed` is like `entity` but with two optional fields `transaction_time` and `valid_time` that are of type `Option<DateTime<FixedOffset>>`.
+    pub async fn entity_timed(
+        &self,
+        id: CruxId,
+        transaction_time: Option<DateTime<FixedOffset>>,
+        valid_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<Edn, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self
+            .send(endpoints::entity(
+                "entity",
+                &crux_id,
+                transaction_time,
+                valid_time,
+            ))
+            .await?;
+        endpoints::parse_entity(resp, self.format, "entity-timed", &crux_id)
+    }
+
+    /// Function `entity_tx` requests endpoint `/entity-tx` via `POST` which retrieves the docs and tx infos
+    /// for the last document for that ID saved in CruxDB.
+    pub async fn entity_tx(&self, id: CruxId) -> Result<EntityTxResponse, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self
+            .send(endpoints::entity("entity-tx", &crux_id, None, None))
+            .await?;
+        endpoints::parse_entity_tx(resp, self.format, "entity-tx", &crux_id)
+    }
+
+    /// Function `entity_tx_timed` is like `entity_tx` but with two optional fields `transaction_time` and `valid_time` that are of type `Option<DateTime<FixedOffset>>`.
+    pub async fn entity_tx_timed(
+        &self,
+        id: CruxId,
+        transaction_time: Option<DateTime<FixedOffset>>,
+        valid_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<EntityTxResponse, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self
+            .send(endpoints::entity(
+                "entity-tx",
+                &crux_id,
+                transaction_time,
+                valid_time,
+            ))
+            .await?;
+        endpoints::parse_entity_tx(resp, self.format, "entity-tx-timed", &crux_id)
+    }
+
+    /// Function `entity_history` requests endpoint `/entity-history` via `GET` which returns a list with all entity's transaction history.
+    /// It is possible to order it with [`Order`](../types/http/enum.Order.html) , `types::http::Order::Asc` and `types::http::Order:Desc`, (second argument) and to include the document for each transaction with the boolean flag `with_docs` (third argument).
+    pub async fn entity_history(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+    ) -> Result<EntityHistoryResponse, CruxError> {
+        let resp = self
+            .send(endpoints::entity_history(
+                &hash,
+                order,
+                with_docs,
+                false,
+                Vec::new(),
+            )?)
+            .await?;
+        endpoints::parse_entity_history(resp, self.format, "entity-history", &hash)
+    }
+
+    /// Function `entity_history_timed` is an txtension of the function `entity_history`.
+    /// This function receives as the last argument a vector containing [`TimeHistory`](../types/http/enum.TimeHistory.html)  elements.
+    /// `TimeHistory` can be `ValidTime` or `TransactionTime` and both have optional `DateTime<Utc>` params corresponding to the start-time and end-time to be queried.
+    pub async fn entity_history_timed(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+    ) -> Result<EntityHistoryResponse, CruxError> {
+        let resp = self
+            .send(endpoints::entity_history(
+                &hash, order, with_docs, false, time,
+            )?)
+            .await?;
+        endpoints::parse_entity_history(resp, self.format, "entity-history-timed", &hash)
+    }
+
+    /// Function `entity_history_with_corrections` is `entity_history_timed` with the `with-corrections` flag, so every transaction that wrote a valid time is part of the history.
+    /// Each [`EntityHistoryElement`](../types/response/struct.EntityHistoryElement.html) is marked by its `coordinates`, a valid time and tx time pair.
+    pub async fn entity_history_with_corrections(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+    ) -> Result<EntityHistoryResponse, CruxError> {
+        let resp = self
+            .send(endpoints::entity_history(
+                &hash, order, with_docs, true, time,
+            )?)
+            .await?;
+        endpoints::parse_entity_history(resp, self.format, "entity-history-with-corrections", &hash)
+    }
+
+    /// Function `entity_bitemporal_grid` requests the history of `hash` with corrections and documents and builds its [`BitemporalGrid`](../types/response/struct.BitemporalGrid.html), valid time × transaction time.
+    pub async fn entity_bitemporal_grid(
+        &self,
+        hash: String,
+        time: Vec<TimeHistory>,
+    ) -> Result<BitemporalGrid, CruxError> {
+        Ok(self
+            .entity_history_with_corrections(hash, Order::Asc, true, time)
+            .await?
+            .bitemporal_grid())
+    }
+
+    /// Function `query` requests endpoint `/query` via `POST` which retrives a Set containing a vector of the values defined by the function [`Query::find` - github example](https://github.com/naomijub/transistor/blob/master/examples/simple_query.rs#L53).
+    /// Argument is a `query` of the type `Query`.
+    pub async fn query(&self, query: Query) -> Result<BTreeSet<Vec<String>>, CruxError> {
+        let resp = self.send(endpoints::query(query)).await?;
+        let query_response: QueryAsyncResponse = endpoints::parse_query(resp, self.format)?;
+
+        Ok(query_response.0)
+    }
+
+    /// Function `active_queries` requests endpoint `/active-queries` via `GET`, the queries currently running in the node.
+    pub async fn active_queries(&self) -> Result<Vec<QueryInfo>, CruxError> {
+        let resp = self.send(endpoints::query_info("active-queries")).await?;
+        endpoints::parse_query_info(resp, self.format, "active-queries")
+    }
+
+    /// Function `recent_queries` requests endpoint `/recent-queries` via `GET`, the latest queries that finished running.
+    pub async fn recent_queries(&self) -> Result<Vec<QueryInfo>, CruxError> {
+        let resp = self.send(endpoints::query_info("recent-queries")).await?;
+        endpoints::parse_query_info(resp, self.format, "recent-queries")
+    }
+
+    /// Function `slowest_queries` requests endpoint `/slowest-queries` via `GET`, the queries that took the longest to run.
+    pub async fn slowest_queries(&self) -> Result<Vec<QueryInfo>, CruxError> {
+        let resp = self.send(endpoints::query_info("slowest-queries")).await?;
+        endpoints::parse_query_info(resp, self.format, "slowest-queries")
+    }
+
+    /// Function `query_info` returns the active and recent entries of `query`, correlated with [`QueryInfo::is_query`](../types/response/struct.QueryInfo.html#method.is_query).
+    pub async fn query_info(&self, query: &Query) -> Result<Vec<QueryInfo>, CruxError> {
+        let mut queries = self.active_queries().await?;
+        queries.extend(self.recent_queries().await?);
+        queries.retain(|info| info.is_query(query));
+
+        Ok(queries)
+    }
+
+    /// Function `query_with_tx` is like `query`, but the `query` runs against a speculative database with `actions` applied, as in Crux's `with-tx`.
+    /// Nothing is committed, so it can be used to check invariants, like uniqueness, before sending `actions` to `tx_log`.
+    pub async fn query_with_tx(
+        &self,
+        query: Query,
+        actions: Actions,
+    ) -> Result<BTreeSet<Vec<String>>, CruxError> {
+        let resp = self.send(endpoints::query_with_tx(query, actions)?).await?;
+        let query_response: QueryAsyncResponse = endpoints::parse_query(resp, self.format)?;
+
+        Ok(query_response.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::client::Crux;
+    use crate::testing::support::person;
+    use crate::types::{http::Actions, response::TxLogResponse, CruxId};
+    use mockito::mock;
+
+    #[tokio::test]
+    async fn tx_log() {
+        let _m = mock("POST", "/tx-log")
+        .with_status(200)
+        .match_body("[[:crux.tx/put { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", }]]")
+        .with_header("content-type", "text/plain")
+        .with_body("{:crux.tx/tx-id 8, :crux.tx/tx-time #inst \"2020-07-16T21:53:14.628-00:00\"}")
+        .create();
+
+        let person = person("jorge-3", "Michael", "Jorge");
+
+        let actions = Actions::new().append_put(person);
+
+        let response = Crux::new("localhost", "4000")
+            .async_client()
+            .tx_log(actions)
+            .await;
+
+        assert_eq!(response.unwrap(), TxLogResponse::default())
+    }
+
+    #[tokio::test]
+    async fn entity() {
+        let expected_body = "Map(Map({\":crux.db/id\": Key(\":hello-entity\"), \":first-name\": Str(\"Hello\"), \":last-name\": Str(\"World\")}))";
+        let _m = mock("POST", "/entity")
+            .with_status(200)
+            .match_body("{:eid :hello-entity}")
+            .with_header("content-type", "application/edn")
+            .with_body("{:crux.db/id :hello-entity :first-name \"Hello\", :last-name \"World\"}")
+            .create();
+
+        let edn_body = Crux::new("localhost", "3000")
+            .async_client()
+            .entity(CruxId::new(":hello-entity"))
+            .await
+            .unwrap();
+
+        assert_eq!(format!("{:?}", edn_body), expected_body);
+    }
+}
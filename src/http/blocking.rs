@@ -0,0 +1,568 @@
+use crate::http::endpoints;
+use crate::http::middleware::Chain;
+use crate::http::telemetry::Call;
+use crate::http::transport::{CruxRequest, CruxResponse, CruxTransport, ReqwestTransport};
+use crate::http::{WireFormat, UPDATE_ENTITY_RETRIES};
+use crate::types::{
+    error::CruxError,
+    http::{Actions, BatchLimit, Order, TimeHistory},
+    query::Query,
+    response::{
+        BitemporalGrid, EntityHistoryResponse, EntityTxResponse, QueryInfo, QueryResponse,
+        TxLogResponse, TxLogsResponse,
+    },
+    CruxId,
+};
+use chrono::prelude::*;
+use edn_rs::{Edn, Serialize};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// `BlockingHttpClient` has the `CruxTransport`, `ReqwestTransport` by default, and the `HeaderMap` with
+/// all the possible headers. Default header is `Content-Type: "application/edn"`, see [`WireFormat`](wire/enum.WireFormat.html) for other formats. Synchronous request.
+pub struct BlockingHttpClient<T: CruxTransport = ReqwestTransport> {
+    pub(crate) transport: T,
+    pub(crate) headers: HeaderMap,
+    pub(crate) format: WireFormat,
+    pub(crate) middleware: Chain,
+}
+
+impl<T: CruxTransport> BlockingHttpClient<T> {
+    pub(crate) fn send(&self, mut request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        request.headers.extend(self.headers.clone());
+        request.body = request
+            .body
+            .map(|body| self.format.encode(&body))
+            .transpose()?;
+        let sent = self.middleware.before_send(&mut request)?;
+        let call = Call::start(&request, self.format);
+        let response = call.in_scope(|| self.transport.send(request));
+        call.finish(&response, self.format);
+        self.middleware.after_receive(sent, response)
+    }
+
+    /// Function `tx_log` requests endpoint `/tx-log` via `POST` which allow you to send actions `Action`
+    /// to CruxDB.
+    /// The "write" endpoint, to post transactions.
+    /// With a `ClusterTransport`, a transaction is only sent to another node when the first one could not be reached, other
+    /// transport errors are returned since the transaction may have been committed, see `tx_committed`.
+    pub fn tx_log(&self, actions: Actions) -> Result<TxLogResponse, CruxError> {
+        let resp = self.send(endpoints::tx_log(actions)?)?;
+        endpoints::parse_tx_log(resp, self.format)
+    }
+
+    /// Function `tx_log_batched` splits `actions` into transactions within `limit`, see [`BatchLimit`](../types/http/enum.BatchLimit.html), and sends them in order with `tx_log`.
+    /// Returns the `TxLogResponse` of each batch. If `await_tx` is true, it also waits for the last transaction to be indexed with `await_tx`.
+    /// `actions` are validated before any batch is sent. Batches are not atomic with each other: if one fails, `CruxError::TxLogBatchError` has the index of the failed batch and the `TxLogResponse`s of the batches already committed.
+    pub fn tx_log_batched(
+        &self,
+        actions: Actions,
+        limit: BatchLimit,
+        await_tx: bool,
+    ) -> Result<Vec<TxLogResponse>, CruxError> {
+        actions.validate()?;
+        let batches = actions.batches(limit)?;
+        let total = batches.len();
+        let mut txs = Vec::with_capacity(total);
+        for (index, batch) in batches.into_iter().enumerate() {
+            match self.tx_log(batch) {
+                Ok(tx) => txs.push(tx),
+                Err(e) => {
+                    let committed = txs.into_iter().enumerate().collect();
+                    return Err(endpoints::batch_error(index, total, committed, e));
+                }
+            }
+        }
+
+        if let (true, Some(last)) = (await_tx, txs.last()) {
+            self.await_tx(last.tx___tx_id, None)?;
+        }
+        Ok(txs)
+    }
+
+    /// Function `await_tx` requests endpoint `/await-tx` via `GET`, which waits until the transaction `tx_id` is indexed, up to `timeout` or the node's default timeout.
+    pub fn await_tx(
+        &self,
+        tx_id: usize,
+        timeout: Option<Duration>,
+    ) -> Result<TxLogResponse, CruxError> {
+        let resp = self.send(endpoints::await_tx(tx_id, timeout))?;
+        endpoints::parse_await_tx(resp, self.format)
+    }
+
+    /// Function `tx_committed` requests endpoint `/tx-committed` via `GET`. Returns `false` if the transaction `tx_id` was discarded because a `Match` failed.
+    /// The transaction must already be indexed, see `await_tx`.
+    pub fn tx_committed(&self, tx_id: usize) -> Result<bool, CruxError> {
+        let resp = self.send(endpoints::tx_committed(tx_id))?;
+        endpoints::parse_tx_committed(resp, self.format)
+    }
+
+    /// Function `update_entity` is `update_entity_with_retries` with `UPDATE_ENTITY_RETRIES` retries.
+    pub fn update_entity<D, F>(&self, id: CruxId, update: F) -> Result<TxLogResponse, CruxError>
+    where
+        D: Serialize,
+        F: FnMut(Edn) -> D,
+    {
+        self.update_entity_with_retries(id, UPDATE_ENTITY_RETRIES, update)
+    }
+
+    /// Function `update_entity_with_retries` is a compare-and-set of entity `id`. It reads the document with `entity`, sends a transaction with a `Match` of it
+    /// and a `Put` of `update(document)`, waits for it with `await_tx` and checks it with `tx_committed`.
+    /// If the entity changed in between, it starts again with the new document, up to `retries` times, and then returns `CruxError::Conflict`.
+    /// Returns the `TxLogResponse` of the committed transaction. `update` may be called once per attempt.
+    pub fn update_entity_with_retries<D, F>(
+        &self,
+        id: CruxId,
+        retries: usize,
+        mut update: F,
+    ) -> Result<TxLogResponse, CruxError>
+    where
+        D: Serialize,
+        F: FnMut(Edn) -> D,
+    {
+        let crux_id = edn_rs::to_string(id.clone());
+        for _ in 0..=retries {
+            let resp = self.send(endpoints::entity("entity", &crux_id, None, None))?;
+            let document = endpoints::match_document(&resp.body, self.format);
+            let current = endpoints::parse_entity(resp, self.format, "entity", &crux_id)?;
+            let new = update(current);
+            let tx = self.tx_log(endpoints::update_entity(id.clone(), document?, new))?;
+            self.await_tx(tx.tx___tx_id, None)?;
+            if self.tx_committed(tx.tx___tx_id)? {
+                return Ok(tx);
+            }
+        }
+        Err(endpoints::update_conflict(&id, retries + 1))
+    }
+
+    /// Function `tx_logs` requests endpoint `/tx-log` via `GET` and returns a list of all transactions
+    pub fn tx_logs(&self) -> Result<TxLogsResponse, CruxError> {
+        let resp = self.send(endpoints::tx_logs())?;
+        endpoints::parse_tx_logs(resp, self.format)
+    }
+
+    /// Function `entity` requests endpoint `/entity` via `POST` which retrieves the last document
+    /// in CruxDB.
+    /// Field with `CruxId` is required.
+    /// Response is a `reqwest::Result<edn_rs::Edn>` with the last Entity with that ID.
+    pub fn entity(&self, id: CruxId) -> Result<Edn, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self.send(endpoints::entity("entity", &crux_id, None, None))?;
+        endpoints::parse_entity(resp, self.format, "entity", &crux_id)
+    }
+
+    /// Function `entity_timed` is like `entity` but with two optional fields `transaction_time` and `valid_time` that are of type `Option<DateTime<FixedOffset>>`.
+    pub fn entity_timed(
+        &self,
+        id: CruxId,
+        transaction_time: Option<DateTime<FixedOffset>>,
+        valid_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<Edn, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self.send(endpoints::entity(
+            "entity",
+            &crux_id,
+            transaction_time,
+            valid_time,
+        ))?;
+        endpoints::parse_entity(resp, self.format, "entity-timed", &crux_id)
+    }
+
+    /// Function `entity_tx` requests endpoint `/entity-tx` via `POST` which retrieves the docs and tx infos
+    /// for the last document for that ID saved in CruxDB.
+    pub fn entity_tx(&self, id: CruxId) -> Result<EntityTxResponse, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self.send(endpoints::entity("entity-tx", &crux_id, None, None))?;
+        endpoints::parse_entity_tx(resp, self.format, "entity-tx", &crux_id)
+    }
+
+    /// Function `entity_tx_timed` is like `entity_tx` but with two optional fields `transaction_time` and `valid_time` that are of type `Option<DateTime<FixedOffset>>`.
+    pub fn entity_tx_timed(
+        &self,
+        id: CruxId,
+        transaction_time: Option<DateTime<FixedOffset>>,
+        valid_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<EntityTxResponse, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self.send(endpoints::entity(
+            "entity-tx",
+            &crux_id,
+            transaction_time,
+            valid_time,
+        ))?;
+        endpoints::parse_entity_tx(resp, self.format, "entity-tx-timed", &crux_id)
+    }
+
+    /// Function `entity_history` requests endpoint `/entity-history` via `GET` which returns a list with all entity's transaction history.
+    /// It is possible to order it with [`Order`](../types/http/enum.Order.html) , `types::http::Order::Asc` and `types::http::Order:Desc`, (second argument) and to include the document for each transaction with the boolean flag `with_docs` (third argument).
+    pub fn entity_history(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+    ) -> Result<EntityHistoryResponse, CruxError> {
+        let resp = self.send(endpoints::entity_history(
+            &hash,
+            order,
+            with_docs,
+            false,
+            Vec::new(),
+        )?)?;
+        endpoints::parse_entity_history(resp, self.format, "entity-history", &hash)
+    }
+
+    /// Function `entity_history_timed` is an txtension of the function `entity_history`.
+    /// This function receives as the last argument a vector containing [`TimeHistory`](../types/http/enum.TimeHistory.html)  elements.
+    /// `TimeHistory` can be `ValidTime` or `TransactionTime` and both have optional `DateTime<Utc>` params corresponding to the start-time and end-time to be queried.
+    pub fn entity_history_timed(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+    ) -> Result<EntityHistoryResponse, CruxError> {
+        let resp = self.send(endpoints::entity_history(
+            &hash, order, with_docs, false, time,
+        )?)?;
+        endpoints::parse_entity_history(resp, self.format, "entity-history-timed", &hash)
+    }
+
+    /// Function `entity_history_with_corrections` is `entity_history_timed` with the `with-corrections` flag, so every transaction that wrote a valid time is part of the history.
+    /// Each [`EntityHistoryElement`](../types/response/struct.EntityHistoryElement.html) is marked by its `coordinates`, a valid time and tx time pair.
+    pub fn entity_history_with_corrections(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+    ) -> Result<EntityHistoryResponse, CruxError> {
+        let resp = self.send(endpoints::entity_history(
+            &hash, order, with_docs, true, time,
+        )?)?;
+        endpoints::parse_entity_history(resp, self.format, "entity-history-with-corrections", &hash)
+    }
+
+    /// Function `entity_bitemporal_grid` requests the history of `hash` with corrections and documents and builds its [`BitemporalGrid`](../types/response/struct.BitemporalGrid.html), valid time × transaction time.
+    pub fn entity_bitemporal_grid(
+        &self,
+        hash: String,
+        time: Vec<TimeHistory>,
+    ) -> Result<BitemporalGrid, CruxError> {
+        Ok(self
+            .entity_history_with_corrections(hash, Order::Asc, true, time)?
+            .bitemporal_grid())
+    }
+
+    /// Function `query` requests endpoint `/query` via `POST` which retrives a Set containing a vector of the values defined by the function [`Query::find` - github example](https://github.com/naomijub/transistor/blob/master/examples/simple_query.rs#L53).
+    /// Argument is a `query` of the type `Query`.
+    pub fn query(&self, query: Query) -> Result<BTreeSet<Vec<String>>, CruxError> {
+        let resp = self.send(endpoints::query(query))?;
+        let query_response: QueryResponse = endpoints::parse_query(resp, self.format)?;
+
+        Ok(query_response.0)
+    }
+
+    /// Function `active_queries` requests endpoint `/active-queries` via `GET`, the queries currently running in the node.
+    pub fn active_queries(&self) -> Result<Vec<QueryInfo>, CruxError> {
+        let resp = self.send(endpoints::query_info("active-queries"))?;
+        endpoints::parse_query_info(resp, self.format, "active-queries")
+    }
+
+    /// Function `recent_queries` requests endpoint `/recent-queries` via `GET`, the latest queries that finished running.
+    pub fn recent_queries(&self) -> Result<Vec<QueryInfo>, CruxError> {
+        let resp = self.send(endpoints::query_info("recent-queries"))?;
+        endpoints::parse_query_info(resp, self.format, "recent-queries")
+    }
+
+    /// Function `slowest_queries` requests endpoint `/slowest-queries` via `GET`, the queries that took the longest to run.
+    pub fn slowest_queries(&self) -> Result<Vec<QueryInfo>, CruxError> {
+        let resp = self.send(endpoints::query_info("slowest-queries"))?;
+        endpoints::parse_query_info(resp, self.format, "slowest-queries")
+    }
+
+    /// Function `query_info` returns the active and recent entries of `query`, correlated with [`QueryInfo::is_query`](../types/response/struct.QueryInfo.html#method.is_query).
+    pub fn query_info(&self, query: &Query) -> Result<Vec<QueryInfo>, CruxError> {
+        let mut queries = self.active_queries()?;
+        queries.extend(self.recent_queries()?);
+        queries.retain(|info| info.is_query(query));
+
+        Ok(queries)
+    }
+
+    /// Function `query_with_tx` is like `query`, but the `query` runs against a speculative database with `actions` applied, as in Crux's `with-tx`.
+    /// Nothing is committed, so it can be used to check invariants, like uniqueness, before sending `actions` to `tx_log`.
+    pub fn query_with_tx(
+        &self,
+        query: Query,
+        actions: Actions,
+    ) -> Result<BTreeSet<Vec<String>>, CruxError> {
+        let resp = self.send(endpoints::query_with_tx(query, actions)?)?;
+        let query_response: QueryResponse = endpoints::parse_query(resp, self.format)?;
+
+        Ok(query_response.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::client::Crux;
+    use crate::testing::support::person;
+    use crate::types::http::Actions;
+    use crate::types::http::Order;
+    use crate::types::{
+        query::Query,
+        response::{EntityHistoryElement, EntityHistoryResponse, EntityTxResponse, TxLogResponse},
+        CruxId,
+    };
+    use mockito::mock;
+
+    #[test]
+    fn tx_log() {
+        let _m = mock("POST", "/tx-log")
+        .with_status(200)
+        .match_body("[[:crux.tx/put { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", }], [:crux.tx/put { :crux.db/id :manuel-1, :first-name \"Diego\", :last-name \"Manuel\", }]]")
+        .with_header("content-type", "text/plain")
+        .with_body("{:crux.tx/tx-id 8, :crux.tx/tx-time #inst \"2020-07-16T21:53:14.628-00:00\"}")
+        .create();
+
+        let person1 = person("jorge-3", "Michael", "Jorge");
+
+        let person2 = person("manuel-1", "Diego", "Manuel");
+
+        let actions = Actions::new().append_put(person1).append_put(person2);
+
+        let response = Crux::new("localhost", "4000")
+            .blocking_client()
+            .tx_log(actions);
+
+        assert_eq!(response.unwrap(), TxLogResponse::default())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn tx_log_transit() {
+        use crate::http::WireFormat;
+
+        let _m = mock("POST", "/tx-log")
+        .with_status(200)
+        .match_header("accept", "application/transit+json")
+        .match_body("[[\"~:crux.tx/put\",[\"^ \",\"~:crux.db/id\",\"~:transit-1\",\"~:first-name\",\"Michael\",\"~:last-name\",\"Transit\"]]]")
+        .with_header("content-type", "application/transit+json")
+        .with_body("[\"^ \",\"~:crux.tx/tx-id\",8,\"~:crux.tx/tx-time\",\"~t2020-07-16T21:53:14.628-00:00\"]")
+        .create();
+
+        let person = person("transit-1", "Michael", "Transit");
+
+        let response = Crux::new("localhost", "4000")
+            .with_format(WireFormat::TransitJson)
+            .blocking_client()
+            .tx_log(Actions::new().append_put(person));
+
+        assert_eq!(response.unwrap(), TxLogResponse::default())
+    }
+
+    #[test]
+    #[should_panic(expected = "TxLogActionError(\"Actions cannot be empty.\")")]
+    fn empty_actions_on_tx_log() {
+        let actions = Actions::new();
+
+        let err = Crux::new("localhost", "4000")
+            .blocking_client()
+            .tx_log(actions);
+        err.unwrap();
+    }
+
+    #[test]
+    fn tx_logs() {
+        let _m = mock("GET", "/tx-log")
+        .with_status(200)
+        .with_header("content-type", "application/edn")
+        .with_body("({:crux.tx/tx-id 0, :crux.tx/tx-time #inst \"2020-07-09T23:38:06.465-00:00\", :crux.tx.event/tx-events [[:crux.tx/put \"a15f8b81a160b4eebe5c84e9e3b65c87b9b2f18e\" \"125d29eb3bed1bf51d64194601ad4ff93defe0e2\"]]}{:crux.tx/tx-id 1, :crux.tx/tx-time #inst \"2020-07-09T23:39:33.815-00:00\", :crux.tx.event/tx-events [[:crux.tx/put \"a15f8b81a160b4eebe5c84e9e3b65c87b9b2f18e\" \"1b42e0d5137e3833423f7bb958622bee29f91eee\"]]})")
+        .create();
+
+        let response = Crux::new("localhost", "4000").blocking_client().tx_logs();
+
+        assert_eq!(response.unwrap().tx_events.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "DeserializeError(\"The following Edn cannot be deserialized to TxLogs: Symbol(\\\"Holy\\\")\")"
+    )]
+    fn tx_log_error() {
+        let _m = mock("GET", "/tx-log")
+            .with_status(200)
+            .with_header("content-type", "application/edn")
+            .with_body("Holy errors!")
+            .create();
+
+        let _error = Crux::new("localhost", "4000")
+            .blocking_client()
+            .tx_logs()
+            .unwrap();
+    }
+
+    #[test]
+    fn entity() {
+        let expected_body = "Map(Map({\":crux.db/id\": Key(\":hello-entity\"), \":first-name\": Str(\"Hello\"), \":last-name\": Str(\"World\")}))";
+        let _m = mock("POST", "/entity")
+            .with_status(200)
+            .match_body("{:eid :ivan}")
+            .with_header("content-type", "application/edn")
+            .with_body("{:crux.db/id :hello-entity :first-name \"Hello\", :last-name \"World\"}")
+            .create();
+
+        let id = CruxId::new(":ivan");
+        let edn_body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .entity(id)
+            .unwrap();
+
+        let resp = format!("{:?}", edn_body);
+        assert_eq!(resp, expected_body);
+    }
+
+    #[test]
+    fn entity_tx() {
+        let expected_body = "{:crux.db/id \"d72ccae848ce3a371bd313865cedc3d20b1478ca\", :crux.db/content-hash \"1828ebf4466f98ea3f5252a58734208cd0414376\", :crux.db/valid-time #inst \"2020-07-19T04:12:13.788-00:00\", :crux.tx/tx-time #inst \"2020-07-19T04:12:13.788-00:00\", :crux.tx/tx-id 28}";
+        let _m = mock("POST", "/entity-tx")
+            .with_status(200)
+            .match_body("{:eid :ivan}")
+            .with_header("content-type", "application/edn")
+            .with_body(expected_body)
+            .create();
+
+        let id = CruxId::new(":ivan");
+        let body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .entity_tx(id)
+            .unwrap();
+
+        assert_eq!(body, EntityTxResponse::default());
+    }
+
+    #[test]
+    fn simple_query() {
+        let expected_body = "#{[:postgres \"Postgres\" true] [:mysql \"MySQL\" true]}";
+        let _m = mock("POST", "/query")
+            .with_status(200)
+            .with_header("content-type", "application/edn")
+            .with_body(expected_body)
+            .create();
+
+        let query = Query::find(vec!["?p1", "?n", "?s"])
+            .unwrap()
+            .where_clause(vec!["?p1 :name ?n", "?p1 :is-sql ?s", "?p1 :is-sql true"])
+            .unwrap()
+            .build();
+        let body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .query(query.unwrap())
+            .unwrap();
+
+        let response = format!("{:?}", body);
+        assert_eq!(
+            response,
+            "{[\":mysql\", \"MySQL\", \"true\"], [\":postgres\", \"Postgres\", \"true\"]}"
+        );
+    }
+
+    #[test]
+    fn query_with_tx() {
+        let _m = mock("POST", "/query")
+            .with_status(200)
+            .match_body("{:query\n {:find [?p1]\n:where [[?p1 :last-name \"Speculative\"]]\n} :with-tx [[:crux.tx/put { :crux.db/id :spec-1, :first-name \"Michael\", :last-name \"Speculative\", }]]}")
+            .with_header("content-type", "application/edn")
+            .with_body("#{[:spec-1]}")
+            .create();
+
+        let person = person("spec-1", "Michael", "Speculative");
+        let query = Query::find(vec!["?p1"])
+            .unwrap()
+            .where_clause(vec!["?p1 :last-name \"Speculative\""])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .query_with_tx(query, Actions::new().append_put(person))
+            .unwrap();
+
+        assert_eq!(format!("{:?}", body), "{[\":spec-1\"]}");
+    }
+
+    #[test]
+    fn simple_query_error() {
+        let _m = mock("POST", "/query")
+            .with_status(400)
+            .with_header("content-type", "application/edn")
+            .create();
+
+        let query = Query::find(vec!["?p1", "?n", "?s"])
+            .unwrap()
+            .where_clause(vec!["?p1 :name ?n", "?p1 :is-sql ?s", "?p1 :is-sql true"])
+            .unwrap()
+            .build();
+        let body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .query(query.unwrap());
+
+        assert!(body.is_err())
+    }
+
+    #[test]
+    fn entity_history() {
+        let expected_body = "({:crux.tx/tx-time \"2020-07-19T04:12:13.788-00:00\", :crux.tx/tx-id 28, :crux.db/valid-time \"2020-07-19T04:12:13.788-00:00\", :crux.db/content-hash  \"1828ebf4466f98ea3f5252a58734208cd0414376\"})";
+        let _m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false")
+            .with_status(200)
+            .with_header("content-type", "application/edn")
+            .with_body(expected_body)
+            .create();
+
+        let edn_body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .entity_history(
+                "ecc6475b7ef9acf689f98e479d539e869432cb5e".to_string(),
+                Order::Asc,
+                false,
+            )
+            .unwrap();
+
+        let expected = EntityHistoryResponse {
+            history: vec![EntityHistoryElement::default()],
+        };
+
+        assert_eq!(edn_body, expected);
+    }
+
+    #[test]
+    fn entity_history_docs() {
+        let expected_body = "({:crux.tx/tx-time \"2020-07-19T04:12:13.788-00:00\", :crux.tx/tx-id 28, :crux.db/valid-time \"2020-07-19T04:12:13.788-00:00\", :crux.db/content-hash  \"1828ebf4466f98ea3f5252a58734208cd0414376\", :crux.db/doc :docs})";
+        let _m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=true")
+            .with_status(200)
+            .with_header("content-type", "application/edn")
+            .with_body(expected_body)
+            .create();
+
+        let edn_body = Crux::new("localhost", "3000")
+            .blocking_client()
+            .entity_history(
+                "ecc6475b7ef9acf689f98e479d539e869432cb5e".to_string(),
+                Order::Asc,
+                true,
+            )
+            .unwrap();
+
+        let expected = EntityHistoryResponse {
+            history: vec![EntityHistoryElement::default_docs()],
+        };
+
+        assert_eq!(edn_body, expected);
+    }
+}
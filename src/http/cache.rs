@@ -0,0 +1,426 @@
+#[cfg(feature = "async")]
+use crate::http::transport::{AsyncCruxTransport, TransportFuture};
+use crate::http::transport::{CruxRequest, CruxResponse, CruxTransport};
+use crate::types::error::CruxError;
+use reqwest::header::ACCEPT;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Key of a cached response, `None` if `request` does not have a fully pinned basis.
+/// Only `/entity`, `/entity-tx` and `/query` with both `valid-time` and `transaction-time` are immutable in Crux.
+fn pinned_key(request: &CruxRequest) -> Option<String> {
+    let mut parts = request.path.splitn(2, '?');
+    let endpoint = parts.next().unwrap_or_default();
+    let params = parts.next().unwrap_or_default();
+    let pinned = matches!(endpoint, "/entity" | "/entity-tx" | "/query")
+        && ["valid-time", "transaction-time"].iter().all(|param| {
+            params
+                .split('&')
+                .any(|p| p.split('=').next() == Some(*param))
+        });
+
+    if pinned {
+        let accept = request
+            .headers
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .unwrap_or_default();
+        Some(format!(
+            "{} {} {}\n{}",
+            request.method,
+            request.path,
+            accept,
+            request.body.as_deref().unwrap_or_default()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Counters of a [`CachingTransport`](struct.CachingTransport.html).
+/// `bypassed` counts requests without a fully pinned basis, which are always sent to the node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub bypassed: usize,
+    pub evictions: usize,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+struct Entry {
+    response: CruxResponse,
+    bytes: usize,
+    used: u64,
+}
+
+/// LRU of responses bounded by the size of their keys and bodies.
+struct Lru {
+    max_bytes: usize,
+    entries: HashMap<String, Entry>,
+    // `Entry::used` to key, the first one is the least recently used.
+    order: BTreeMap<u64, String>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl Lru {
+    fn get(&mut self, key: &str) -> Option<CruxResponse> {
+        self.clock += 1;
+        let clock = self.clock;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                self.order.remove(&entry.used);
+                self.order.insert(clock, key.to_string());
+                entry.used = clock;
+                self.stats.hits += 1;
+                Some(entry.response.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: String, response: CruxResponse) {
+        let bytes = key.len() + response.body.len();
+        if bytes > self.max_bytes || self.entries.contains_key(&key) {
+            return;
+        }
+        while self.stats.bytes + bytes > self.max_bytes {
+            let (_, oldest) = match self.order.pop_first() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.stats.bytes -= entry.bytes;
+                self.stats.evictions += 1;
+            }
+        }
+
+        self.clock += 1;
+        self.order.insert(self.clock, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                response,
+                bytes,
+                used: self.clock,
+            },
+        );
+        self.stats.bytes += bytes;
+        self.stats.entries = self.entries.len();
+    }
+}
+
+/// `CachingTransport` keeps the successful responses of reads with a fully pinned basis, which are immutable in Crux,
+/// and answers repeated reads without sending them to `transport`. Reads are `entity_timed`, `entity_tx_timed` and queries of a
+/// [`DbSnapshot`](struct.DbSnapshot.html) with both `valid_time` and `transaction_time`, keyed by endpoint, times, format and id or query.
+/// Every other request, including reads at the latest basis, bypasses the cache.
+///
+/// Evicts remove documents from every basis, so a successful `tx_log` with an evict clears the whole cache.
+/// Crux only evicts once the transaction is indexed, pinned reads sent before that can cache the documents again,
+/// call `clear` after `await_tx` to drop them.
+///
+/// The least recently used responses are evicted once their keys and bodies exceed `max_bytes`.
+/// Clones share the same cache.
+#[derive(Clone)]
+pub struct CachingTransport<T> {
+    transport: T,
+    cache: Arc<Mutex<Lru>>,
+}
+
+impl<T> CachingTransport<T> {
+    pub fn new(transport: T, max_bytes: usize) -> Self {
+        Self {
+            transport,
+            cache: Arc::new(Mutex::new(Lru {
+                max_bytes,
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                clock: 0,
+                stats: CacheStats::default(),
+            })),
+        }
+    }
+
+    /// Hit, miss, bypass and eviction counters, and the current size of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    /// Removes every cached response, the counters are kept.
+    pub fn clear(&self) {
+        let mut cache = self.lock();
+        cache.entries.clear();
+        cache.order.clear();
+        cache.stats.entries = 0;
+        cache.stats.bytes = 0;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Cached response of `request`, and its key if it should be cached after being sent.
+    fn lookup(&self, request: &CruxRequest) -> (Option<CruxResponse>, Option<String>) {
+        let mut cache = self.lock();
+        match pinned_key(request) {
+            Some(key) => (cache.get(&key), Some(key)),
+            None => {
+                cache.stats.bypassed += 1;
+                (None, None)
+            }
+        }
+    }
+
+    fn store(&self, key: Option<String>, evicts: bool, response: &Result<CruxResponse, CruxError>) {
+        match (key, response) {
+            (_, Ok(response)) if evicts && response.is_success() => self.clear(),
+            (Some(key), Ok(response)) if response.is_success() => {
+                self.lock().insert(key, response.clone())
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<T: CruxTransport> CruxTransport for CachingTransport<T> {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        let key = match self.lookup(&request) {
+            (Some(response), _) => return Ok(response),
+            (None, key) => key,
+        };
+        let evicts = request.evicts;
+        let response = self.transport.send(request);
+        self.store(key, evicts, &response);
+        response
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + Sync> AsyncCruxTransport for CachingTransport<T> {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let key = match self.lookup(&request) {
+                (Some(response), _) => return Ok(response),
+                (None, key) => key,
+            };
+            let evicts = request.evicts;
+            let response = self.transport.send(request).await;
+            self.store(key, evicts, &response);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Crux;
+    use crate::testing::support::person;
+    use crate::testing::FakeCrux;
+    use crate::types::{query::Query, Actions, CruxId};
+    use chrono::prelude::*;
+
+    // Puts at fixed valid times, `FakeCrux` may give consecutive transactions tx-times ahead of now.
+    fn put(
+        client: &crate::http::BlockingHttpClient<CachingTransport<FakeCrux>>,
+        name: &str,
+        day: u32,
+    ) {
+        let valid_time = Utc.with_ymd_and_hms(2020, 1, day, 0, 0, 0).unwrap();
+        client
+            .tx_log(
+                Actions::new()
+                    .append_put_timed(person("jorge-3", name, "Jorge"), valid_time.into()),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn pinned_reads_are_cached() {
+        let transport = CachingTransport::new(FakeCrux::new(), 1 << 20);
+        let client = Crux::new("localhost", "3000").blocking_client_with(transport.clone());
+        put(&client, "Michael", 1);
+        let db = client.db_at_latest().unwrap();
+        let query = Query::find(vec!["?p"])
+            .unwrap()
+            .where_clause(vec!["?p :first-name ?n"])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        for _ in 0..3 {
+            db.entity(CruxId::new("jorge-3")).unwrap();
+            db.query(query.clone()).unwrap();
+        }
+        client.entity(CruxId::new("jorge-3")).unwrap();
+
+        let stats = transport.stats();
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.hits, 4);
+        assert_eq!(stats.entries, 2);
+        // tx_log, latest_completed_tx and entity
+        assert_eq!(stats.bypassed, 3);
+    }
+
+    #[test]
+    fn latest_reads_bypass_the_cache() {
+        let transport = CachingTransport::new(FakeCrux::new(), 1 << 20);
+        let client = Crux::new("localhost", "3000").blocking_client_with(transport.clone());
+        put(&client, "Michael", 1);
+        let past = client.db_at_latest().unwrap();
+        past.entity(CruxId::new("jorge-3")).unwrap();
+        put(&client, "Mike", 2);
+
+        let latest = client.entity(CruxId::new("jorge-3")).unwrap();
+        let pinned = past.entity(CruxId::new("jorge-3")).unwrap();
+        let only_valid_time = client
+            .entity_timed(CruxId::new("jorge-3"), None, Some(Utc::now().into()))
+            .unwrap();
+
+        assert_eq!(latest[":first-name"], edn_rs::Edn::Str("Mike".to_string()));
+        assert_eq!(
+            only_valid_time[":first-name"],
+            edn_rs::Edn::Str("Mike".to_string())
+        );
+        assert_eq!(
+            pinned[":first-name"],
+            edn_rs::Edn::Str("Michael".to_string())
+        );
+        assert_eq!(transport.stats().hits, 1);
+    }
+
+    #[test]
+    fn evict_clears_the_cache() {
+        let transport = CachingTransport::new(FakeCrux::new(), 1 << 20);
+        let client = Crux::new("localhost", "3000").blocking_client_with(transport.clone());
+        put(&client, "Michael", 1);
+        let db = client.db_at_latest().unwrap();
+        db.entity(CruxId::new("jorge-3")).unwrap();
+        assert_eq!(transport.stats().entries, 1);
+
+        let tx = client
+            .tx_log(Actions::new().append_evict(CruxId::new("jorge-3")))
+            .unwrap();
+        client.await_tx(tx.tx___tx_id, None).unwrap();
+
+        assert_eq!(transport.stats().entries, 0);
+        assert!(db.entity(CruxId::new("jorge-3")).is_err());
+        assert_eq!(transport.stats().hits, 0);
+    }
+
+    // JSON node answering every request with a fixed body per endpoint, `FakeCrux` only speaks EDN.
+    #[cfg(feature = "json")]
+    #[derive(Clone)]
+    struct JsonNode;
+
+    #[cfg(feature = "json")]
+    impl CruxTransport for JsonNode {
+        fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+            let body = match request.path.split('?').next() {
+                Some("/tx-log") => {
+                    "{\"crux.tx/tx-id\": 1, \"crux.tx/tx-time\": \"2020-01-02T00:00:00.000Z\"}"
+                }
+                _ => "{\"crux.db/id\": \"jorge-3\", \"first-name\": \"Michael\"}",
+            };
+            Ok(CruxResponse::new(200, body))
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn evict_clears_the_cache_over_json() {
+        let transport = CachingTransport::new(JsonNode, 1 << 20);
+        let client = Crux::new("localhost", "3000")
+            .with_format(crate::http::WireFormat::Json)
+            .blocking_client_with(transport.clone());
+        let time = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let read = || {
+            client
+                .entity_timed(CruxId::new("jorge-3"), Some(time.into()), Some(time.into()))
+                .unwrap()
+        };
+        read();
+        read();
+        assert_eq!(transport.stats().entries, 1);
+        assert_eq!(transport.stats().hits, 1);
+
+        client
+            .tx_log(Actions::new().append_evict(CruxId::new("jorge-3")))
+            .unwrap();
+
+        assert_eq!(transport.stats().entries, 0);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let request = |id: &str| {
+            CruxRequest::post(
+                "/entity?transaction-time=2020&valid-time=2020",
+                format!("{{:eid :{}}}", id),
+            )
+        };
+        let transport = CachingTransport::new(FakeCrux::new(), 0);
+        let bytes = pinned_key(&request("a")).unwrap().len() + 3;
+        let mut cache = transport.lock();
+        cache.max_bytes = 2 * bytes;
+
+        cache.insert(
+            pinned_key(&request("a")).unwrap(),
+            CruxResponse::new(200, "{a}"),
+        );
+        cache.insert(
+            pinned_key(&request("b")).unwrap(),
+            CruxResponse::new(200, "{b}"),
+        );
+        cache.get(&pinned_key(&request("a")).unwrap());
+        cache.insert(
+            pinned_key(&request("c")).unwrap(),
+            CruxResponse::new(200, "{c}"),
+        );
+        cache.insert(
+            pinned_key(&request("d")).unwrap(),
+            CruxResponse::new(200, &"d".repeat(3 * bytes)),
+        );
+
+        assert!(cache.get(&pinned_key(&request("b")).unwrap()).is_none());
+        assert!(cache.get(&pinned_key(&request("a")).unwrap()).is_some());
+        assert!(cache.get(&pinned_key(&request("c")).unwrap()).is_some());
+        assert_eq!(cache.stats.evictions, 1);
+        assert_eq!(cache.stats.bytes, 2 * bytes);
+    }
+
+    #[test]
+    fn pinned_keys() {
+        let mut json = CruxRequest::post(
+            "/entity?transaction-time=2020&valid-time=2020",
+            "{:eid :a}".to_string(),
+        );
+        json.headers
+            .insert(ACCEPT, "application/json".parse().unwrap());
+
+        assert!(pinned_key(&CruxRequest::post("/entity", "{:eid :a}".to_string())).is_none());
+        assert!(pinned_key(&CruxRequest::post(
+            "/entity?valid-time=2020",
+            "{:eid :a}".to_string()
+        ))
+        .is_none());
+        assert!(pinned_key(&CruxRequest::get(
+            "/await-tx?transaction-time=2020&valid-time=2020"
+        ))
+        .is_none());
+        assert_ne!(
+            pinned_key(&json),
+            pinned_key(&CruxRequest::post(
+                "/entity?transaction-time=2020&valid-time=2020",
+                "{:eid :a}".to_string()
+            ))
+        );
+    }
+}
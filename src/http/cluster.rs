@@ -0,0 +1,600 @@
+#[cfg(feature = "async")]
+use crate::http::transport::{AsyncCruxTransport, AsyncReqwestTransport, TransportFuture};
+use crate::http::transport::{CruxRequest, CruxResponse, CruxTransport, Method, ReqwestTransport};
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
+use crate::http::BlockingHttpClient;
+use crate::types::error::CruxError;
+use edn_rs::Edn;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Reads spread across nodes, every other request goes to the first available node.
+fn is_read(request: &CruxRequest) -> bool {
+    let path = request.path.split('?').next().unwrap_or_default();
+    matches!(path, "/entity" | "/entity-tx" | "/query") || path.starts_with("/entity-history/")
+}
+
+/// `:crux.tx/tx-id` of a `/latest-completed-tx` response, `None` for a node without transactions.
+fn latest_tx_id(response: &CruxResponse) -> Option<usize> {
+    if !response.is_success() {
+        return None;
+    }
+    match Edn::from_str(&response.body).ok()?[":crux.tx/tx-id"] {
+        Edn::UInt(id) => Some(id),
+        Edn::Int(id) if id >= 0 => Some(id as usize),
+        _ => None,
+    }
+}
+
+/// Whether a request that failed with `error` can be sent to the next node.
+/// A failed connection never reached the node. Any other error may have happened after the node committed a `/tx-log`,
+/// so transactions are not sent again.
+fn fails_over(request: &CruxRequest, error: &CruxError) -> bool {
+    match error {
+        CruxError::ConnectionError(_) => true,
+        CruxError::RequestError(e) if e.is_connect() => true,
+        _ => !(request.method == Method::Post && request.path.starts_with("/tx-log")),
+    }
+}
+
+fn cluster_error(nodes: usize, last: Option<CruxError>) -> CruxError {
+    match last {
+        Some(e) => {
+            CruxError::ClusterError(format!("All {} nodes failed, last error: {}", nodes, e))
+        }
+        None => CruxError::ClusterError("ClusterTransport has no nodes".to_string()),
+    }
+}
+
+struct Node<T> {
+    transport: T,
+    healthy: AtomicBool,
+    indexed: Mutex<Option<usize>>,
+}
+
+impl<T> Node<T> {
+    fn indexed(&self) -> Option<usize> {
+        *self
+            .indexed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_indexed(&self, tx_id: Option<usize>) {
+        *self
+            .indexed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = tx_id;
+    }
+
+    fn has_indexed(&self, tx_id: usize) -> bool {
+        self.indexed().is_some_and(|indexed| indexed >= tx_id)
+    }
+}
+
+struct Nodes<T> {
+    nodes: Vec<Node<T>>,
+    next: AtomicUsize,
+}
+
+impl<T> Nodes<T> {
+    /// Order in which nodes are tried for `request`. Healthy nodes come first, rotated for reads and in the given order
+    /// otherwise, and unhealthy nodes last, so a request is still tried if every node is marked unhealthy.
+    /// Reads with `min_tx` try healthy nodes that have indexed it first.
+    fn order(&self, request: &CruxRequest, min_tx: Option<usize>) -> Vec<usize> {
+        let len = self.nodes.len();
+        let read = is_read(request);
+        let start = if read && len > 0 {
+            self.next.fetch_add(1, Ordering::Relaxed) % len
+        } else {
+            0
+        };
+
+        let (mut order, unhealthy): (Vec<usize>, Vec<usize>) = (0..len)
+            .map(|i| (start + i) % len)
+            .partition(|&i| self.nodes[i].healthy.load(Ordering::Relaxed));
+        if let (true, Some(tx_id)) = (read, min_tx) {
+            order.sort_by_key(|&i| !self.nodes[i].has_indexed(tx_id));
+        }
+        order.extend(unhealthy);
+        order
+    }
+
+    /// Whether `min_tx` is unknown to every healthy node, so their latest completed transactions should be requested.
+    fn stale(&self, request: &CruxRequest, min_tx: Option<usize>) -> bool {
+        match min_tx {
+            Some(tx_id) if is_read(request) => !self
+                .nodes
+                .iter()
+                .any(|node| node.healthy.load(Ordering::Relaxed) && node.has_indexed(tx_id)),
+            _ => false,
+        }
+    }
+}
+
+/// `ClusterTransport` sends the requests of a client to several Crux nodes, each reached through its own transport.
+/// - Reads, `entity`, `entity_tx`, `entity_history` and `query` with their variants, are spread round-robin across healthy nodes.
+/// - Writes, `tx_log`, and every other endpoint go to the first healthy node.
+/// - A request that fails with a transport error marks its node unhealthy and, unless it is a write that may have reached the node, is sent to the next node.
+///   HTTP error statuses are returned to the client without failover. `CruxError::ClusterError` is returned if every node fails.
+/// - [`check_health`](#method.check_health) requests `/status` and `/latest-completed-tx` of every node, marking it healthy again if it responds.
+///   [`spawn_health_checks`](#method.spawn_health_checks) runs it periodically.
+/// - [`prefer_indexed`](#method.prefer_indexed) prefers, for reads, nodes that have indexed a tx-id.
+///
+/// Clones share the nodes and their health, so health checks of one clone affect all of them.
+pub struct ClusterTransport<T = ReqwestTransport> {
+    nodes: Arc<Nodes<T>>,
+    headers: HeaderMap,
+    min_tx: Option<usize>,
+}
+
+impl<T> Clone for ClusterTransport<T> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            headers: self.headers.clone(),
+            min_tx: self.min_tx,
+        }
+    }
+}
+
+impl<T> ClusterTransport<T> {
+    /// Cluster of `nodes`, all considered healthy until a request or a health check fails.
+    pub fn new(nodes: Vec<T>) -> Self {
+        Self {
+            nodes: Arc::new(Nodes {
+                nodes: nodes
+                    .into_iter()
+                    .map(|transport| Node {
+                        transport,
+                        healthy: AtomicBool::new(true),
+                        indexed: Mutex::new(None),
+                    })
+                    .collect(),
+                next: AtomicUsize::new(0),
+            }),
+            headers: HeaderMap::new(),
+            min_tx: None,
+        }
+    }
+
+    /// `AUTHORIZATION` token of the health check requests. Requests of the clients keep their own headers.
+    pub fn with_authorization(mut self, authorization: &str) -> Self {
+        self.headers
+            .insert(AUTHORIZATION, authorization.parse().unwrap());
+        self
+    }
+
+    /// Clone of this transport whose reads prefer healthy nodes that have indexed transaction `tx_id`, like the `tx___tx_id` of a `tx_log`.
+    /// If no healthy node is known to have indexed it, their latest completed transactions are requested first.
+    /// Reads fall back to the other nodes if none has indexed it yet, use `await_tx` to wait for it.
+    pub fn prefer_indexed(&self, tx_id: usize) -> Self {
+        Self {
+            min_tx: Some(tx_id),
+            ..self.clone()
+        }
+    }
+
+    /// Indexes of the nodes currently considered healthy, in the order they were given.
+    pub fn healthy_nodes(&self) -> Vec<usize> {
+        self.nodes
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.healthy.load(Ordering::Relaxed))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Latest transaction indexed by each node, as of the last health check. `None` if unknown or the node has no transactions.
+    pub fn indexed_tx_ids(&self) -> Vec<Option<usize>> {
+        self.nodes.nodes.iter().map(Node::indexed).collect()
+    }
+
+    fn health_request(&self, path: &str) -> CruxRequest {
+        let mut request = CruxRequest::get(path);
+        request.headers = self.headers.clone();
+        request
+            .headers
+            .insert(ACCEPT, HeaderValue::from_static("application/edn"));
+        request
+    }
+}
+
+impl ClusterTransport<ReqwestTransport> {
+    /// Cluster of `ReqwestTransport`s, `uris` are node addresses like `http://crux-1:3000`.
+    pub fn from_uris(uris: &[&str]) -> Self {
+        Self::new(uris.iter().map(|uri| ReqwestTransport::new(uri)).collect())
+    }
+}
+
+impl<T: CruxTransport> ClusterTransport<T> {
+    fn refresh_indexed(&self, node: &Node<T>) -> bool {
+        let healthy = match node
+            .transport
+            .send(self.health_request("/latest-completed-tx"))
+        {
+            Ok(response) => {
+                node.set_indexed(latest_tx_id(&response));
+                response.is_success()
+            }
+            Err(_) => false,
+        };
+        node.healthy.store(healthy, Ordering::Relaxed);
+        healthy
+    }
+
+    /// Requests `/status` of every node and marks it healthy if it responds successfully, then records its latest completed transaction for `prefer_indexed`.
+    /// Returns the indexes of the healthy nodes.
+    pub fn check_health(&self) -> Vec<usize> {
+        for node in &self.nodes.nodes {
+            let up = node
+                .transport
+                .send(self.health_request("/status"))
+                .is_ok_and(|response| response.is_success());
+            if up {
+                self.refresh_indexed(node);
+            } else {
+                node.healthy.store(false, Ordering::Relaxed);
+            }
+        }
+        self.healthy_nodes()
+    }
+
+    /// Runs `check_health` every `interval` in a background thread, which stops once every clone of this transport is dropped.
+    pub fn spawn_health_checks(&self, interval: Duration) -> std::thread::JoinHandle<()>
+    where
+        T: Send + Sync + 'static,
+    {
+        let nodes: Weak<Nodes<T>> = Arc::downgrade(&self.nodes);
+        let headers = self.headers.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            match nodes.upgrade() {
+                Some(nodes) => {
+                    ClusterTransport {
+                        nodes,
+                        headers: headers.clone(),
+                        min_tx: None,
+                    }
+                    .check_health();
+                }
+                None => break,
+            }
+        })
+    }
+}
+
+impl<T: CruxTransport> CruxTransport for ClusterTransport<T> {
+    /// Sends `request` to the nodes in order until one responds.
+    /// `/tx-log` writes only fail over on `CruxError::ConnectionError`, when the node never received them. Any other
+    /// transport error is returned as is, since the node may have committed the transaction, check it with `tx_committed` or `tx_logs`.
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        if self.nodes.stale(&request, self.min_tx) {
+            for node in &self.nodes.nodes {
+                if node.healthy.load(Ordering::Relaxed) {
+                    self.refresh_indexed(node);
+                }
+            }
+        }
+
+        let order = self.nodes.order(&request, self.min_tx);
+        let mut last = None;
+        for i in order {
+            let node = &self.nodes.nodes[i];
+            match node.transport.send(request.clone()) {
+                Ok(response) => {
+                    node.healthy.store(true, Ordering::Relaxed);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    node.healthy.store(false, Ordering::Relaxed);
+                    if !fails_over(&request, &e) {
+                        return Err(e);
+                    }
+                    last = Some(e);
+                }
+            }
+        }
+        Err(cluster_error(self.nodes.nodes.len(), last))
+    }
+}
+
+#[cfg(feature = "async")]
+impl ClusterTransport<AsyncReqwestTransport> {
+    /// Cluster of `AsyncReqwestTransport`s, `uris` are node addresses like `http://crux-1:3000`.
+    pub fn from_async_uris(uris: &[&str]) -> Self {
+        Self::new(
+            uris.iter()
+                .map(|uri| AsyncReqwestTransport::new(uri))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + Sync> ClusterTransport<T> {
+    async fn refresh_indexed_async(&self, node: &Node<T>) -> bool {
+        let healthy = match node
+            .transport
+            .send(self.health_request("/latest-completed-tx"))
+            .await
+        {
+            Ok(response) => {
+                node.set_indexed(latest_tx_id(&response));
+                response.is_success()
+            }
+            Err(_) => false,
+        };
+        node.healthy.store(healthy, Ordering::Relaxed);
+        healthy
+    }
+
+    /// Async version of [`check_health`](#method.check_health). Periodic checks are left to the runtime, like a `tokio::time::interval` loop.
+    pub async fn check_health_async(&self) -> Vec<usize> {
+        for node in &self.nodes.nodes {
+            let up = node
+                .transport
+                .send(self.health_request("/status"))
+                .await
+                .is_ok_and(|response| response.is_success());
+            if up {
+                self.refresh_indexed_async(node).await;
+            } else {
+                node.healthy.store(false, Ordering::Relaxed);
+            }
+        }
+        self.healthy_nodes()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + Send + Sync> AsyncCruxTransport for ClusterTransport<T> {
+    /// Async version of the blocking `send`, `/tx-log` writes only fail over on `CruxError::ConnectionError`.
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            if self.nodes.stale(&request, self.min_tx) {
+                for node in &self.nodes.nodes {
+                    if node.healthy.load(Ordering::Relaxed) {
+                        self.refresh_indexed_async(node).await;
+                    }
+                }
+            }
+
+            let order = self.nodes.order(&request, self.min_tx);
+            let mut last = None;
+            for i in order {
+                let node = &self.nodes.nodes[i];
+                match node.transport.send(request.clone()).await {
+                    Ok(response) => {
+                        node.healthy.store(true, Ordering::Relaxed);
+                        return Ok(response);
+                    }
+                    Err(e) => {
+                        node.healthy.store(false, Ordering::Relaxed);
+                        if !fails_over(&request, &e) {
+                            return Err(e);
+                        }
+                        last = Some(e);
+                    }
+                }
+            }
+            Err(cluster_error(self.nodes.nodes.len(), last))
+        })
+    }
+}
+
+impl<T: CruxTransport> BlockingHttpClient<ClusterTransport<T>> {
+    /// Client whose reads prefer nodes that have indexed transaction `tx_id`, see [`ClusterTransport::prefer_indexed`](struct.ClusterTransport.html#method.prefer_indexed).
+    pub fn prefer_indexed(&self, tx_id: usize) -> Self {
+        Self {
+            transport: self.transport.prefer_indexed(tx_id),
+            headers: self.headers.clone(),
+            format: self.format,
+            middleware: self.middleware.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + Send + Sync> AsyncHttpClient<ClusterTransport<T>> {
+    /// Client whose reads prefer nodes that have indexed transaction `tx_id`, see [`ClusterTransport::prefer_indexed`](struct.ClusterTransport.html#method.prefer_indexed).
+    pub fn prefer_indexed(&self, tx_id: usize) -> Self {
+        Self {
+            transport: self.transport.prefer_indexed(tx_id),
+            headers: self.headers.clone(),
+            format: self.format,
+            middleware: self.middleware.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Crux;
+    use crate::testing::support::person;
+    use crate::testing::FakeCrux;
+    use crate::types::{Actions, CruxId};
+    use edn_rs::Edn;
+
+    // `FakeCrux` node that can be taken down, or time out after handling requests, and counts the requests it answered.
+    #[derive(Clone, Default)]
+    struct Node {
+        fake: FakeCrux,
+        down: Arc<AtomicBool>,
+        times_out: Arc<AtomicBool>,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Node {
+        fn with(fake: &FakeCrux) -> Self {
+            Self {
+                fake: fake.clone(),
+                ..Self::default()
+            }
+        }
+
+        fn requests(&self, path: &str) -> usize {
+            self.requests
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|p| p.as_str() == path)
+                .count()
+        }
+    }
+
+    impl CruxTransport for Node {
+        fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(CruxError::ConnectionError("connection refused".to_string()));
+            }
+            self.requests.lock().unwrap().push(request.path.clone());
+            let response = CruxTransport::send(&self.fake, request);
+            if self.times_out.load(Ordering::SeqCst) {
+                return Err(CruxError::TransportError("timed out".to_string()));
+            }
+            response
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncCruxTransport for Node {
+        fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+            let response = CruxTransport::send(self, request);
+            Box::pin(async move { response })
+        }
+    }
+
+    fn cluster(nodes: &[Node]) -> BlockingHttpClient<ClusterTransport<Node>> {
+        Crux::new("localhost", "3000").blocking_client_with(ClusterTransport::new(nodes.to_vec()))
+    }
+
+    #[test]
+    fn reads_are_balanced_and_writes_go_to_first_node() {
+        let fake = FakeCrux::new();
+        let nodes = vec![Node::with(&fake), Node::with(&fake), Node::with(&fake)];
+        let client = cluster(&nodes);
+
+        client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap();
+        for _ in 0..6 {
+            client.entity(CruxId::new("jorge-3")).unwrap();
+        }
+
+        assert_eq!(nodes[0].requests("/tx-log"), 1);
+        assert!(nodes.iter().all(|node| node.requests("/entity") == 2));
+    }
+
+    #[test]
+    fn fails_over_and_recovers_with_health_checks() {
+        let fake = FakeCrux::new();
+        let nodes = vec![Node::with(&fake), Node::with(&fake)];
+        let client = cluster(&nodes);
+        nodes[0].down.store(true, Ordering::SeqCst);
+
+        let tx = client.tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")));
+        let entity = client.entity(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(tx.unwrap().tx___tx_id, 0);
+        assert_eq!(entity[":first-name"], Edn::Str("Michael".to_string()));
+        assert_eq!(client.transport.healthy_nodes(), vec![1]);
+        assert_eq!(client.transport.check_health(), vec![1]);
+
+        nodes[0].down.store(false, Ordering::SeqCst);
+        assert_eq!(client.transport.check_health(), vec![0, 1]);
+        assert_eq!(nodes[0].requests("/status"), 1);
+        assert_eq!(client.transport.indexed_tx_ids(), vec![Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn tx_log_is_not_resent_after_a_timeout() {
+        let fake = FakeCrux::new();
+        let nodes = vec![Node::with(&fake), Node::with(&fake)];
+        let client = cluster(&nodes);
+        nodes[0].times_out.store(true, Ordering::SeqCst);
+
+        let err = client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap_err();
+        let entity = client.entity(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(format!("{}", err), "timed out");
+        assert_eq!(nodes[1].requests("/tx-log"), 0);
+        assert_eq!(fake.client().tx_logs().unwrap().tx_events.len(), 1);
+        assert_eq!(entity[":first-name"], Edn::Str("Michael".to_string()));
+        assert_eq!(client.transport.healthy_nodes(), vec![1]);
+    }
+
+    #[test]
+    fn every_node_down() {
+        let nodes = vec![Node::default(), Node::default()];
+        let client = cluster(&nodes);
+        nodes
+            .iter()
+            .for_each(|node| node.down.store(true, Ordering::SeqCst));
+
+        let err = client.tx_logs().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "All 2 nodes failed, last error: connection refused"
+        );
+        assert!(client.transport.healthy_nodes().is_empty());
+        assert!(cluster(&[]).tx_logs().is_err());
+    }
+
+    #[test]
+    fn prefers_nodes_that_indexed_tx() {
+        let lagging = Node::default();
+        let indexed = Node::default();
+        let tx = indexed
+            .fake
+            .client()
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap();
+        let client = cluster(&[lagging.clone(), indexed.clone()]);
+
+        let consistent = client.prefer_indexed(tx.tx___tx_id);
+        for _ in 0..4 {
+            consistent.entity(CruxId::new("jorge-3")).unwrap();
+        }
+        client.entity(CruxId::new("jorge-3")).unwrap_err();
+
+        assert_eq!(indexed.requests("/entity"), 4);
+        assert_eq!(lagging.requests("/entity"), 1);
+        assert_eq!(lagging.requests("/latest-completed-tx"), 1);
+        assert_eq!(client.transport.indexed_tx_ids(), vec![None, Some(0)]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_failover() {
+        let fake = FakeCrux::new();
+        let nodes = vec![Node::with(&fake), Node::with(&fake)];
+        let client =
+            Crux::new("localhost", "3000").async_client_with(ClusterTransport::new(nodes.clone()));
+        nodes[0].down.store(true, Ordering::SeqCst);
+
+        let tx = client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .await
+            .unwrap();
+        let entity = client
+            .prefer_indexed(tx.tx___tx_id)
+            .entity(CruxId::new("jorge-3"))
+            .await
+            .unwrap();
+
+        assert_eq!(entity[":first-name"], Edn::Str("Michael".to_string()));
+        assert_eq!(client.transport.healthy_nodes(), vec![1]);
+        nodes[0].down.store(false, Ordering::SeqCst);
+        assert_eq!(client.transport.check_health_async().await, vec![0, 1]);
+    }
+}
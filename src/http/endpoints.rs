@@ -0,0 +1,343 @@
+use crate::http::transport::{CruxRequest, CruxResponse};
+use crate::http::{build_timed_url, entity_body, WireFormat};
+#[cfg(feature = "json")]
+use crate::types::edn_text;
+use crate::types::{
+    error::CruxError,
+    http::{Actions, Order, TimeHistory},
+    query::Query,
+    response::{
+        EntityHistoryResponse, EntityTxResponse, QueryInfo, QueryInfoResponse, TxLogResponse,
+        TxLogsResponse,
+    },
+    CruxId,
+};
+use chrono::prelude::*;
+use edn_rs::{Deserialize, Edn, Serialize};
+use std::time::Duration;
+
+// Requests and response parsing shared by `BlockingHttpClient` and `AsyncHttpClient`.
+// Clients only differ in how the `CruxRequest` is sent.
+// Request bodies are built as EDN and encoded by the client, responses are decoded with the client's `WireFormat`.
+
+fn decode_edn(format: WireFormat, body: &str) -> Result<Edn, CruxError> {
+    // `#inst` and `#crux/id` values are read as plain strings by the response types.
+    format.decode(&body.replace("#crux/id", "").replace("#inst", ""))
+}
+
+fn decode<T: Deserialize>(format: WireFormat, body: &str) -> Result<T, CruxError> {
+    edn_rs::from_edn(&decode_edn(format, body)?).map_err(|e| e.into())
+}
+
+pub(crate) fn tx_log(actions: Actions) -> Result<CruxRequest, CruxError> {
+    actions.validate()?;
+
+    let evicts = actions.has_evict();
+    let mut request = CruxRequest::post("/tx-log", actions.build());
+    request.evicts = evicts;
+    Ok(request)
+}
+
+pub(crate) fn parse_tx_log(
+    resp: CruxResponse,
+    format: WireFormat,
+) -> Result<TxLogResponse, CruxError> {
+    if resp.is_success() {
+        decode(format, &resp.body)
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "tx_log responded with {}",
+            resp.status,
+        )))
+    }
+}
+
+pub(crate) fn await_tx(tx_id: usize, timeout: Option<Duration>) -> CruxRequest {
+    let timeout = timeout
+        .map(|t| format!("&timeout={}", t.as_millis()))
+        .unwrap_or_default();
+    CruxRequest::get(&format!("/await-tx?tx-id={}{}", tx_id, timeout))
+}
+
+pub(crate) fn parse_await_tx(
+    resp: CruxResponse,
+    format: WireFormat,
+) -> Result<TxLogResponse, CruxError> {
+    if resp.is_success() {
+        decode(format, &resp.body)
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "await_tx responded with {}",
+            resp.status,
+        )))
+    }
+}
+
+pub(crate) fn tx_committed(tx_id: usize) -> CruxRequest {
+    CruxRequest::get(&format!("/tx-committed?tx-id={}", tx_id))
+}
+
+pub(crate) fn parse_tx_committed(
+    resp: CruxResponse,
+    format: WireFormat,
+) -> Result<bool, CruxError> {
+    if resp.is_success() {
+        match decode_edn(format, &resp.body)? {
+            Edn::Bool(committed) => Ok(committed),
+            edn => Err(CruxError::ResponseFailed(format!(
+                "tx_committed responded with {}",
+                edn
+            ))),
+        }
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "tx_committed responded with {}",
+            resp.status,
+        )))
+    }
+}
+
+// `Match` of the `current` document of `id` followed by the `Put` of its update, the transaction is discarded if `id` changed in between.
+// `current` is the EDN of the document as stored, see `match_document`.
+pub(crate) fn update_entity<T: Serialize>(id: CruxId, current: String, update: T) -> Actions {
+    Actions::new()
+        .append_match_doc(id, RawEdn(current))
+        .append_put(update)
+}
+
+// EDN of the document in an `/entity` response body, to be matched by `update_entity`. EDN bodies are used as is because the `Edn` returned by
+// `entity` has no `#inst` and `#crux/id` tags. Other formats are decoded and written back as EDN.
+pub(crate) fn match_document(body: &str, format: WireFormat) -> Result<String, CruxError> {
+    match format {
+        WireFormat::Edn => Ok(body.trim().to_string()),
+        #[cfg(feature = "json")]
+        _ => Ok(edn_text(&format.decode(body)?)),
+    }
+}
+
+// Document already in EDN, like the response of `entity`.
+struct RawEdn(String);
+
+impl Serialize for RawEdn {
+    fn serialize(self) -> String {
+        self.0
+    }
+}
+
+pub(crate) fn update_conflict(id: &CruxId, attempts: usize) -> CruxError {
+    CruxError::Conflict(format!(
+        "update_entity of {} was not committed after {} attempts, the entity kept changing",
+        edn_rs::to_string(id.clone()),
+        attempts
+    ))
+}
+
+// Error of batch `index`, with the index and transaction of every batch committed, before or concurrently with it.
+pub(crate) fn batch_error(
+    index: usize,
+    total: usize,
+    committed: Vec<(usize, TxLogResponse)>,
+    error: CruxError,
+) -> CruxError {
+    let message = format!(
+        "Batch at index {} of {} batches failed: {}. Committed batch indexes and tx-ids: {:?}, batches after the failed one may have been committed when sent concurrently",
+        index,
+        total,
+        error,
+        committed
+            .iter()
+            .map(|(batch, tx)| (*batch, tx.tx___tx_id))
+            .collect::<Vec<(usize, usize)>>(),
+    );
+    CruxError::TxLogBatchError {
+        message,
+        failed: index,
+        committed,
+    }
+}
+
+// `/active-queries`, `/recent-queries` or `/slowest-queries`.
+pub(crate) fn query_info(name: &str) -> CruxRequest {
+    CruxRequest::get(&format!("/{}", name))
+}
+
+pub(crate) fn parse_query_info(
+    resp: CruxResponse,
+    format: WireFormat,
+    name: &str,
+) -> Result<Vec<QueryInfo>, CruxError> {
+    if resp.is_success() {
+        let QueryInfoResponse(queries) = decode(format, &resp.body)?;
+        Ok(queries)
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "{} responded with {}",
+            name, resp.status,
+        )))
+    }
+}
+
+pub(crate) fn tx_logs() -> CruxRequest {
+    CruxRequest::get("/tx-log")
+}
+
+pub(crate) fn parse_tx_logs(
+    resp: CruxResponse,
+    format: WireFormat,
+) -> Result<TxLogsResponse, CruxError> {
+    if resp.is_success() {
+        decode(format, &resp.body)
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "tx_logs responded with {}",
+            resp.status,
+        )))
+    }
+}
+
+pub(crate) fn latest_completed_tx() -> CruxRequest {
+    CruxRequest::get("/latest-completed-tx")
+}
+
+pub(crate) fn parse_latest_completed_tx(
+    resp: CruxResponse,
+    format: WireFormat,
+) -> Result<Option<TxLogResponse>, CruxError> {
+    if resp.is_success() {
+        match decode_edn(format, &resp.body)? {
+            Edn::Nil => Ok(None),
+            edn => edn_rs::from_edn(&edn).map(Some).map_err(|e| e.into()),
+        }
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "latest_completed_tx responded with {}",
+            resp.status,
+        )))
+    }
+}
+
+pub(crate) fn entity(
+    endpoint: &str,
+    crux_id: &str,
+    transaction_time: Option<DateTime<FixedOffset>>,
+    valid_time: Option<DateTime<FixedOffset>>,
+) -> CruxRequest {
+    let path = build_timed_url(String::new(), endpoint, transaction_time, valid_time);
+    CruxRequest::post(&path, entity_body(crux_id))
+}
+
+pub(crate) fn parse_entity(
+    resp: CruxResponse,
+    format: WireFormat,
+    name: &str,
+    crux_id: &str,
+) -> Result<Edn, CruxError> {
+    if resp.is_success() {
+        decode_edn(format, &resp.body).map_err(|_| {
+            CruxError::ResponseFailed(format!(
+                "{} responded with {} for id \"{}\" ",
+                name, 500, crux_id
+            ))
+        })
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "{} responded with {} for id \"{}\" ",
+            name, resp.status, crux_id
+        )))
+    }
+}
+
+pub(crate) fn parse_entity_tx(
+    resp: CruxResponse,
+    format: WireFormat,
+    name: &str,
+    crux_id: &str,
+) -> Result<EntityTxResponse, CruxError> {
+    if resp.is_success() {
+        decode(format, &resp.body)
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "{} responded with {} for id \"{}\" ",
+            name, resp.status, crux_id
+        )))
+    }
+}
+
+pub(crate) fn entity_history(
+    hash: &str,
+    order: Order,
+    with_docs: bool,
+    with_corrections: bool,
+    time: Vec<TimeHistory>,
+) -> Result<CruxRequest, CruxError> {
+    let time = TimeHistory::to_params(time, order)?;
+    let corrections = if with_corrections {
+        "&with-corrections=true"
+    } else {
+        ""
+    };
+    Ok(CruxRequest::get(&format!(
+        "/entity-history/{}?sort-order={}&with-docs={}{}{}",
+        hash,
+        edn_rs::to_string(order),
+        with_docs,
+        corrections,
+        time
+    )))
+}
+
+pub(crate) fn parse_entity_history(
+    resp: CruxResponse,
+    format: WireFormat,
+    name: &str,
+    hash: &str,
+) -> Result<EntityHistoryResponse, CruxError> {
+    if resp.is_success() {
+        decode(format, &resp.body)
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "{} responded with {} for hash \"{}\" ",
+            name, resp.status, hash
+        )))
+    }
+}
+
+pub(crate) fn query(query: Query) -> CruxRequest {
+    query_timed(query, None, None)
+}
+
+pub(crate) fn query_timed(
+    query: Query,
+    transaction_time: Option<DateTime<FixedOffset>>,
+    valid_time: Option<DateTime<FixedOffset>>,
+) -> CruxRequest {
+    let path = build_timed_url(String::new(), "query", transaction_time, valid_time);
+    CruxRequest::post(&path, edn_rs::to_string(query))
+}
+
+// `{:query {...} :with-tx [...]}`, the operations are applied to a speculative db that is discarded after the query.
+pub(crate) fn query_with_tx(query: Query, actions: Actions) -> Result<CruxRequest, CruxError> {
+    actions.validate()?;
+
+    let body = format!(
+        "{{:query\n {} :with-tx {}}}",
+        query.query_map(),
+        actions.build()
+    );
+    Ok(CruxRequest::post("/query", body))
+}
+
+pub(crate) fn parse_query<T: Deserialize>(
+    resp: CruxResponse,
+    format: WireFormat,
+) -> Result<T, CruxError> {
+    if resp.is_success() {
+        let edn = format.decode(&resp.body)?;
+        edn_rs::from_edn(&edn).map_err(|e| e.into())
+    } else {
+        Err(CruxError::BadResponse(format!(
+            "query responded with {}",
+            resp.status,
+        )))
+    }
+}
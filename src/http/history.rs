@@ -0,0 +1,478 @@
+use crate::http::endpoints;
+use crate::http::transport::{CruxRequest, CruxResponse};
+#[cfg(feature = "async")]
+use crate::http::{AsyncCruxTransport, AsyncHttpClient};
+use crate::http::{BlockingHttpClient, CruxTransport, WireFormat};
+use crate::types::{
+    error::CruxError,
+    http::{Order, TimeHistory},
+    response::{EntityHistoryElement, EntityHistoryResponse},
+};
+use chrono::prelude::*;
+use chrono::Duration;
+use std::collections::VecDeque;
+
+/// Valid time window of a page, start inclusive and end exclusive in the order of the history, like the params of Crux.
+/// The start of a descending window is its latest time. `None` is an open bound.
+type Window = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Cursor shared by the blocking and async history pagers.
+/// Pages are consecutive valid time windows of `page` length, walked in `order` from the start valid time to the end valid time.
+/// Times after now of an open ascending end or an open descending start are fetched in a single page,
+/// as is an open descending end once a window comes back empty.
+struct HistoryCursor {
+    hash: String,
+    order: Order,
+    with_docs: bool,
+    with_corrections: bool,
+    transaction_time: Option<TimeHistory>,
+    page: Duration,
+    cursor: DateTime<Utc>,
+    limit: Option<DateTime<Utc>>,
+    after_now: bool,
+    empty_window: bool,
+    done: bool,
+}
+
+impl HistoryCursor {
+    fn new(
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+        page: Duration,
+    ) -> Result<Self, CruxError> {
+        TimeHistory::to_params(time.clone(), order)?;
+        if page <= Duration::zero() {
+            return Err(CruxError::TimeHistoryError(format!(
+                "Page length must be positive, found {}",
+                page
+            )));
+        }
+
+        let (start, end) = time
+            .iter()
+            .find_map(|t| match t {
+                TimeHistory::ValidTime(start, end) => Some((*start, *end)),
+                TimeHistory::TransactionTime(..) => None,
+            })
+            .unwrap_or((None, None));
+        let transaction_time = time
+            .into_iter()
+            .find(|t| matches!(t, TimeHistory::TransactionTime(..)));
+        let now = Utc::now();
+        let (cursor, limit, after_now) = match order {
+            Order::Asc => (
+                start.ok_or_else(|| {
+                    CruxError::TimeHistoryError(
+                        "Ascending entity history requires a start valid time".to_string(),
+                    )
+                })?,
+                Some(end.unwrap_or(now)),
+                end.is_none(),
+            ),
+            // the start of a descending history is its latest valid time
+            Order::Desc => (start.unwrap_or(now), end, start.is_none()),
+        };
+
+        Ok(Self {
+            hash,
+            order,
+            with_docs,
+            with_corrections: false,
+            transaction_time,
+            page,
+            cursor,
+            limit,
+            after_now,
+            empty_window: false,
+            done: false,
+        })
+    }
+
+    fn next_window(&mut self) -> Option<Window> {
+        if self.done {
+            return None;
+        }
+        match self.order {
+            Order::Asc => {
+                let limit = self.limit.unwrap_or(self.cursor);
+                if self.cursor < limit {
+                    let start = self.cursor;
+                    self.cursor = std::cmp::min(start + self.page, limit);
+                    Some((Some(start), Some(self.cursor)))
+                } else if self.after_now {
+                    self.done = true;
+                    Some((Some(self.cursor), None))
+                } else {
+                    self.done = true;
+                    None
+                }
+            }
+            Order::Desc => {
+                if self.after_now {
+                    self.after_now = false;
+                    return Some((None, Some(self.cursor)));
+                }
+                match self.limit {
+                    Some(limit) if self.cursor > limit => {
+                        let start = self.cursor;
+                        self.cursor = std::cmp::max(start - self.page, limit);
+                        Some((Some(start), Some(self.cursor)))
+                    }
+                    Some(_) => {
+                        self.done = true;
+                        None
+                    }
+                    None if self.empty_window => {
+                        self.done = true;
+                        Some((Some(self.cursor), None))
+                    }
+                    None => {
+                        let start = self.cursor;
+                        self.cursor = start - self.page;
+                        Some((Some(start), Some(self.cursor)))
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_request(&mut self) -> Option<Result<CruxRequest, CruxError>> {
+        let (start, end) = self.next_window()?;
+        let mut time = vec![TimeHistory::ValidTime(start, end)];
+        time.extend(self.transaction_time.clone());
+
+        Some(endpoints::entity_history(
+            &self.hash,
+            self.order,
+            self.with_docs,
+            self.with_corrections,
+            time,
+        ))
+    }
+
+    fn parse(
+        &mut self,
+        resp: Result<CruxResponse, CruxError>,
+        format: WireFormat,
+    ) -> Result<Vec<EntityHistoryElement>, CruxError> {
+        let history = resp
+            .and_then(|resp| {
+                endpoints::parse_entity_history(resp, format, "entity-history-iter", &self.hash)
+            })
+            .map(|h: EntityHistoryResponse| h.history);
+        match &history {
+            Ok(h) => self.empty_window = h.is_empty(),
+            Err(_) => self.done = true,
+        }
+        history
+    }
+}
+
+/// `EntityHistoryIter` is a lazy iterator over the history of an entity, created by
+/// [`BlockingHttpClient::entity_history_iter`](struct.BlockingHttpClient.html#method.entity_history_iter).
+/// Each page requests `/entity-history` for one valid time window, so long histories are never loaded at once.
+/// Iteration stops after the first error.
+pub struct EntityHistoryIter<'a, T: CruxTransport> {
+    client: &'a BlockingHttpClient<T>,
+    cursor: HistoryCursor,
+    buffer: VecDeque<EntityHistoryElement>,
+}
+
+impl<T: CruxTransport> BlockingHttpClient<T> {
+    /// Function `entity_history_iter` returns an [`EntityHistoryIter`](struct.EntityHistoryIter.html) over the history of `hash`,
+    /// requesting valid time windows of length `page` in `order`. `time` accepts the same [`TimeHistory`](../types/http/enum.TimeHistory.html) ranges as `entity_history_timed`.
+    /// * `Order::Asc` requires a start valid time and walks from it to the end valid time. An open end is walked up to now and the rest is fetched in one page.
+    /// * `Order::Desc` walks from the start valid time, or now, down to the end valid time, since a descending history starts at its latest time like in Crux.
+    ///   An open start first fetches the times after now in one page, and an open end ends with the remaining history once a window is empty.
+    ///
+    /// Returns `CruxError::TimeHistoryError` if `time` has repeated or inverted ranges or if `page` is not positive.
+    pub fn entity_history_iter(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+        page: Duration,
+    ) -> Result<EntityHistoryIter<'_, T>, CruxError> {
+        Ok(EntityHistoryIter {
+            client: self,
+            cursor: HistoryCursor::new(hash, order, with_docs, time, page)?,
+            buffer: VecDeque::new(),
+        })
+    }
+}
+
+impl<'a, T: CruxTransport> EntityHistoryIter<'a, T> {
+    /// Includes every correction of a valid time, `with-corrections=true`, instead of only the latest one.
+    pub fn with_corrections(mut self, with_corrections: bool) -> Self {
+        self.cursor.with_corrections = with_corrections;
+        self
+    }
+}
+
+impl<'a, T: CruxTransport> Iterator for EntityHistoryIter<'a, T> {
+    type Item = Result<EntityHistoryElement, CruxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(element) = self.buffer.pop_front() {
+                return Some(Ok(element));
+            }
+            let resp = self
+                .cursor
+                .next_request()?
+                .and_then(|req| self.client.send(req));
+            match self.cursor.parse(resp, self.client.format) {
+                Ok(history) => self.buffer.extend(history),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// `AsyncEntityHistoryPages` pages through the history of an entity like [`EntityHistoryIter`](struct.EntityHistoryIter.html),
+/// one valid time window per `next_page`. Feature `async` is required.
+#[cfg(feature = "async")]
+pub struct AsyncEntityHistoryPages<'a, T: AsyncCruxTransport> {
+    client: &'a AsyncHttpClient<T>,
+    cursor: HistoryCursor,
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport> AsyncHttpClient<T> {
+    /// Function `entity_history_pages` returns an [`AsyncEntityHistoryPages`](struct.AsyncEntityHistoryPages.html), with the same arguments and errors as
+    /// [`BlockingHttpClient::entity_history_iter`](struct.BlockingHttpClient.html#method.entity_history_iter).
+    pub fn entity_history_pages(
+        &self,
+        hash: String,
+        order: Order,
+        with_docs: bool,
+        time: Vec<TimeHistory>,
+        page: Duration,
+    ) -> Result<AsyncEntityHistoryPages<'_, T>, CruxError> {
+        Ok(AsyncEntityHistoryPages {
+            client: self,
+            cursor: HistoryCursor::new(hash, order, with_docs, time, page)?,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T: AsyncCruxTransport> AsyncEntityHistoryPages<'a, T> {
+    /// Includes every correction of a valid time, `with-corrections=true`, instead of only the latest one.
+    pub fn with_corrections(mut self, with_corrections: bool) -> Self {
+        self.cursor.with_corrections = with_corrections;
+        self
+    }
+
+    /// Requests the next valid time window, `None` once the range is exhausted or after an error. Pages can be empty.
+    pub async fn next_page(&mut self) -> Option<Result<Vec<EntityHistoryElement>, CruxError>> {
+        let resp = match self.cursor.next_request()? {
+            Ok(req) => self.client.send(req).await,
+            Err(e) => Err(e),
+        };
+        Some(self.cursor.parse(resp, self.client.format))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::support::person;
+    use crate::testing::FakeCrux;
+    use crate::types::{
+        error::CruxError,
+        http::{Order, TimeHistory},
+        Actions, CruxId,
+    };
+    use chrono::prelude::*;
+    use chrono::Duration;
+    use edn_rs::Edn;
+
+    fn day(day: i64) -> DateTime<Utc> {
+        "2020-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::days(day)
+    }
+
+    fn history_client() -> (
+        crate::http::BlockingHttpClient<crate::testing::FakeCrux>,
+        String,
+    ) {
+        let client = FakeCrux::new().client();
+        let actions = (0..10).fold(Actions::new(), |actions, d| {
+            actions.append_put_timed(
+                person("jorge-3", &format!("v{}", d), "Jorge"),
+                day(d * 10).into(),
+            )
+        });
+        client.tx_log(actions).unwrap();
+        let hash = client.entity_tx(CruxId::new("jorge-3")).unwrap().db___id;
+        (client, hash)
+    }
+
+    fn names(
+        history: Vec<Result<crate::types::response::EntityHistoryElement, CruxError>>,
+    ) -> Vec<String> {
+        history
+            .into_iter()
+            .map(|e| match &e.unwrap().db__doc.unwrap()[":first-name"] {
+                Edn::Str(s) => s.clone(),
+                edn => panic!("{:?}", edn),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn desc_iterates_whole_history() {
+        let (client, hash) = history_client();
+        client
+            .tx_log(Actions::new().append_put_timed(
+                person("jorge-3", "future", "Jorge"),
+                (Utc::now() + Duration::days(3)).into(),
+            ))
+            .unwrap();
+
+        let history = client
+            .entity_history_iter(hash, Order::Desc, true, Vec::new(), Duration::days(7))
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        let mut expected = (0..10).map(|d| format!("v{}", d)).collect::<Vec<_>>();
+        expected.push("future".to_string());
+        expected.reverse();
+        assert_eq!(names(history), expected);
+    }
+
+    #[test]
+    fn asc_iterates_valid_time_range() {
+        let (client, hash) = history_client();
+
+        let history = client
+            .entity_history_iter(
+                hash,
+                Order::Asc,
+                true,
+                vec![TimeHistory::ValidTime(Some(day(25)), Some(day(65)))],
+                Duration::days(15),
+            )
+            .unwrap()
+            .collect::<Vec<_>>();
+
+        assert_eq!(names(history), vec!["v3", "v4", "v5", "v6"]);
+    }
+
+    #[test]
+    fn desc_iterates_from_start_down_to_end() {
+        let (client, hash) = history_client();
+
+        let range = client
+            .entity_history_iter(
+                hash.clone(),
+                Order::Desc,
+                true,
+                vec![TimeHistory::ValidTime(Some(day(65)), Some(day(25)))],
+                Duration::days(15),
+            )
+            .unwrap()
+            .collect::<Vec<_>>();
+        let open_end = client
+            .entity_history_iter(
+                hash.clone(),
+                Order::Desc,
+                true,
+                vec![TimeHistory::ValidTime(Some(day(45)), None)],
+                Duration::days(15),
+            )
+            .unwrap()
+            .collect::<Vec<_>>();
+        let timed = client
+            .entity_history_timed(
+                hash,
+                Order::Desc,
+                false,
+                vec![TimeHistory::ValidTime(Some(day(40)), Some(day(10)))],
+            )
+            .unwrap();
+
+        assert_eq!(names(range), vec!["v6", "v5", "v4", "v3"]);
+        assert_eq!(names(open_end), vec!["v4", "v3", "v2", "v1", "v0"]);
+        assert_eq!(
+            timed
+                .history
+                .iter()
+                .map(|e| e.db___valid_time.to_utc().unwrap())
+                .collect::<Vec<_>>(),
+            vec![day(40), day(30), day(20)]
+        );
+    }
+
+    #[test]
+    fn asc_requires_start() {
+        let (client, hash) = history_client();
+
+        assert!(matches!(
+            client.entity_history_iter(hash, Order::Asc, false, Vec::new(), Duration::days(1)),
+            Err(CruxError::TimeHistoryError(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_time_history() {
+        let (client, hash) = history_client();
+
+        assert!(client
+            .entity_history_timed(
+                hash.clone(),
+                Order::Asc,
+                false,
+                vec![
+                    TimeHistory::ValidTime(None, None),
+                    TimeHistory::ValidTime(Some(day(3)), None),
+                ],
+            )
+            .is_err());
+        assert!(client
+            .entity_history_iter(
+                hash.clone(),
+                Order::Desc,
+                false,
+                vec![TimeHistory::TransactionTime(Some(day(1)), Some(day(3)))],
+                Duration::days(1),
+            )
+            .is_err());
+        assert!(client
+            .entity_history_iter(hash, Order::Desc, false, Vec::new(), Duration::zero())
+            .is_err());
+    }
+
+    #[test]
+    fn with_corrections() {
+        let (client, hash) = history_client();
+        client
+            .tx_log(
+                Actions::new()
+                    .append_put_timed(person("jorge-3", "v9 corrected", "Jorge"), day(90).into()),
+            )
+            .unwrap();
+        let time = vec![TimeHistory::ValidTime(Some(day(85)), None)];
+
+        let latest = client
+            .entity_history_iter(
+                hash.clone(),
+                Order::Asc,
+                true,
+                time.clone(),
+                Duration::days(30),
+            )
+            .unwrap()
+            .collect::<Vec<_>>();
+        let corrections = client
+            .entity_history_iter(hash, Order::Asc, true, time, Duration::days(30))
+            .unwrap()
+            .with_corrections(true)
+            .collect::<Vec<_>>();
+
+        assert_eq!(names(latest), vec!["v9 corrected"]);
+        assert_eq!(names(corrections), vec!["v9", "v9 corrected"]);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::http::transport::{CruxRequest, CruxResponse};
+use crate::types::error::CruxError;
+use reqwest::header::{HeaderName, HeaderValue};
+use std::sync::Arc;
+
+/// `Middleware` hooks run by `BlockingHttpClient` and `AsyncHttpClient` for every request, added with
+/// [`Crux::with_middleware`](../../client/struct.Crux.html#method.with_middleware).
+/// `before_send` hooks run in the order they were added and `after_receive` hooks in reverse order.
+/// An error in any hook is returned by the client call.
+pub trait Middleware: Send + Sync {
+    /// Runs before `request` is sent, after the client headers are added and its body is encoded in the `WireFormat`.
+    fn before_send(&self, _request: &mut CruxRequest) -> Result<(), CruxError> {
+        Ok(())
+    }
+
+    /// Runs after `response` to `request` is received and before it is parsed. It does not run if the transport fails.
+    fn after_receive(
+        &self,
+        _request: &CruxRequest,
+        _response: &mut CruxResponse,
+    ) -> Result<(), CruxError> {
+        Ok(())
+    }
+}
+
+/// Middlewares of a client, in the order they were added.
+#[derive(Clone, Default)]
+pub(crate) struct Chain(Vec<Arc<dyn Middleware>>);
+
+impl Chain {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.0.push(middleware);
+    }
+
+    /// Runs the `before_send` hooks. Returns the request `after_receive` hooks will see, `None` without middlewares.
+    pub(crate) fn before_send(
+        &self,
+        request: &mut CruxRequest,
+    ) -> Result<Option<CruxRequest>, CruxError> {
+        for middleware in &self.0 {
+            middleware.before_send(request)?;
+        }
+        Ok(if self.0.is_empty() {
+            None
+        } else {
+            Some(request.clone())
+        })
+    }
+
+    pub(crate) fn after_receive(
+        &self,
+        request: Option<CruxRequest>,
+        response: Result<CruxResponse, CruxError>,
+    ) -> Result<CruxResponse, CruxError> {
+        match request {
+            Some(request) => self.0.iter().rev().try_fold(response?, |mut response, m| {
+                m.after_receive(&request, &mut response)?;
+                Ok(response)
+            }),
+            None => response,
+        }
+    }
+}
+
+/// `HeaderInjector` is a `Middleware` that sets a header on every request, like a correlation id or a signature.
+pub struct HeaderInjector {
+    name: HeaderName,
+    value: Box<dyn Fn(&CruxRequest) -> HeaderValue + Send + Sync>,
+}
+
+impl HeaderInjector {
+    /// Sets header `name` to `value` on every request.
+    pub fn new(name: HeaderName, value: HeaderValue) -> Self {
+        Self::with(name, move |_| value.clone())
+    }
+
+    /// Sets header `name` to the result of `value` for each request, after its body is encoded, so `value` can sign the request.
+    pub fn with<F>(name: HeaderName, value: F) -> Self
+    where
+        F: Fn(&CruxRequest) -> HeaderValue + Send + Sync + 'static,
+    {
+        Self {
+            name,
+            value: Box::new(value),
+        }
+    }
+}
+
+impl Middleware for HeaderInjector {
+    fn before_send(&self, request: &mut CruxRequest) -> Result<(), CruxError> {
+        let value = (self.value)(request);
+        request.headers.insert(self.name.clone(), value);
+        Ok(())
+    }
+}
+
+/// `BodyLogger` is a `Middleware` that passes the method, path and body of requests and responses of `endpoints` to `log`.
+/// Endpoints are paths without query params, like `/tx-log` or `/entity-history`, and all endpoints are logged if `endpoints` is empty.
+/// Headers are not logged.
+pub struct BodyLogger {
+    endpoints: Vec<String>,
+    log: Box<dyn Fn(&str) + Send + Sync>,
+}
+
+impl BodyLogger {
+    pub fn new<F>(endpoints: &[&str], log: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        Self {
+            endpoints: endpoints.iter().map(|e| e.to_string()).collect(),
+            log: Box::new(log),
+        }
+    }
+
+    fn logs(&self, request: &CruxRequest) -> bool {
+        let path = request.path.split('?').next().unwrap_or_default();
+        self.endpoints.is_empty()
+            || self.endpoints.iter().any(|endpoint| {
+                path == endpoint
+                    || path
+                        .strip_prefix(endpoint.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+}
+
+impl Middleware for BodyLogger {
+    fn before_send(&self, request: &mut CruxRequest) -> Result<(), CruxError> {
+        if self.logs(request) {
+            (self.log)(&format!(
+                "{} {} {}",
+                request.method,
+                request.path,
+                request.body.as_deref().unwrap_or_default()
+            ));
+        }
+        Ok(())
+    }
+
+    fn after_receive(
+        &self,
+        request: &CruxRequest,
+        response: &mut CruxResponse,
+    ) -> Result<(), CruxError> {
+        if self.logs(request) {
+            (self.log)(&format!(
+                "{} {} responded {} {}",
+                request.method, request.path, response.status, response.body
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Crux;
+    use crate::http::transport::CruxTransport;
+    use crate::testing::FakeCrux;
+    use crate::types::{Actions, CruxId};
+    use reqwest::header::HeaderMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    // `FakeCrux` that keeps the headers of every request.
+    #[derive(Default, Clone)]
+    struct Headers {
+        fake: FakeCrux,
+        sent: Arc<Mutex<Vec<HeaderMap>>>,
+    }
+
+    impl CruxTransport for Headers {
+        fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+            self.sent.lock().unwrap().push(request.headers.clone());
+            self.fake.send(request)
+        }
+    }
+
+    // Appends its name to the response body, to check the order of the hooks.
+    struct Tag(&'static str);
+
+    impl Middleware for Tag {
+        fn after_receive(
+            &self,
+            _request: &CruxRequest,
+            response: &mut CruxResponse,
+        ) -> Result<(), CruxError> {
+            response.body.push_str(self.0);
+            Ok(())
+        }
+    }
+
+    struct Deny;
+
+    impl Middleware for Deny {
+        fn before_send(&self, request: &mut CruxRequest) -> Result<(), CruxError> {
+            Err(CruxError::MiddlewareError(format!(
+                "{} is not allowed",
+                request.path
+            )))
+        }
+    }
+
+    #[test]
+    fn header_injector() {
+        let transport = Headers::default();
+        let ids = AtomicUsize::new(0);
+        let client = Crux::new("localhost", "3000")
+            .with_middleware(HeaderInjector::new(
+                HeaderName::from_static("x-tenant"),
+                HeaderValue::from_static("crux"),
+            ))
+            .with_middleware(HeaderInjector::with(
+                HeaderName::from_static("x-correlation-id"),
+                move |_| HeaderValue::from(ids.fetch_add(1, Ordering::SeqCst)),
+            ))
+            .blocking_client_with(transport.clone());
+
+        client
+            .tx_log(Actions::new().append_delete(CruxId::new("jorge-3")))
+            .unwrap();
+        client.tx_logs().unwrap();
+
+        let sent = transport.sent.lock().unwrap();
+        assert_eq!(sent[0]["x-tenant"], "crux");
+        assert_eq!(sent[0]["x-correlation-id"], "0");
+        assert_eq!(sent[1]["x-correlation-id"], "1");
+    }
+
+    #[test]
+    fn body_logger() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let logged = lines.clone();
+        let client = Crux::new("localhost", "3000")
+            .with_authorization("secret-token")
+            .with_middleware(BodyLogger::new(&["/tx-log"], move |line| {
+                logged.lock().unwrap().push(line.to_string())
+            }))
+            .blocking_client_with(FakeCrux::new());
+
+        client
+            .tx_log(Actions::new().append_delete(CruxId::new("jorge-3")))
+            .unwrap();
+        client.entity(CruxId::new("jorge-3")).unwrap_err();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "POST /tx-log [[:crux.tx/delete :jorge-3]]");
+        assert!(lines[1].starts_with("POST /tx-log responded 200 {"));
+        assert!(!lines.concat().contains("secret-token"));
+    }
+
+    #[test]
+    fn hooks_order_and_errors() {
+        let mut chain = Chain::default();
+        chain.push(Arc::new(Tag(" first")));
+        chain.push(Arc::new(Tag(" second")));
+        let denied = Crux::new("localhost", "3000")
+            .with_middleware(Deny)
+            .blocking_client_with(FakeCrux::new());
+
+        let response = chain
+            .after_receive(
+                Some(CruxRequest::get("/")),
+                Ok(CruxResponse::new(200, "body")),
+            )
+            .unwrap();
+
+        assert_eq!(response.body, "body second first");
+        assert_eq!(
+            format!("{}", denied.tx_logs().unwrap_err()),
+            "/tx-log is not allowed"
+        );
+    }
+}
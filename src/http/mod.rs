@@ -0,0 +1,191 @@
+use crate::types::instant::rfc3339_millis;
+use chrono::prelude::*;
+
+mod blocking;
+mod cache;
+mod cluster;
+mod endpoints;
+mod history;
+pub mod middleware;
+mod snapshot;
+mod telemetry;
+pub mod transport;
+pub mod wire;
+pub use blocking::BlockingHttpClient;
+pub use cache::{CacheStats, CachingTransport};
+pub use cluster::ClusterTransport;
+pub use history::EntityHistoryIter;
+pub use middleware::Middleware;
+pub use snapshot::DbSnapshot;
+pub use transport::{CruxTransport, ReqwestTransport};
+pub use wire::WireFormat;
+
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncHttpClient;
+#[cfg(feature = "async")]
+pub use history::AsyncEntityHistoryPages;
+#[cfg(feature = "async")]
+pub use snapshot::AsyncDbSnapshot;
+#[cfg(feature = "async")]
+pub use transport::{AsyncCruxTransport, AsyncReqwestTransport};
+
+/// Retries of `update_entity` after its first attempt, see `update_entity_with_retries`.
+pub const UPDATE_ENTITY_RETRIES: usize = 3;
+
+/// `HttpClient` is the client returned by [`Crux::http_client`](../client/struct.Crux.html#method.http_client).
+/// It is [`BlockingHttpClient`](struct.BlockingHttpClient.html) by default and [`AsyncHttpClient`](struct.AsyncHttpClient.html) when feature `async` is enabled.
+/// Both clients can be used at the same time through `Crux::blocking_client` and `Crux::async_client`.
+#[cfg(not(feature = "async"))]
+pub type HttpClient = BlockingHttpClient;
+
+/// `HttpClient` is the client returned by [`Crux::http_client`](../client/struct.Crux.html#method.http_client).
+/// It is [`BlockingHttpClient`](struct.BlockingHttpClient.html) by default and [`AsyncHttpClient`](struct.AsyncHttpClient.html) when feature `async` is enabled.
+/// Both clients can be used at the same time through `Crux::blocking_client` and `Crux::async_client`.
+#[cfg(feature = "async")]
+pub type HttpClient = AsyncHttpClient;
+
+/// Percent-encodes a query param value. RFC 3339 offsets like `+09:00` would be read as a space otherwise.
+pub(crate) fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub(crate) fn entity_body(crux_id: &str) -> String {
+    let mut s = String::new();
+    s.push_str("{:eid ");
+    s.push_str(crux_id);
+    s.push('}');
+    s
+}
+
+fn build_timed_url(
+    url: String,
+    endpoint: &str,
+    transaction_time: Option<DateTime<FixedOffset>>,
+    valid_time: Option<DateTime<FixedOffset>>,
+) -> String {
+    let params = [
+        ("transaction-time", transaction_time),
+        ("valid-time", valid_time),
+    ]
+    .iter()
+    .filter_map(|(param, time)| {
+        time.map(|t| format!("{}={}", param, encode_query_value(&rfc3339_millis(&t))))
+    })
+    .collect::<Vec<String>>();
+
+    if params.is_empty() {
+        format!("{}/{}", url, endpoint)
+    } else {
+        format!("{}/{}?{}", url, endpoint, params.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod build_url {
+    use super::{build_timed_url, encode_query_value};
+    use chrono::prelude::*;
+
+    #[test]
+    fn both_times_are_none() {
+        let url = build_timed_url("localhost:3000".to_string(), "entity", None, None);
+
+        assert_eq!(url, "localhost:3000/entity");
+    }
+
+    #[test]
+    fn both_times_are_some() {
+        let url = build_timed_url(
+            "localhost:3000".to_string(),
+            "entity",
+            Some(
+                "2020-08-09T18:05:29.301-03:00"
+                    .parse::<DateTime<FixedOffset>>()
+                    .unwrap(),
+            ),
+            Some(
+                "2020-11-09T18:05:29.301-03:00"
+                    .parse::<DateTime<FixedOffset>>()
+                    .unwrap(),
+            ),
+        );
+
+        assert_eq!(url, "localhost:3000/entity?transaction-time=2020-08-09T18:05:29.301-03:00&valid-time=2020-11-09T18:05:29.301-03:00");
+    }
+
+    #[test]
+    fn only_tx_time_is_some() {
+        let url = build_timed_url(
+            "localhost:3000".to_string(),
+            "entity",
+            Some(
+                "2020-08-09T18:05:29.301-03:00"
+                    .parse::<DateTime<FixedOffset>>()
+                    .unwrap(),
+            ),
+            None,
+        );
+
+        assert_eq!(
+            url,
+            "localhost:3000/entity?transaction-time=2020-08-09T18:05:29.301-03:00"
+        );
+    }
+
+    #[test]
+    fn only_valid_time_is_some() {
+        let url = build_timed_url(
+            "localhost:3000".to_string(),
+            "entity",
+            None,
+            Some(
+                "2020-08-09T18:05:29.301+03:00"
+                    .parse::<DateTime<FixedOffset>>()
+                    .unwrap(),
+            ),
+        );
+
+        assert_eq!(
+            url,
+            "localhost:3000/entity?valid-time=2020-08-09T18:05:29.301%2B03:00"
+        );
+    }
+
+    #[test]
+    fn utc_times_use_z() {
+        let url = build_timed_url(
+            "localhost:3000".to_string(),
+            "entity-tx",
+            None,
+            Some(
+                "2020-08-09T18:05:29.3Z"
+                    .parse::<DateTime<Utc>>()
+                    .unwrap()
+                    .into(),
+            ),
+        );
+
+        assert_eq!(
+            url,
+            "localhost:3000/entity-tx?valid-time=2020-08-09T18:05:29.300Z"
+        );
+    }
+
+    #[test]
+    fn encode_query_values() {
+        assert_eq!(
+            encode_query_value("2020-08-09T18:05:29.301+03:00"),
+            "2020-08-09T18:05:29.301%2B03:00"
+        );
+        assert_eq!(encode_query_value("a b&c"), "a%20b%26c");
+    }
+}
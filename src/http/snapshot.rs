@@ -0,0 +1,304 @@
+use crate::http::endpoints;
+#[cfg(feature = "async")]
+use crate::http::{AsyncCruxTransport, AsyncHttpClient};
+use crate::http::{BlockingHttpClient, CruxTransport};
+#[cfg(feature = "async")]
+use crate::types::response::QueryAsyncResponse;
+use crate::types::{
+    error::CruxError,
+    query::Query,
+    response::{EntityTxResponse, QueryResponse, TxLogResponse},
+    CruxId,
+};
+use chrono::prelude::*;
+use edn_rs::Edn;
+use std::collections::BTreeSet;
+
+/// Basis used when `transaction_time` is not given, the tx-time of the latest completed transaction.
+/// A node without transactions is pinned to the Unix epoch, before any transaction.
+fn latest_basis(latest: Option<TxLogResponse>) -> Result<DateTime<FixedOffset>, CruxError> {
+    match latest {
+        Some(tx) => tx.tx___tx_time.to_datetime(),
+        None => Ok(DateTime::<Utc>::UNIX_EPOCH.into()),
+    }
+}
+
+/// `DbSnapshot` is a view of Crux pinned to a `valid_time` and a `transaction_time`, created by
+/// [`BlockingHttpClient::db_as_of`](struct.BlockingHttpClient.html#method.db_as_of) and
+/// [`BlockingHttpClient::db_at_latest`](struct.BlockingHttpClient.html#method.db_at_latest).
+/// Every `entity`, `entity_tx` and `query` of a snapshot reads the same basis, so transactions sent after its creation are not visible.
+pub struct DbSnapshot<'a, T: CruxTransport> {
+    client: &'a BlockingHttpClient<T>,
+    valid_time: DateTime<FixedOffset>,
+    transaction_time: DateTime<FixedOffset>,
+}
+
+impl<T: CruxTransport> BlockingHttpClient<T> {
+    /// Function `latest_completed_tx` requests endpoint `/latest-completed-tx` via `GET`. Returns `None` if no transaction was indexed yet.
+    pub fn latest_completed_tx(&self) -> Result<Option<TxLogResponse>, CruxError> {
+        let resp = self.send(endpoints::latest_completed_tx())?;
+        endpoints::parse_latest_completed_tx(resp, self.format)
+    }
+
+    /// Function `db_as_of` returns a [`DbSnapshot`](struct.DbSnapshot.html) pinned to `valid_time` and `transaction_time`.
+    /// `valid_time` defaults to now and `transaction_time` to the latest completed transaction, both resolved once at creation.
+    pub fn db_as_of(
+        &self,
+        valid_time: Option<DateTime<FixedOffset>>,
+        transaction_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<DbSnapshot<'_, T>, CruxError> {
+        let valid_time = valid_time.unwrap_or_else(|| Utc::now().into());
+        let transaction_time = match transaction_time {
+            Some(time) => time,
+            None => latest_basis(self.latest_completed_tx()?)?,
+        };
+
+        Ok(DbSnapshot {
+            client: self,
+            valid_time,
+            transaction_time,
+        })
+    }
+
+    /// Function `db_at_latest` is `db_as_of(None, None)`, a snapshot of the current valid time and latest completed transaction.
+    pub fn db_at_latest(&self) -> Result<DbSnapshot<'_, T>, CruxError> {
+        self.db_as_of(None, None)
+    }
+}
+
+impl<'a, T: CruxTransport> DbSnapshot<'a, T> {
+    /// Valid time of the snapshot.
+    pub fn valid_time(&self) -> DateTime<FixedOffset> {
+        self.valid_time
+    }
+
+    /// Transaction time of the snapshot.
+    pub fn transaction_time(&self) -> DateTime<FixedOffset> {
+        self.transaction_time
+    }
+
+    /// Function `entity` is `BlockingHttpClient::entity_timed` at the snapshot basis.
+    pub fn entity(&self, id: CruxId) -> Result<Edn, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self.client.send(endpoints::entity(
+            "entity",
+            &crux_id,
+            Some(self.transaction_time),
+            Some(self.valid_time),
+        ))?;
+        endpoints::parse_entity(resp, self.client.format, "entity", &crux_id)
+    }
+
+    /// Function `entity_tx` is `BlockingHttpClient::entity_tx_timed` at the snapshot basis.
+    pub fn entity_tx(&self, id: CruxId) -> Result<EntityTxResponse, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self.client.send(endpoints::entity(
+            "entity-tx",
+            &crux_id,
+            Some(self.transaction_time),
+            Some(self.valid_time),
+        ))?;
+        endpoints::parse_entity_tx(resp, self.client.format, "entity-tx", &crux_id)
+    }
+
+    /// Function `query` is `BlockingHttpClient::query` at the snapshot basis, sent as `valid-time` and `transaction-time` query params.
+    pub fn query(&self, query: Query) -> Result<BTreeSet<Vec<String>>, CruxError> {
+        let resp = self.client.send(endpoints::query_timed(
+            query,
+            Some(self.transaction_time),
+            Some(self.valid_time),
+        ))?;
+        let query_response: QueryResponse = endpoints::parse_query(resp, self.client.format)?;
+
+        Ok(query_response.0)
+    }
+}
+
+/// `AsyncDbSnapshot` is the [`DbSnapshot`](struct.DbSnapshot.html) of an `AsyncHttpClient`. Feature `async` is required.
+#[cfg(feature = "async")]
+pub struct AsyncDbSnapshot<'a, T: AsyncCruxTransport> {
+    client: &'a AsyncHttpClient<T>,
+    valid_time: DateTime<FixedOffset>,
+    transaction_time: DateTime<FixedOffset>,
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport> AsyncHttpClient<T> {
+    /// Function `latest_completed_tx` requests endpoint `/latest-completed-tx` via `GET`. Returns `None` if no transaction was indexed yet.
+    pub async fn latest_completed_tx(&self) -> Result<Option<TxLogResponse>, CruxError> {
+        let resp = self.send(endpoints::latest_completed_tx()).await?;
+        endpoints::parse_latest_completed_tx(resp, self.format)
+    }
+
+    /// Function `db_as_of` returns an [`AsyncDbSnapshot`](struct.AsyncDbSnapshot.html) pinned to `valid_time` and `transaction_time`.
+    /// `valid_time` defaults to now and `transaction_time` to the latest completed transaction, both resolved once at creation.
+    pub async fn db_as_of(
+        &self,
+        valid_time: Option<DateTime<FixedOffset>>,
+        transaction_time: Option<DateTime<FixedOffset>>,
+    ) -> Result<AsyncDbSnapshot<'_, T>, CruxError> {
+        let valid_time = valid_time.unwrap_or_else(|| Utc::now().into());
+        let transaction_time = match transaction_time {
+            Some(time) => time,
+            None => latest_basis(self.latest_completed_tx().await?)?,
+        };
+
+        Ok(AsyncDbSnapshot {
+            client: self,
+            valid_time,
+            transaction_time,
+        })
+    }
+
+    /// Function `db_at_latest` is `db_as_of(None, None)`, a snapshot of the current valid time and latest completed transaction.
+    pub async fn db_at_latest(&self) -> Result<AsyncDbSnapshot<'_, T>, CruxError> {
+        self.db_as_of(None, None).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T: AsyncCruxTransport> AsyncDbSnapshot<'a, T> {
+    /// Valid time of the snapshot.
+    pub fn valid_time(&self) -> DateTime<FixedOffset> {
+        self.valid_time
+    }
+
+    /// Transaction time of the snapshot.
+    pub fn transaction_time(&self) -> DateTime<FixedOffset> {
+        self.transaction_time
+    }
+
+    /// Function `entity` is `AsyncHttpClient::entity_timed` at the snapshot basis.
+    pub async fn entity(&self, id: CruxId) -> Result<Edn, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self
+            .client
+            .send(endpoints::entity(
+                "entity",
+                &crux_id,
+                Some(self.transaction_time),
+                Some(self.valid_time),
+            ))
+            .await?;
+        endpoints::parse_entity(resp, self.client.format, "entity", &crux_id)
+    }
+
+    /// Function `entity_tx` is `AsyncHttpClient::entity_tx_timed` at the snapshot basis.
+    pub async fn entity_tx(&self, id: CruxId) -> Result<EntityTxResponse, CruxError> {
+        let crux_id = edn_rs::to_string(id);
+
+        let resp = self
+            .client
+            .send(endpoints::entity(
+                "entity-tx",
+                &crux_id,
+                Some(self.transaction_time),
+                Some(self.valid_time),
+            ))
+            .await?;
+        endpoints::parse_entity_tx(resp, self.client.format, "entity-tx", &crux_id)
+    }
+
+    /// Function `query` is `AsyncHttpClient::query` at the snapshot basis, sent as `valid-time` and `transaction-time` query params.
+    pub async fn query(&self, query: Query) -> Result<BTreeSet<Vec<String>>, CruxError> {
+        let resp = self
+            .client
+            .send(endpoints::query_timed(
+                query,
+                Some(self.transaction_time),
+                Some(self.valid_time),
+            ))
+            .await?;
+        let query_response: QueryAsyncResponse = endpoints::parse_query(resp, self.client.format)?;
+
+        Ok(query_response.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::support::{person, seeded_client};
+    use crate::testing::FakeCrux;
+    use crate::types::{query::Query, Actions, CruxId};
+    use chrono::prelude::*;
+    use edn_rs::Edn;
+
+    #[test]
+    fn snapshot_ignores_later_transactions() {
+        let client = seeded_client();
+        let tx = client.latest_completed_tx().unwrap().unwrap();
+
+        let db = client.db_at_latest().unwrap();
+        client
+            .tx_log(
+                Actions::new()
+                    .append_put(person("jorge-3", "Mike", "Jorge"))
+                    .append_put(person("manuel-1", "Diego", "Jorge")),
+            )
+            .unwrap();
+
+        let query = Query::find(vec!["?p"])
+            .unwrap()
+            .where_clause(vec!["?p :first-name ?n"])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            db.transaction_time(),
+            tx.tx___tx_time.to_datetime().unwrap()
+        );
+        assert_eq!(
+            db.entity(CruxId::new("jorge-3")).unwrap()[":first-name"],
+            Edn::Str("Michael".to_string())
+        );
+        assert_eq!(
+            db.entity_tx(CruxId::new("jorge-3")).unwrap().tx___tx_id,
+            tx.tx___tx_id
+        );
+        assert!(db.entity(CruxId::new("manuel-1")).is_err());
+        assert_eq!(db.query(query.clone()).unwrap().len(), 1);
+        assert_eq!(client.query(query).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn snapshot_of_empty_node() {
+        let client = FakeCrux::new().client();
+
+        let db = client.db_at_latest().unwrap();
+        client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap();
+
+        assert_eq!(client.latest_completed_tx().unwrap().unwrap().tx___tx_id, 0);
+        assert_eq!(db.transaction_time(), DateTime::<Utc>::UNIX_EPOCH);
+        assert!(db.entity(CruxId::new("jorge-3")).is_err());
+    }
+
+    #[test]
+    fn snapshot_as_of_valid_time() {
+        let client = FakeCrux::new().client();
+        let past = "2014-11-28T21:00:09+09:00"
+            .parse::<DateTime<FixedOffset>>()
+            .unwrap();
+        client
+            .tx_log(
+                Actions::new()
+                    .append_put_timed(person("jorge-3", "Michael", "Jorge"), past)
+                    .append_put(person("jorge-3", "Mike", "Jorge")),
+            )
+            .unwrap();
+
+        let db = client
+            .db_as_of(Some(past + chrono::Duration::days(1)), None)
+            .unwrap();
+
+        assert_eq!(
+            db.entity(CruxId::new("jorge-3")).unwrap()[":first-name"],
+            Edn::Str("Michael".to_string())
+        );
+    }
+}
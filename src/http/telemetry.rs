@@ -0,0 +1,261 @@
+use crate::http::transport::{CruxRequest, CruxResponse};
+use crate::http::WireFormat;
+use crate::types::error::CruxError;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::time::Instant;
+
+// Instrumentation of every request sent by the clients: a `crux_request` span with feature `tracing`
+// and counters and histograms with feature `metrics`.
+// Only the endpoint, method, status, body sizes and ids are recorded. Headers are never read, so credentials
+// from `with_authorization` are not logged.
+
+/// Endpoint of `path`, its first segment: `/entity-history/ecc6...?sort-order=asc` is `entity-history` and `/` is `state`.
+#[cfg(any(test, feature = "tracing", feature = "metrics"))]
+fn endpoint(path: &str) -> &str {
+    path.split('?')
+        .next()
+        .and_then(|path| path.split('/').find(|segment| !segment.is_empty()))
+        .unwrap_or("state")
+}
+
+/// Entity of the request, the `:eid` of `/entity` and `/entity-tx` or the hash of `/entity-history`.
+#[cfg(any(test, feature = "tracing"))]
+fn entity_id(request: &CruxRequest, format: WireFormat) -> Option<String> {
+    match endpoint(&request.path) {
+        "entity" | "entity-tx" => match &format.decode(request.body.as_deref()?).ok()?[":eid"] {
+            edn_rs::Edn::Nil => None,
+            eid => Some(eid.to_string()),
+        },
+        "entity-history" => request
+            .path
+            .split('?')
+            .next()?
+            .strip_prefix("/entity-history/")
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Transaction of the response, `:crux.tx/tx-id` of `/tx-log` `POST`, `/await-tx` and `/latest-completed-tx`.
+#[cfg(any(test, feature = "tracing"))]
+fn tx_id(endpoint: &str, response: &CruxResponse, format: WireFormat) -> Option<usize> {
+    match endpoint {
+        "tx-log" | "await-tx" | "latest-completed-tx" if response.is_success() => {
+            match format.decode(&response.body).ok()?[":crux.tx/tx-id"] {
+                edn_rs::Edn::UInt(id) => Some(id),
+                edn_rs::Edn::Int(id) => Some(id as usize),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A request being sent, created by the clients' `send` after the body is encoded.
+pub(crate) struct Call {
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    endpoint: String,
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    started: Instant,
+    #[cfg(feature = "metrics")]
+    request_bytes: usize,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Call {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn start(request: &CruxRequest, format: WireFormat) -> Self {
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let endpoint = endpoint(&request.path).to_string();
+
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "crux_request",
+                endpoint = %endpoint,
+                method = %request.method,
+                request_bytes = request.body.as_ref().map_or(0, String::len),
+                entity_id = entity_id(request, format).as_deref(),
+                status = tracing::field::Empty,
+                response_bytes = tracing::field::Empty,
+                tx_id = tracing::field::Empty,
+            ),
+            #[cfg(feature = "metrics")]
+            request_bytes: request.body.as_ref().map_or(0, String::len),
+            #[cfg(any(feature = "tracing", feature = "metrics"))]
+            started: Instant::now(),
+            #[cfg(any(feature = "tracing", feature = "metrics"))]
+            endpoint,
+        }
+    }
+
+    /// Runs the blocking transport inside the span.
+    pub(crate) fn in_scope<R>(&self, send: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.enter();
+        send()
+    }
+
+    /// Runs the async transport inside the span.
+    #[cfg(feature = "async")]
+    pub(crate) async fn instrument<F: std::future::Future>(&self, send: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        let send = tracing::Instrument::instrument(send, self.span.clone());
+        send.await
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn finish(self, result: &Result<CruxResponse, CruxError>, format: WireFormat) {
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let elapsed = self.started.elapsed();
+
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(response) => {
+                self.span.record("status", response.status);
+                self.span.record("response_bytes", response.body.len());
+                if let Some(id) = tx_id(&self.endpoint, response, format) {
+                    self.span.record("tx_id", id);
+                }
+                tracing::debug!(
+                    parent: &self.span,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "crux request finished"
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    parent: &self.span,
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    error = %e,
+                    "crux request failed"
+                );
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            let status = match result {
+                Ok(response) => response.status.to_string(),
+                Err(_) => "error".to_string(),
+            };
+            let endpoint = self.endpoint;
+            metrics::counter!(
+                "transistor_requests_total",
+                "endpoint" => endpoint.clone(),
+                "status" => status
+            )
+            .increment(1);
+            metrics::histogram!(
+                "transistor_request_duration_seconds",
+                "endpoint" => endpoint.clone()
+            )
+            .record(elapsed.as_secs_f64());
+            metrics::histogram!("transistor_request_bytes", "endpoint" => endpoint.clone())
+                .record(self.request_bytes as f64);
+            if let Ok(response) = result {
+                metrics::histogram!("transistor_response_bytes", "endpoint" => endpoint)
+                    .record(response.body.len() as f64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn endpoints() {
+        assert_eq!(endpoint("/"), "state");
+        assert_eq!(endpoint("/?x=1"), "state");
+        assert_eq!(endpoint("/tx-log"), "tx-log");
+        assert_eq!(endpoint("/entity-tx?valid-time=2020"), "entity-tx");
+        assert_eq!(
+            endpoint("/entity-history/ecc6?sort-order=asc"),
+            "entity-history"
+        );
+    }
+
+    #[test]
+    fn ids() {
+        let entity = CruxRequest::post("/entity", "{:eid :jorge-3}".to_string());
+        let history = CruxRequest::get("/entity-history/ecc6?sort-order=asc");
+        let tx = CruxResponse::new(
+            200,
+            "{:crux.tx/tx-id 7, :crux.tx/tx-time \"2020-07-16T21:53:14.628-00:00\"}",
+        );
+
+        assert_eq!(
+            entity_id(&entity, WireFormat::Edn).as_deref(),
+            Some(":jorge-3")
+        );
+        assert_eq!(
+            entity_id(&history, WireFormat::Edn).as_deref(),
+            Some("ecc6")
+        );
+        assert_eq!(tx_id("tx-log", &tx, WireFormat::Edn), Some(7));
+        assert_eq!(tx_id("entity", &tx, WireFormat::Edn), None);
+    }
+
+    // Subscriber that keeps every span and event field as `name=value`.
+    #[cfg(feature = "tracing")]
+    #[derive(Default, Clone)]
+    struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for Recorder {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            span.record(&mut self.clone());
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut self.clone());
+        }
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            event.record(&mut self.clone());
+        }
+        fn enter(&self, _: &tracing::span::Id) {}
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn spans_without_credentials() {
+        use crate::client::Crux;
+        use crate::testing::FakeCrux;
+        use crate::types::{Actions, CruxId};
+
+        let recorder = Recorder::default();
+        let client = Crux::new("localhost", "3000")
+            .with_authorization("secret-token")
+            .blocking_client_with(FakeCrux::new());
+
+        tracing::subscriber::with_default(recorder.clone(), || {
+            client
+                .tx_log(Actions::new().append_delete(CruxId::new("jorge-3")))
+                .unwrap();
+        });
+
+        let fields = recorder.0.lock().unwrap().join(" ");
+        assert!(fields.contains("endpoint=tx-log"));
+        assert!(fields.contains("status=200"));
+        assert!(fields.contains("tx_id=0"));
+        assert!(!fields.contains("secret-token"));
+    }
+}
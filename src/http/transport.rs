@@ -0,0 +1,293 @@
+use crate::types::error::CruxError;
+use reqwest::header::HeaderMap;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// HTTP methods used by Crux REST API endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Method::Get => write!(f, "GET"),
+            Method::Post => write!(f, "POST"),
+        }
+    }
+}
+
+/// `CruxRequest` is what a `CruxTransport` receives from the clients.
+/// `path` is relative to the node, `/entity-tx?valid-time=...`, and contains the query params.
+/// `evicts` is set by `tx_log` when the transaction has an `Evict`, since the encoded `body` depends on the wire format.
+#[derive(Debug, Clone)]
+pub struct CruxRequest {
+    pub method: Method,
+    pub path: String,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+    pub evicts: bool,
+}
+
+impl CruxRequest {
+    /// `GET` request to `path` without body.
+    pub fn get(path: &str) -> Self {
+        Self {
+            method: Method::Get,
+            path: path.to_string(),
+            headers: HeaderMap::new(),
+            body: None,
+            evicts: false,
+        }
+    }
+
+    /// `POST` request to `path` with `body`.
+    pub fn post(path: &str, body: String) -> Self {
+        Self {
+            method: Method::Post,
+            path: path.to_string(),
+            headers: HeaderMap::new(),
+            body: Some(body),
+            evicts: false,
+        }
+    }
+}
+
+/// `CruxResponse` is what a `CruxTransport` returns to the clients, the HTTP `status` and the response `body`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CruxResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl CruxResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    /// Crux responses with status lower than 300 are considered successful.
+    pub fn is_success(&self) -> bool {
+        self.status < 300
+    }
+}
+
+/// `CruxTransport` is the HTTP backend used by `BlockingHttpClient`.
+/// It receives a `CruxRequest` with method, path, headers and body and returns the status and body as a `CruxResponse`.
+/// [`ReqwestTransport`](struct.ReqwestTransport.html) is the default implementation.
+pub trait CruxTransport {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError>;
+}
+
+impl<T: CruxTransport + ?Sized> CruxTransport for &T {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        (**self).send(request)
+    }
+}
+
+impl<T: CruxTransport + ?Sized> CruxTransport for Box<T> {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        (**self).send(request)
+    }
+}
+
+impl<T: CruxTransport + ?Sized> CruxTransport for Arc<T> {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        (**self).send(request)
+    }
+}
+
+/// Default `CruxTransport`, it sends requests to `uri` with `reqwest::blocking::Client`.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    pub(crate) client: reqwest::blocking::Client,
+    pub(crate) uri: String,
+}
+
+impl ReqwestTransport {
+    /// `uri` is the node address, `http://localhost:3000`.
+    pub fn new(uri: &str) -> Self {
+        Self::with_client(reqwest::blocking::Client::new(), uri)
+    }
+
+    /// Same as `new` but with a preconfigured `reqwest::blocking::Client`, for timeouts, proxies or TLS settings.
+    pub fn with_client(client: reqwest::blocking::Client, uri: &str) -> Self {
+        Self {
+            client,
+            uri: uri.to_string(),
+        }
+    }
+}
+
+impl CruxTransport for ReqwestTransport {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        let url = format!("{}{}", self.uri, request.path);
+        let builder = match request.method {
+            Method::Get => self.client.get(url),
+            Method::Post => self.client.post(url),
+        }
+        .headers(request.headers);
+        let builder = match request.body {
+            Some(body) => builder.body(body),
+            None => builder,
+        };
+
+        let resp = builder.send().map_err(send_error)?;
+        let status = resp.status().as_u16();
+        Ok(CruxResponse {
+            status,
+            body: resp.text()?,
+        })
+    }
+}
+
+/// A request that failed to connect was never sent, every other failure may have reached the node.
+fn send_error(error: reqwest::Error) -> CruxError {
+    if error.is_connect() {
+        CruxError::ConnectionError(error.to_string())
+    } else {
+        CruxError::RequestError(error)
+    }
+}
+
+/// Future returned by `AsyncCruxTransport::send`.
+#[cfg(feature = "async")]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<CruxResponse, CruxError>> + Send + 'a>>;
+
+/// `AsyncCruxTransport` is the HTTP backend used by `AsyncHttpClient`. It is the async version of `CruxTransport`.
+/// Implementations decide which runtime drives the request, so `async-std` or `smol` HTTP clients can be used instead of [`AsyncReqwestTransport`](struct.AsyncReqwestTransport.html).
+#[cfg(feature = "async")]
+pub trait AsyncCruxTransport {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_>;
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + ?Sized> AsyncCruxTransport for Box<T> {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + ?Sized> AsyncCruxTransport for Arc<T> {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+/// Default `AsyncCruxTransport`, it sends requests to `uri` with `reqwest::Client`. A Tokio 1.x runtime is required.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone)]
+pub struct AsyncReqwestTransport {
+    pub(crate) client: reqwest::Client,
+    pub(crate) uri: String,
+}
+
+#[cfg(feature = "async")]
+impl AsyncReqwestTransport {
+    /// `uri` is the node address, `http://localhost:3000`.
+    pub fn new(uri: &str) -> Self {
+        Self::with_client(reqwest::Client::new(), uri)
+    }
+
+    /// Same as `new` but with a preconfigured `reqwest::Client`.
+    pub fn with_client(client: reqwest::Client, uri: &str) -> Self {
+        Self {
+            client,
+            uri: uri.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncCruxTransport for AsyncReqwestTransport {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let url = format!("{}{}", self.uri, request.path);
+            let builder = match request.method {
+                Method::Get => self.client.get(url),
+                Method::Post => self.client.post(url),
+            }
+            .headers(request.headers);
+            let builder = match request.body {
+                Some(body) => builder.body(body),
+                None => builder,
+            };
+
+            let resp = builder.send().await.map_err(send_error)?;
+            let status = resp.status().as_u16();
+            Ok(CruxResponse {
+                status,
+                body: resp.text().await?,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Crux;
+    use crate::types::CruxId;
+    use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+    use std::cell::RefCell;
+
+    struct InMemoryTransport {
+        requests: RefCell<Vec<CruxRequest>>,
+        response: CruxResponse,
+    }
+
+    impl InMemoryTransport {
+        fn new(status: u16, body: &str) -> Self {
+            Self {
+                requests: RefCell::new(Vec::new()),
+                response: CruxResponse::new(status, body),
+            }
+        }
+    }
+
+    impl CruxTransport for InMemoryTransport {
+        fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+            self.requests.borrow_mut().push(request);
+            Ok(self.response.clone())
+        }
+    }
+
+    #[test]
+    fn entity_over_custom_transport() {
+        let transport = InMemoryTransport::new(200, "{:crux.db/id :ivan :name \"Ivan\"}");
+        let edn = Crux::new("localhost", "3000")
+            .with_authorization("auth")
+            .blocking_client_with(&transport)
+            .entity(CruxId::new("ivan"))
+            .unwrap();
+
+        let requests = transport.requests.borrow();
+        assert_eq!(edn[":name"], edn_rs::Edn::Str("Ivan".to_string()));
+        assert_eq!(requests[0].method, Method::Post);
+        assert_eq!(requests[0].path, "/entity");
+        assert_eq!(requests[0].body, Some("{:eid :ivan}".to_string()));
+        assert_eq!(requests[0].headers[AUTHORIZATION], "auth");
+        assert_eq!(requests[0].headers[CONTENT_TYPE], "application/edn");
+    }
+
+    #[test]
+    fn bad_status_over_custom_transport() {
+        let transport = InMemoryTransport::new(404, "");
+        let err = Crux::new("localhost", "3000")
+            .blocking_client_with(&transport)
+            .tx_logs()
+            .unwrap_err();
+
+        assert_eq!(transport.requests.borrow()[0].method, Method::Get);
+        assert_eq!(format!("{}", err), "tx_logs responded with 404");
+    }
+}
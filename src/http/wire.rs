@@ -0,0 +1,466 @@
+use crate::types::error::CruxError;
+use edn_rs::Edn;
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use edn_rs::{Double, List, Map, Set, Vector};
+#[cfg(feature = "json")]
+use serde_json::{Map as JsonMap, Number, Value};
+#[cfg(feature = "json")]
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Wire format used to talk to Crux, selected with [`Crux::with_format`](../../client/struct.Crux.html#method.with_format).
+/// Requests are encoded and responses are decoded in the selected format, so every response type
+/// in `types::response` is available regardless of the format.
+/// `Json` and `TransitJson` require feature `json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    /// `application/edn`, default format.
+    #[default]
+    Edn,
+    /// `application/json`. Keywords and symbols are sent as plain strings and map keys are read as keywords.
+    #[cfg(feature = "json")]
+    Json,
+    /// `application/transit+json`. Keeps keywords, symbols, sets and instants.
+    #[cfg(feature = "json")]
+    TransitJson,
+}
+
+impl WireFormat {
+    /// Value for the `Content-Type` and `Accept` headers.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            WireFormat::Edn => "application/edn",
+            #[cfg(feature = "json")]
+            WireFormat::Json => "application/json",
+            #[cfg(feature = "json")]
+            WireFormat::TransitJson => "application/transit+json",
+        }
+    }
+
+    /// Encodes an EDN request body into this format.
+    pub(crate) fn encode(&self, edn: &str) -> Result<String, CruxError> {
+        match self {
+            WireFormat::Edn => Ok(edn.to_string()),
+            #[cfg(feature = "json")]
+            WireFormat::Json => Ok(edn_to_json(&Edn::from_str(edn)?).to_string()),
+            #[cfg(feature = "json")]
+            WireFormat::TransitJson => {
+                let value = match Edn::from_str(edn)? {
+                    e @ Edn::Vector(_) | e @ Edn::List(_) | e @ Edn::Set(_) | e @ Edn::Map(_) => {
+                        edn_to_transit(&e)
+                    }
+                    scalar => Value::Array(vec![
+                        Value::String("~#'".to_string()),
+                        edn_to_transit(&scalar),
+                    ]),
+                };
+                Ok(value.to_string())
+            }
+        }
+    }
+
+    /// Decodes a response body in this format into `Edn`.
+    pub(crate) fn decode(&self, body: &str) -> Result<Edn, CruxError> {
+        match self {
+            WireFormat::Edn => Ok(Edn::from_str(body)?),
+            #[cfg(feature = "json")]
+            WireFormat::Json => Ok(json_to_edn(&parse_json(body)?)),
+            #[cfg(feature = "json")]
+            WireFormat::TransitJson => TransitReader::default().read(&parse_json(body)?),
+        }
+    }
+}
+
+impl std::fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.content_type())
+    }
+}
+
+#[cfg(feature = "json")]
+fn parse_json(body: &str) -> Result<Value, CruxError> {
+    serde_json::from_str(body)
+        .map_err(|e| CruxError::ParseEdnError(format!("Unable to parse JSON response: {}", e)))
+}
+
+#[cfg(feature = "json")]
+fn keyword(key: &str) -> String {
+    if key.starts_with(':') {
+        key.to_string()
+    } else {
+        format!(":{}", key)
+    }
+}
+
+#[cfg(feature = "json")]
+fn number(n: &Number) -> Edn {
+    if let Some(u) = n.as_u64() {
+        Edn::UInt(u as usize)
+    } else if let Some(i) = n.as_i64() {
+        Edn::Int(i as isize)
+    } else {
+        Edn::Double(Double::from(n.as_f64().unwrap_or_default()))
+    }
+}
+
+#[cfg(feature = "json")]
+fn float(f: f64) -> Value {
+    Number::from_f64(f)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(feature = "json")]
+fn json_to_edn(value: &Value) -> Edn {
+    match value {
+        Value::Null => Edn::Nil,
+        Value::Bool(b) => Edn::Bool(*b),
+        Value::Number(n) => number(n),
+        Value::String(s) => Edn::Str(s.clone()),
+        Value::Array(a) => Edn::Vector(Vector::new(a.iter().map(json_to_edn).collect())),
+        Value::Object(o) => Edn::Map(Map::new(
+            o.iter()
+                .map(|(k, v)| (keyword(k), json_to_edn(v)))
+                .collect(),
+        )),
+    }
+}
+
+// Map keys are stored as the `Display` of the key, `:crux.db/id` or `"name"`.
+#[cfg(feature = "json")]
+fn key_edn(key: &str) -> Edn {
+    Edn::from_str(key).unwrap_or_else(|_| Edn::Str(key.to_string()))
+}
+
+#[cfg(feature = "json")]
+fn json_key(key: &str) -> String {
+    match key_edn(key) {
+        Edn::Key(k) => k.trim_start_matches(':').to_string(),
+        Edn::Str(s) | Edn::Symbol(s) => s,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(feature = "json")]
+fn edn_to_json(edn: &Edn) -> Value {
+    match edn {
+        Edn::Vector(v) => Value::Array(v.clone().to_vec().iter().map(edn_to_json).collect()),
+        Edn::List(l) => Value::Array(l.clone().to_vec().iter().map(edn_to_json).collect()),
+        Edn::Set(s) => Value::Array(s.clone().to_set().iter().map(edn_to_json).collect()),
+        Edn::Map(m) => Value::Object(
+            m.clone()
+                .to_map()
+                .iter()
+                .map(|(k, v)| (json_key(k), edn_to_json(v)))
+                .collect::<JsonMap<String, Value>>(),
+        ),
+        Edn::NamespacedMap(ns, m) => Value::Object(
+            m.clone()
+                .to_map()
+                .iter()
+                .map(|(k, v)| (format!("{}/{}", ns, json_key(k)), edn_to_json(v)))
+                .collect::<JsonMap<String, Value>>(),
+        ),
+        Edn::Key(k) => Value::String(k.trim_start_matches(':').to_string()),
+        Edn::Symbol(s) | Edn::Str(s) | Edn::Inst(s) | Edn::Uuid(s) => Value::String(s.clone()),
+        Edn::Int(i) => Value::from(*i as i64),
+        Edn::UInt(u) => Value::from(*u as u64),
+        Edn::Double(_) | Edn::Rational(_) => float(edn.to_float().unwrap_or_default()),
+        Edn::Char(c) => Value::String(c.to_string()),
+        Edn::Bool(b) => Value::Bool(*b),
+        Edn::Nil | Edn::Empty => Value::Null,
+    }
+}
+
+#[cfg(feature = "json")]
+fn transit_str(s: &str) -> String {
+    if s.starts_with('~') || s.starts_with('^') || s.starts_with('`') {
+        format!("~{}", s)
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(feature = "json")]
+fn tagged(tag: &str, value: Value) -> Value {
+    Value::Array(vec![Value::String(format!("~#{}", tag)), value])
+}
+
+#[cfg(feature = "json")]
+fn edn_to_transit(edn: &Edn) -> Value {
+    match edn {
+        Edn::Vector(v) => Value::Array(v.clone().to_vec().iter().map(edn_to_transit).collect()),
+        Edn::List(l) => tagged(
+            "list",
+            Value::Array(l.clone().to_vec().iter().map(edn_to_transit).collect()),
+        ),
+        Edn::Set(s) => tagged(
+            "set",
+            Value::Array(s.clone().to_set().iter().map(edn_to_transit).collect()),
+        ),
+        Edn::Map(m) => {
+            let mut map = vec![Value::String("^ ".to_string())];
+            for (k, v) in m.clone().to_map() {
+                map.push(edn_to_transit(&key_edn(&k)));
+                map.push(edn_to_transit(&v));
+            }
+            Value::Array(map)
+        }
+        Edn::NamespacedMap(ns, m) => {
+            let mut map = vec![Value::String("^ ".to_string())];
+            for (k, v) in m.clone().to_map() {
+                map.push(Value::String(format!("~:{}/{}", ns, json_key(&k))));
+                map.push(edn_to_transit(&v));
+            }
+            Value::Array(map)
+        }
+        Edn::Key(k) => Value::String(format!("~:{}", k.trim_start_matches(':'))),
+        Edn::Symbol(s) => Value::String(format!("~${}", s)),
+        Edn::Str(s) => Value::String(transit_str(s)),
+        Edn::Inst(s) => Value::String(format!("~t{}", s)),
+        Edn::Uuid(s) => Value::String(format!("~u{}", s)),
+        Edn::Char(c) => Value::String(format!("~c{}", c)),
+        Edn::Int(i) => Value::from(*i as i64),
+        Edn::UInt(u) => Value::from(*u as u64),
+        Edn::Double(_) | Edn::Rational(_) => float(edn.to_float().unwrap_or_default()),
+        Edn::Bool(b) => Value::Bool(*b),
+        Edn::Nil | Edn::Empty => Value::Null,
+    }
+}
+
+// Transit writers replace repeated keywords, symbols, tags and map keys by `^<index>` references.
+#[cfg(feature = "json")]
+const CACHE_BASE: usize = 44;
+#[cfg(feature = "json")]
+const CACHE_SIZE: usize = CACHE_BASE * CACHE_BASE;
+
+#[cfg(feature = "json")]
+#[derive(Default)]
+struct TransitReader {
+    cache: Vec<String>,
+}
+
+#[cfg(feature = "json")]
+impl TransitReader {
+    fn read(&mut self, value: &Value) -> Result<Edn, CruxError> {
+        match value {
+            Value::String(s) => {
+                let s = self.resolve(s, false)?;
+                Ok(self.scalar(&s))
+            }
+            Value::Array(a) => self.array(a),
+            Value::Object(o) => {
+                let mut map = BTreeMap::new();
+                for (k, v) in o {
+                    let key = self.resolve(k, true)?;
+                    map.insert(self.scalar(&key).to_string(), self.read(v)?);
+                }
+                Ok(Edn::Map(Map::new(map)))
+            }
+            other => Ok(json_to_edn(other)),
+        }
+    }
+
+    fn array(&mut self, array: &[Value]) -> Result<Edn, CruxError> {
+        let first = match array.first() {
+            Some(Value::String(s)) => self.resolve(s, false)?,
+            _ => return self.vector(array),
+        };
+
+        if first == "^ " {
+            let mut map = BTreeMap::new();
+            for pair in array[1..].chunks(2) {
+                let key = match &pair[0] {
+                    Value::String(k) => {
+                        let k = self.resolve(k, true)?;
+                        self.scalar(&k)
+                    }
+                    other => self.read(other)?,
+                };
+                let value = pair.get(1).map(|v| self.read(v)).transpose()?;
+                map.insert(key.to_string(), value.unwrap_or(Edn::Nil));
+            }
+            return Ok(Edn::Map(Map::new(map)));
+        }
+
+        if first.starts_with("~#") && array.len() == 2 {
+            let value = self.read(&array[1])?;
+            return Ok(match &first[2..] {
+                "set" => Edn::Set(Set::new(
+                    value
+                        .iter()
+                        .into_iter()
+                        .flatten()
+                        .cloned()
+                        .collect::<BTreeSet<Edn>>(),
+                )),
+                "list" => Edn::List(List::new(
+                    value.iter().into_iter().flatten().cloned().collect(),
+                )),
+                "cmap" => {
+                    let entries = value
+                        .iter()
+                        .into_iter()
+                        .flatten()
+                        .cloned()
+                        .collect::<Vec<Edn>>();
+                    Edn::Map(Map::new(
+                        entries
+                            .chunks(2)
+                            .map(|kv| (kv[0].to_string(), kv.get(1).cloned().unwrap_or(Edn::Nil)))
+                            .collect(),
+                    ))
+                }
+                // `'` wraps top level scalars and `crux/id` wraps entity ids, both read as their value.
+                _ => value,
+            });
+        }
+
+        let mut items = vec![self.scalar(&first)];
+        for v in &array[1..] {
+            items.push(self.read(v)?);
+        }
+        Ok(Edn::Vector(Vector::new(items)))
+    }
+
+    fn vector(&mut self, array: &[Value]) -> Result<Edn, CruxError> {
+        Ok(Edn::Vector(Vector::new(
+            array
+                .iter()
+                .map(|v| self.read(v))
+                .collect::<Result<Vec<Edn>, CruxError>>()?,
+        )))
+    }
+
+    /// Returns the cached string for `^<index>` references and caches cacheable strings.
+    fn resolve(&mut self, s: &str, map_key: bool) -> Result<String, CruxError> {
+        if s.starts_with('^') && s != "^ " {
+            let digits = s[1..].bytes().map(|b| (b as usize).wrapping_sub(48));
+            let index = digits.fold(0, |acc, d| acc * CACHE_BASE + d);
+            return self.cache.get(index).cloned().ok_or_else(|| {
+                CruxError::ParseEdnError(format!("Invalid transit cache reference {}", s))
+            });
+        }
+
+        let cacheable = s.len() > 3
+            && (map_key || s.starts_with("~:") || s.starts_with("~$") || s.starts_with("~#"));
+        if cacheable {
+            if self.cache.len() == CACHE_SIZE {
+                self.cache.clear();
+            }
+            self.cache.push(s.to_string());
+        }
+        Ok(s.to_string())
+    }
+
+    fn scalar(&self, s: &str) -> Edn {
+        if !s.starts_with('~') || s.len() < 2 {
+            return Edn::Str(s.to_string());
+        }
+
+        let rest = &s[2..];
+        match &s[1..2] {
+            ":" => Edn::Key(format!(":{}", rest)),
+            "$" => Edn::Symbol(rest.to_string()),
+            "t" | "u" => Edn::Str(rest.to_string()),
+            "m" => rest
+                .parse::<i64>()
+                .ok()
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|t| Edn::Str(t.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)))
+                .unwrap_or_else(|| Edn::Str(rest.to_string())),
+            "i" | "n" => rest
+                .parse::<isize>()
+                .map(Edn::Int)
+                .unwrap_or_else(|_| Edn::Str(rest.to_string())),
+            "d" | "f" => rest
+                .parse::<f64>()
+                .map(|f| Edn::Double(Double::from(f)))
+                .unwrap_or_else(|_| Edn::Str(rest.to_string())),
+            "?" => Edn::Bool(rest == "t"),
+            "_" => Edn::Nil,
+            "c" => rest.chars().next().map(Edn::Char).unwrap_or(Edn::Nil),
+            "~" | "^" | "`" => Edn::Str(s[1..].to_string()),
+            _ => Edn::Str(s.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edn_is_untouched() {
+        let body = "[[:crux.tx/put {:crux.db/id :jorge-3, :name \"Jorge\", }]]";
+
+        assert_eq!(WireFormat::Edn.encode(body).unwrap(), body);
+        assert_eq!(
+            WireFormat::Edn.decode("{:a 1}").unwrap(),
+            Edn::from_str("{:a 1}").unwrap()
+        );
+        assert_eq!(WireFormat::default().content_type(), "application/edn");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_roundtrip() {
+        let body = "[[:crux.tx/put {:crux.db/id :jorge-3, :name \"Jorge\", :age 33}]]";
+        let expected =
+            "[[\"crux.tx/put\",{\"age\":33,\"crux.db/id\":\"jorge-3\",\"name\":\"Jorge\"}]]";
+
+        assert_eq!(WireFormat::Json.encode(body).unwrap(), expected);
+
+        let edn = WireFormat::Json
+            .decode("{\"crux.tx/tx-id\": 8, \"crux.tx/tx-time\": \"2020-07-16T21:53:14.628Z\"}")
+            .unwrap();
+        assert_eq!(edn[":crux.tx/tx-id"], Edn::UInt(8));
+        assert_eq!(
+            edn[":crux.tx/tx-time"],
+            Edn::Str("2020-07-16T21:53:14.628Z".to_string())
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn transit_encode() {
+        let body = "{:query {:find [?p] :where [[?p :name \"~Jorge\"]]} :args #{1}}";
+        let expected = "[\"^ \",\"~:args\",[\"~#set\",[1]],\"~:query\",[\"^ \",\"~:find\",[\"~$?p\"],\"~:where\",[[\"~$?p\",\"~:name\",\"~~Jorge\"]]]]";
+
+        assert_eq!(WireFormat::TransitJson.encode(body).unwrap(), expected);
+        assert_eq!(
+            WireFormat::TransitJson.encode(":a").unwrap(),
+            "[\"~#'\",\"~:a\"]"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn transit_decode_with_cache() {
+        let body = r#"[["^ ","~:crux.db/id",["~#crux/id","jorge-3"],"~:crux.tx/tx-time","~t2020-07-16T21:53:14.628Z"],["^ ","^0",["^1","ivan"],"^2","~m0"]]"#;
+        let edn = WireFormat::TransitJson.decode(body).unwrap();
+
+        assert_eq!(edn[0][":crux.db/id"], Edn::Str("jorge-3".to_string()));
+        assert_eq!(
+            edn[0][":crux.tx/tx-time"],
+            Edn::Str("2020-07-16T21:53:14.628Z".to_string())
+        );
+        assert_eq!(edn[1][":crux.db/id"], Edn::Str("ivan".to_string()));
+        assert_eq!(
+            edn[1][":crux.tx/tx-time"],
+            Edn::Str("1970-01-01T00:00:00.000Z".to_string())
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn transit_set_and_keywords() {
+        let edn = WireFormat::TransitJson
+            .decode(r#"["~#set",[["~:jorge","~$?p",1]]]"#)
+            .unwrap();
+        let expected = Edn::from_str("#{[:jorge ?p 1]}").unwrap();
+
+        assert_eq!(edn, expected);
+    }
+}
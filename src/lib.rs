@@ -1,8 +1,12 @@
+// Allows `CruxDocument` generated code, which refers to `::transistor`, to be used inside this crate.
+extern crate self as transistor;
+
 pub use edn_rs;
 
 /// Generic Request/Response Types for Crux.
 /// Availables types are:
 /// * `CruxId` is the field that receives a String and serielizes it to a EDN Keyword.
+/// * `CruxDocument` derives `Serialize` and `Deserialize` for Crux documents, with attributes `#[crux(id)]`, `#[crux(key = ":person/name")]`, `#[crux(skip_if_none)]`, `#[crux(rename_all = "kebab-case")]` and `#[crux(namespace = "person")]`.
 ///
 /// Availables types for responses in module `types::response` are:
 /// * `StateResponse` response for Crux REST API at endpoint `/state`.
@@ -17,12 +21,13 @@ pub use edn_rs;
 /// * Enum [`Order`](../types/http/enum.Order.html)  is available in this module to be used with `entity_history`.
 /// * Enum [`TimeHistory`](../types/http/enum.TimeHistory.html)  is available in this module to be used with `entity_history_timed`.
 ///
-/// It is possible to use `chrono`  for time related responses (`TxLogResponse`, `EntityTxResponse`, `EntityHistoryElement`). to use it you need to enable feature `"time".
+/// Times in responses (`TxLogResponse`, `EntityTxResponse`, `EntityHistoryElement`) are [`CruxInstant`](../types/instant/struct.CruxInstant.html)s, which convert to `chrono` types. With feature `time`, they also convert to and from `time::OffsetDateTime`.
 pub mod types;
 
-/// Http Client  module. It contains the [`HttpClient`](../http/struct.HttpClient.html#impl) for Docker and Standalone HTTP Server.
+/// Http Client  module. It contains the [`BlockingHttpClient`](../http/struct.BlockingHttpClient.html#impl) and, with feature `async`, the [`AsyncHttpClient`](../http/struct.AsyncHttpClient.html#impl) for Docker and Standalone HTTP Server.
+/// [`HttpClient`](../http/type.HttpClient.html) is an alias to `BlockingHttpClient`, or to `AsyncHttpClient` when feature `async` is enabled. Both clients can coexist in the same binary.
 ///
-/// `HttpClient` Contains the following functions:
+/// Both clients contain the following functions:
 /// * `tx_log` requests endpoint `/tx-log` via `POST`. A Vector of `types::http::Action` is expected as argument.
 /// * `tx_logs` requests endpoint `/tx-log` via `GET`. No args.
 /// * `entity` requests endpoint `/entity` via `POST`. A serialized `CruxId`, serialized `Edn::Key` or a String containing a [`keyword`](https://github.com/edn-format/edn#keywords) must be passed as argument.
@@ -32,10 +37,20 @@ pub mod types;
 /// * `entity_history` requests endpoint `/entity-history` via `GET`. Arguments are the `crux.db/id` as a `String`, an ordering argument defined by the enum `types::http::Order` (`Asc` or `Desc`) and a boolean for the `with-docs?` flag (this returns values for the field `:crux.db/doc`).
 /// * `entity_history_timed` similar to `entity_history`, but receives one more argument that is a `Vec<TimeHistory>` to define `valid-time` and `transaction-time`
 /// * `query` requests endpoint `/query` via `POST`. Argument is a `query` of the type `Query`. Retrives a Set containing a vector of the values defined by the function `Query::find`.
-/// * All endpoints support async calls when `--feature "async"` is enabled, check [`async_<...>` examples](https://github.com/naomijub/transistor/tree/master/examples) for usage. [Tokio runtime](https://docs.rs/tokio/0.2.22/tokio/) is required.
+/// * All endpoints support async calls on `AsyncHttpClient` when `--feature "async"` is enabled, check [`async_<...>` examples](https://github.com/naomijub/transistor/tree/master/examples) for usage. `AsyncHttpClient` is built on `reqwest::Client`, so it must run inside a [Tokio 1.x runtime](https://docs.rs/tokio/1/tokio/). Transistor does not start or depend on a runtime itself.
+///
+/// Clients send their requests through a [`CruxTransport`](../http/transport/trait.CruxTransport.html), or an [`AsyncCruxTransport`](../http/transport/trait.AsyncCruxTransport.html) for `AsyncHttpClient`.
+/// `ReqwestTransport` and `AsyncReqwestTransport` are the default implementations, custom transports can be used with `Crux::blocking_client_with` and `Crux::async_client_with`.
 ///
 /// Examples can be found in the [examples directory](https://github.com/naomijub/transistor/tree/master/examples).
 pub mod http;
 
+/// In-memory Crux node and HTTP fixtures for tests, feature `testing` is required.
+/// * [`FakeCrux`](../testing/struct.FakeCrux.html) is a `CruxTransport` that applies `Actions` to a bitemporal document store, so tests can run without a Crux node or Docker.
+/// * [`RecordingTransport`](../testing/struct.RecordingTransport.html) records request/response pairs against a real node into a fixture file, `Crux::recording_client`.
+/// * [`ReplayTransport`](../testing/struct.ReplayTransport.html) serves a recorded fixture offline, `Crux::replay_client`.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 /// This module contains the basic client, struct `Crux`, which configures `host:port` and `authorization`, and returns the needed `client`.
 pub mod client;
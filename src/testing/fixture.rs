@@ -0,0 +1,401 @@
+#[cfg(feature = "async")]
+use crate::http::transport::{AsyncCruxTransport, TransportFuture};
+use crate::http::transport::{CruxRequest, CruxResponse, CruxTransport, Method};
+use crate::types::error::CruxError;
+use edn_rs::Edn;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// A recorded request/response pair.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Exchange {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) body: Option<String>,
+    pub(crate) status: u16,
+    pub(crate) response: String,
+}
+
+impl Exchange {
+    fn matches(&self, request: &CruxRequest) -> bool {
+        self.method == request.method
+            && normalize_path(&self.path) == normalize_path(&request.path)
+            && self.body.as_deref().map(normalize_body)
+                == request.body.as_deref().map(normalize_body)
+    }
+
+    fn to_edn_string(&self) -> String {
+        let body = self
+            .body
+            .as_ref()
+            .map(|b| escape(b))
+            .unwrap_or_else(|| "nil".to_string());
+        format!(
+            "{{:method \"{}\"\n  :path {}\n  :body {}\n  :status {}\n  :response {}}}",
+            self.method,
+            escape(&self.path),
+            body,
+            self.status,
+            escape(&self.response)
+        )
+    }
+
+    fn from_edn(edn: &Edn) -> Result<Self, CruxError> {
+        let method = match &edn[":method"] {
+            Edn::Str(m) if m == "GET" => Method::Get,
+            Edn::Str(m) if m == "POST" => Method::Post,
+            m => return Err(fixture_error(format!("Unknown method {}", m))),
+        };
+        let string = |key: &str| match &edn[key] {
+            Edn::Str(s) => Ok(s.clone()),
+            e => Err(fixture_error(format!(
+                "{} should be a string, found {}",
+                key, e
+            ))),
+        };
+        let status = match &edn[":status"] {
+            Edn::Int(s) => *s as u16,
+            Edn::UInt(s) => *s as u16,
+            e => {
+                return Err(fixture_error(format!(
+                    ":status should be a number, found {}",
+                    e
+                )))
+            }
+        };
+
+        Ok(Self {
+            method,
+            path: string(":path")?,
+            body: match edn[":body"] {
+                Edn::Nil => None,
+                _ => Some(string(":body")?),
+            },
+            status,
+            response: string(":response")?,
+        })
+    }
+}
+
+/// `RecordingTransport` sends requests through `inner` and writes every request/response pair to the `fixture` file,
+/// so they can later be served by a [`ReplayTransport`](struct.ReplayTransport.html) without a Crux node.
+/// The fixture is rewritten after each request and is an EDN vector of `{:method :path :body :status :response}` maps.
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    inner: T,
+    fixture: PathBuf,
+    exchanges: Mutex<Vec<Exchange>>,
+}
+
+impl<T> RecordingTransport<T> {
+    pub fn new<P: AsRef<Path>>(inner: T, fixture: P) -> Self {
+        Self {
+            inner,
+            fixture: fixture.as_ref().to_path_buf(),
+            exchanges: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn record(
+        &self,
+        request: CruxRequest,
+        response: &Result<CruxResponse, CruxError>,
+    ) -> Result<(), CruxError> {
+        if let Ok(response) = response {
+            let mut exchanges = self
+                .exchanges
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            exchanges.push(Exchange {
+                method: request.method,
+                path: request.path,
+                body: request.body,
+                status: response.status,
+                response: response.body.clone(),
+            });
+            write_fixture(&self.fixture, &exchanges)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: CruxTransport> CruxTransport for RecordingTransport<T> {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        let response = self.inner.send(request.clone());
+        self.record(request, &response)?;
+        response
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncCruxTransport + Sync> AsyncCruxTransport for RecordingTransport<T> {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self.inner.send(request.clone()).await;
+            self.record(request, &response)?;
+            response
+        })
+    }
+}
+
+/// `ReplayTransport` serves the responses recorded by a [`RecordingTransport`](struct.RecordingTransport.html).
+/// Each request is answered by the first unused recorded pair with the same method, path and body.
+/// `#inst` times, time query params and tx ids are normalized before comparing, so timed requests recorded in another run still match.
+/// A request without a recorded pair fails with `CruxError::TransportError`.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    exchanges: Vec<Exchange>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayTransport {
+    /// Loads the recorded pairs from `fixture`.
+    pub fn from_file<P: AsRef<Path>>(fixture: P) -> Result<Self, CruxError> {
+        let content = fs::read_to_string(fixture.as_ref()).map_err(|e| {
+            fixture_error(format!(
+                "Unable to read {}: {}",
+                fixture.as_ref().display(),
+                e
+            ))
+        })?;
+        Self::from_str(&content)
+    }
+
+    fn replay(&self, request: &CruxRequest) -> Result<CruxResponse, CruxError> {
+        let mut used = self
+            .used
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let index = self
+            .exchanges
+            .iter()
+            .enumerate()
+            .position(|(i, exchange)| !used[i] && exchange.matches(request))
+            .ok_or_else(|| {
+                CruxError::TransportError(format!(
+                    "No recorded response for {} {} with body {:?}",
+                    request.method, request.path, request.body
+                ))
+            })?;
+        used[index] = true;
+
+        let exchange = &self.exchanges[index];
+        Ok(CruxResponse::new(exchange.status, &exchange.response))
+    }
+}
+
+impl FromStr for ReplayTransport {
+    type Err = CruxError;
+
+    /// Parses the content of a fixture file.
+    fn from_str(fixture: &str) -> Result<Self, CruxError> {
+        let exchanges = Edn::from_str(fixture)?
+            .iter()
+            .ok_or_else(|| fixture_error("Fixture should be a vector".to_string()))?
+            .map(Exchange::from_edn)
+            .collect::<Result<Vec<Exchange>, CruxError>>()?;
+
+        Ok(Self {
+            used: Mutex::new(vec![false; exchanges.len()]),
+            exchanges,
+        })
+    }
+}
+
+impl CruxTransport for ReplayTransport {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        self.replay(&request)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncCruxTransport for ReplayTransport {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        let response = self.replay(&request);
+        Box::pin(async move { response })
+    }
+}
+
+fn fixture_error(message: String) -> CruxError {
+    CruxError::TransportError(message)
+}
+
+fn write_fixture(fixture: &Path, exchanges: &[Exchange]) -> Result<(), CruxError> {
+    let content = format!(
+        "[{}]\n",
+        exchanges
+            .iter()
+            .map(Exchange::to_edn_string)
+            .collect::<Vec<String>>()
+            .join("\n ")
+    );
+    if let Some(dir) = fixture.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| fixture_error(e.to_string()))?;
+    }
+    fs::write(fixture, content)
+        .map_err(|e| fixture_error(format!("Unable to write {}: {}", fixture.display(), e)))
+}
+
+/// EDN string literal for `value`.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Replaces time and tx id query param values, `valid-time=<time>`.
+fn normalize_path(path: &str) -> String {
+    let mut parts = path.splitn(2, '?');
+    let endpoint = parts.next().unwrap_or_default();
+    match parts.next() {
+        None => endpoint.to_string(),
+        Some(params) => {
+            let params = params
+                .split('&')
+                .map(|param| match param.split_once('=') {
+                    Some((key, _)) if key.ends_with("time") => format!("{}=<time>", key),
+                    Some((key, _)) if key == "tx-id" => format!("{}=<tx-id>", key),
+                    _ => param.to_string(),
+                })
+                .collect::<Vec<String>>();
+            format!("{}?{}", endpoint, params.join("&"))
+        }
+    }
+}
+
+/// Replaces `#inst "..."` and `:crux.tx/tx-id 1` values, `#inst "<time>"` and `:crux.tx/tx-id <tx-id>`.
+fn normalize_body(body: &str) -> String {
+    let mut normalized = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("#inst") {
+        normalized.push_str(&rest[..start]);
+        let after = &rest[start + "#inst".len()..];
+        match after
+            .find('"')
+            .and_then(|open| after[open + 1..].find('"').map(|close| open + close + 2))
+        {
+            Some(end) => {
+                normalized.push_str("#inst \"<time>\"");
+                rest = &after[end..];
+            }
+            None => {
+                normalized.push_str("#inst");
+                rest = after;
+            }
+        }
+    }
+    normalized.push_str(rest);
+
+    let mut result = String::with_capacity(normalized.len());
+    let mut rest = normalized.as_str();
+    while let Some(start) = rest.find(":crux.tx/tx-id") {
+        let key_end = start + ":crux.tx/tx-id".len();
+        result.push_str(&rest[..key_end]);
+        let after = rest[key_end..].trim_start();
+        let digits = after.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 {
+            result.push_str(" <tx-id>");
+            rest = &after[digits..];
+        } else {
+            rest = &rest[key_end..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Crux;
+    use crate::testing::support::person;
+    use crate::testing::FakeCrux;
+    use crate::types::{http::Actions, CruxId};
+    use chrono::prelude::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("transistor-{}-{}.edn", name, std::process::id()))
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let path = fixture("record-and-replay");
+        let person = person("jorge-3", "Mich\"ael\n", "Jorge");
+        let recorder = Crux::new("localhost", "3000")
+            .blocking_client_with(RecordingTransport::new(FakeCrux::new(), &path));
+        let recorded_tx = recorder
+            .tx_log(Actions::new().append_put(person.clone()))
+            .unwrap();
+        let recorded = recorder.entity(CruxId::new("jorge-3")).unwrap();
+
+        let replay = Crux::new("localhost", "3000")
+            .blocking_client_with(ReplayTransport::from_file(&path).unwrap());
+        let tx = replay.tx_log(Actions::new().append_put(person)).unwrap();
+        let entity = replay.entity(CruxId::new("jorge-3")).unwrap();
+        let missing = replay.entity(CruxId::new("jorge-3")).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(tx, recorded_tx);
+        assert_eq!(entity, recorded);
+        assert_eq!(entity[":first-name"], Edn::Str("Mich\"ael\n".to_string()));
+        assert_eq!(
+            format!("{}", missing),
+            "No recorded response for POST /entity with body Some(\"{:eid :jorge-3}\")"
+        );
+    }
+
+    #[test]
+    fn replay_normalizes_times() {
+        let path = fixture("replay-normalizes-times");
+        let person = person("jorge-3", "Michael", "Jorge");
+        let recorder = Crux::new("localhost", "3000")
+            .blocking_client_with(RecordingTransport::new(FakeCrux::new(), &path));
+        recorder
+            .tx_log(Actions::new().append_put_timed(person.clone(), Utc::now().into()))
+            .unwrap();
+        recorder
+            .entity_timed(CruxId::new("jorge-3"), None, Some(Utc::now().into()))
+            .unwrap();
+
+        let replay = Crux::new("localhost", "3000")
+            .blocking_client_with(ReplayTransport::from_file(&path).unwrap());
+        let valid_time = "2014-11-28T21:00:09+09:00"
+            .parse::<DateTime<FixedOffset>>()
+            .unwrap();
+        let tx = replay.tx_log(Actions::new().append_put_timed(person, valid_time));
+        let entity = replay.entity_timed(CruxId::new("jorge-3"), None, Some(valid_time));
+        fs::remove_file(&path).unwrap();
+
+        assert!(tx.is_ok());
+        assert!(entity.is_ok());
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            normalize_path("/entity?transaction-time=2020-08-09T18:05:29%2B00:00&valid-time=2020-08-09T18:05:29%2B00:00"),
+            "/entity?transaction-time=<time>&valid-time=<time>"
+        );
+        assert_eq!(
+            normalize_body("[[:crux.tx/put {:a 1} #inst \"2020-08-09T18:05:29+00:00\"]]"),
+            "[[:crux.tx/put {:a 1} #inst \"<time>\"]]"
+        );
+        assert_eq!(
+            normalize_body("{:crux.tx/tx-id 8, :crux.tx/tx-time #inst \"2020\"}"),
+            "{:crux.tx/tx-id <tx-id>, :crux.tx/tx-time #inst \"<time>\"}"
+        );
+    }
+}
@@ -0,0 +1,1175 @@
+use crate::client::Crux;
+#[cfg(feature = "async")]
+use crate::http::transport::{AsyncCruxTransport, TransportFuture};
+use crate::http::transport::{CruxRequest, CruxResponse, CruxTransport, Method};
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
+use crate::http::BlockingHttpClient;
+use crate::types::edn_text;
+use crate::types::error::CruxError;
+use chrono::prelude::*;
+use edn_rs::{Edn, Map, Vector};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+mod fixture;
+mod query;
+mod store;
+#[cfg(test)]
+pub(crate) mod support;
+
+pub use fixture::{RecordingTransport, ReplayTransport};
+
+use store::{format_time, parse_time, QueryRecord, Store, Tx, Version};
+
+/// `FakeCrux` is an in-memory Crux node that implements `CruxTransport`, and `AsyncCruxTransport` with feature `async`.
+/// It applies `Actions` to a bitemporal document store and answers `/status`, `tx_log`, `tx_logs`, `await_tx`, `tx_committed`, `latest_completed_tx`, `entity`, `entity_tx`, `entity_history` and `query`, timed and speculative variants included.
+/// Answered queries are listed by `recent_queries` and `slowest_queries`, `active_queries` is always empty.
+/// Queries support triple patterns `[?e :attribute ?v]`, `:args`, `:order-by`, `:limit`, `:offset` and `:full-results?`. Aggregates, predicates and rules respond with status 400.
+///
+/// Clones share the same store, so a clone can be handed to a client and the original kept for more clients.
+/// ```rust
+/// use edn_derive::Serialize;
+/// use transistor::testing::FakeCrux;
+/// use transistor::types::{Actions, CruxId};
+///
+/// #[derive(Debug, Clone, Serialize)]
+/// #[allow(non_snake_case)]
+/// struct Person {
+///     crux__db___id: CruxId,
+///     name: String,
+/// }
+///
+/// let client = FakeCrux::new().client();
+/// let person = Person { crux__db___id: CruxId::new("ivan"), name: "Ivan".to_string() };
+/// client.tx_log(Actions::new().append_put(person)).unwrap();
+///
+/// let entity = client.entity(CruxId::new("ivan")).unwrap();
+/// assert_eq!(entity[":name"], transistor::edn_rs::Edn::Str("Ivan".to_string()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FakeCrux {
+    store: Arc<Mutex<Store>>,
+}
+
+impl FakeCrux {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `BlockingHttpClient` backed by this node.
+    pub fn client(&self) -> BlockingHttpClient<FakeCrux> {
+        Crux::new("localhost", "3000").blocking_client_with(self.clone())
+    }
+
+    /// `AsyncHttpClient` backed by this node.
+    #[cfg(feature = "async")]
+    pub fn async_client(&self) -> AsyncHttpClient<FakeCrux> {
+        Crux::new("localhost", "3000").async_client_with(self.clone())
+    }
+
+    fn handle(&self, request: CruxRequest) -> CruxResponse {
+        let (path, params) = split_path(&request.path);
+        let body = request.body.unwrap_or_default();
+        let mut store = self
+            .store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let result = match (request.method, path.as_str()) {
+            (Method::Post, "/tx-log") => parse_body(&body)
+                .and_then(|ops| store.submit(&ops).map_err(bad_request))
+                .map(|tx| tx_response(&tx, false)),
+            (Method::Get, "/tx-log") => Ok(Edn::Vector(Vector::new(
+                store.txs.iter().map(|tx| tx_response(tx, true)).collect(),
+            ))),
+            (Method::Get, "/await-tx") => params
+                .get("tx-id")
+                .and_then(|id| id.parse::<usize>().ok())
+                .and_then(|id| store.txs.iter().find(|tx| tx.tx_id == id))
+                .map(|tx| tx_response(tx, false))
+                .ok_or_else(|| bad_request("Unknown `tx-id`".to_string())),
+            (Method::Get, "/status") => Ok(status()),
+            (Method::Get, "/tx-committed") => params
+                .get("tx-id")
+                .and_then(|id| id.parse::<usize>().ok())
+                .and_then(|id| store.txs.iter().find(|tx| tx.tx_id == id))
+                .map(|tx| Edn::Bool(tx.committed))
+                .ok_or_else(|| bad_request("Unknown `tx-id`".to_string())),
+            (Method::Get, "/latest-completed-tx") => Ok(store
+                .txs
+                .last()
+                .map(|tx| tx_response(tx, false))
+                .unwrap_or(Edn::Nil)),
+            (Method::Post, "/entity") => {
+                basis(&store, &params).and_then(|(valid_time, tx_time)| {
+                    let id = eid(&body)?;
+                    store
+                        .doc_at(&id, valid_time, tx_time)
+                        .cloned()
+                        .ok_or_else(not_found)
+                })
+            }
+            (Method::Post, "/entity-tx") => {
+                basis(&store, &params).and_then(|(valid_time, tx_time)| {
+                    let id = eid(&body)?;
+                    store
+                        .version_at(&id, valid_time, tx_time)
+                        .map(|version| entity_tx_response(&id, version))
+                        .ok_or_else(not_found)
+                })
+            }
+            (Method::Get, p) if p.starts_with("/entity-history/") => {
+                entity_history(&store, &p["/entity-history/".len()..], &params)
+            }
+            (Method::Post, "/query") => parse_body(&body).and_then(|query| {
+                let started_at = Utc::now();
+                let result = run_query(&store, &query, &params);
+                let record = query_record(store.queries.len(), &query, started_at, &result);
+                store.queries.push(record);
+                result
+            }),
+            (Method::Get, "/active-queries") => Ok(Edn::Vector(Vector::new(Vec::new()))),
+            (Method::Get, "/recent-queries") => Ok(Edn::Vector(Vector::new(
+                store.queries.iter().rev().map(|q| q.info.clone()).collect(),
+            ))),
+            (Method::Get, "/slowest-queries") => {
+                let mut queries = store.queries.clone();
+                queries.sort_by_key(|q| std::cmp::Reverse(q.duration));
+                Ok(Edn::Vector(Vector::new(
+                    queries.into_iter().map(|q| q.info).collect(),
+                )))
+            }
+            _ => Err(not_found()),
+        };
+
+        match result {
+            Ok(edn) => CruxResponse::new(200, &edn_text(&edn)),
+            Err(resp) => resp,
+        }
+    }
+}
+
+impl CruxTransport for FakeCrux {
+    fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+        Ok(self.handle(request))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncCruxTransport for FakeCrux {
+    fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+        let response = self.handle(request);
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+fn not_found() -> CruxResponse {
+    CruxResponse::new(404, "")
+}
+
+fn bad_request(message: String) -> CruxResponse {
+    CruxResponse::new(400, &format!("{{:crux.error/message {:?}}}", message))
+}
+
+fn parse_body(body: &str) -> Result<Edn, CruxResponse> {
+    Edn::from_str(body).map_err(|e| bad_request(e.to_string()))
+}
+
+fn eid(body: &str) -> Result<String, CruxResponse> {
+    match &parse_body(body)?[":eid"] {
+        Edn::Nil => Err(bad_request("Body requires `:eid`".to_string())),
+        eid => Ok(eid.to_string()),
+    }
+}
+
+fn split_path(path: &str) -> (String, BTreeMap<String, String>) {
+    let mut parts = path.splitn(2, '?');
+    let path = parts.next().unwrap_or_default().to_string();
+    let params = parts
+        .next()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let mut kv = p.splitn(2, '=');
+            (
+                kv.next().unwrap_or_default().to_string(),
+                percent_decode(kv.next().unwrap_or_default()),
+            )
+        })
+        .collect();
+    (path, params)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], value.get(i + 1..i + 3)) {
+            (b'%', Some(hex)) if u8::from_str_radix(hex, 16).is_ok() => {
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn time_param(
+    params: &BTreeMap<String, String>,
+    name: &str,
+) -> Result<Option<DateTime<Utc>>, CruxResponse> {
+    params
+        .get(name)
+        .map(|t| parse_time(t).map_err(bad_request))
+        .transpose()
+}
+
+/// Runs `query`, its `:with-tx` operations are applied to a copy of the store that is discarded afterwards.
+fn run_query(
+    store: &Store,
+    query: &Edn,
+    params: &BTreeMap<String, String>,
+) -> Result<Edn, CruxResponse> {
+    let speculative;
+    let store = match query.get(":with-tx") {
+        Some(ops) => {
+            let mut with_tx = store.clone();
+            with_tx.submit(ops).map_err(bad_request)?;
+            speculative = with_tx;
+            &speculative
+        }
+        None => store,
+    };
+    let (valid_time, tx_time) = basis(store, params)?;
+    query::run(store, query, valid_time, tx_time).map_err(bad_request)
+}
+
+// Queries run synchronously, so they are never active and are recorded once finished.
+fn query_record(
+    id: usize,
+    query: &Edn,
+    started_at: DateTime<Utc>,
+    result: &Result<Edn, CruxResponse>,
+) -> QueryRecord {
+    let finished_at = Utc::now();
+    let (status, error) = match result {
+        Ok(_) => (":completed", Edn::Nil),
+        Err(resp) => (":failed", Edn::Str(resp.body.clone())),
+    };
+    let mut info = BTreeMap::new();
+    info.insert(":query-id".to_string(), Edn::Str(format!("query-{}", id)));
+    info.insert(":query".to_string(), query[":query"].clone());
+    info.insert(
+        ":started-at".to_string(),
+        Edn::Inst(format_time(&started_at)),
+    );
+    info.insert(
+        ":finished-at".to_string(),
+        Edn::Inst(format_time(&finished_at)),
+    );
+    info.insert(":status".to_string(), Edn::Key(status.to_string()));
+    info.insert(":error".to_string(), error);
+
+    QueryRecord {
+        duration: finished_at - started_at,
+        info: Edn::Map(Map::new(info)),
+    }
+}
+
+/// Valid time and transaction time of a request, defaulting to the latest transaction.
+fn basis(
+    store: &Store,
+    params: &BTreeMap<String, String>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), CruxResponse> {
+    let latest = store.latest_tx_time().max(Utc::now());
+    let tx_time = time_param(params, "transaction-time")?.unwrap_or(latest);
+    let valid_time = time_param(params, "valid-time")?.unwrap_or(latest);
+    Ok((valid_time, tx_time))
+}
+
+fn entity_history(
+    store: &Store,
+    eid: &str,
+    params: &BTreeMap<String, String>,
+) -> Result<Edn, CruxResponse> {
+    let id = store.find_entity(eid).ok_or_else(not_found)?;
+    let with_docs = params.get("with-docs").map(String::as_str) == Some("true");
+    let with_corrections = params.get("with-corrections").map(String::as_str) == Some("true");
+    let start_valid = time_param(params, "start-valid-time")?;
+    let end_valid = time_param(params, "end-valid-time")?;
+    let start_tx = time_param(params, "start-transaction-time")?;
+    let end_tx = time_param(params, "end-transaction-time")?;
+    let desc = params.get("sort-order").map(String::as_str) == Some("desc");
+    // Like Crux, ranges go from an inclusive start to an exclusive end in sort order,
+    // so the start of a descending history is its latest time.
+    let in_range =
+        |time: DateTime<Utc>, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>| {
+            if desc {
+                start.is_none_or(|t| time <= t) && end.is_none_or(|t| time > t)
+            } else {
+                start.is_none_or(|t| time >= t) && end.is_none_or(|t| time < t)
+            }
+        };
+    // The latest transaction time bound is the basis of the corrections, the earliest one filters the history.
+    let (tx_latest, tx_earliest) = if desc {
+        ((start_tx, None), (None, end_tx))
+    } else {
+        ((None, end_tx), (start_tx, None))
+    };
+
+    let versions = store.entities[&id]
+        .iter()
+        .filter(|v| in_range(v.tx_time, tx_latest.0, tx_latest.1));
+    // Only the latest correction of each valid time is part of the history, unless `with-corrections` is set.
+    let mut history: Vec<&Version> = if with_corrections {
+        versions.collect()
+    } else {
+        let mut latest: BTreeMap<DateTime<Utc>, &Version> = BTreeMap::new();
+        for version in versions {
+            match latest.get(&version.valid_time) {
+                Some(v) if v.tx_time > version.tx_time => (),
+                _ => {
+                    latest.insert(version.valid_time, version);
+                }
+            }
+        }
+        latest.into_values().collect()
+    };
+    history.sort_by_key(|v| (v.valid_time, v.tx_id));
+
+    let mut history = history
+        .into_iter()
+        .filter(|v| in_range(v.valid_time, start_valid, end_valid))
+        .filter(|v| in_range(v.tx_time, tx_earliest.0, tx_earliest.1))
+        .map(|v| {
+            let mut element = BTreeMap::new();
+            element.insert(
+                ":crux.db/content-hash".to_string(),
+                Edn::Str(v.content_hash.clone()),
+            );
+            element.insert(
+                ":crux.db/valid-time".to_string(),
+                Edn::Inst(format_time(&v.valid_time)),
+            );
+            element.insert(":crux.tx/tx-id".to_string(), Edn::UInt(v.tx_id));
+            element.insert(
+                ":crux.tx/tx-time".to_string(),
+                Edn::Inst(format_time(&v.tx_time)),
+            );
+            if with_docs {
+                element.insert(
+                    ":crux.db/doc".to_string(),
+                    store.docs.get(&v.content_hash).cloned().unwrap_or(Edn::Nil),
+                );
+            }
+            Edn::Map(Map::new(element))
+        })
+        .collect::<Vec<Edn>>();
+
+    if desc {
+        history.reverse();
+    }
+    Ok(Edn::Vector(Vector::new(history)))
+}
+
+fn status() -> Edn {
+    let mut status = BTreeMap::new();
+    status.insert(
+        ":crux.version/version".to_string(),
+        Edn::Str("20.09-1.12.1-beta".to_string()),
+    );
+    status.insert(":crux.index/index-version".to_string(), Edn::UInt(13));
+    Edn::Map(Map::new(status))
+}
+
+fn tx_response(tx: &Tx, with_events: bool) -> Edn {
+    let mut response = BTreeMap::new();
+    response.insert(":crux.tx/tx-id".to_string(), Edn::UInt(tx.tx_id));
+    response.insert(
+        ":crux.tx/tx-time".to_string(),
+        Edn::Inst(format_time(&tx.tx_time)),
+    );
+    if with_events {
+        let events = tx
+            .events
+            .iter()
+            .map(|event| {
+                Edn::Vector(Vector::new(
+                    event.iter().map(|e| Edn::Str(e.clone())).collect(),
+                ))
+            })
+            .collect();
+        response.insert(
+            ":crux.tx.event/tx-events".to_string(),
+            Edn::Vector(Vector::new(events)),
+        );
+    }
+    Edn::Map(Map::new(response))
+}
+
+fn entity_tx_response(id: &str, version: &Version) -> Edn {
+    let mut response = BTreeMap::new();
+    response.insert(":crux.db/id".to_string(), Edn::Str(store::hash_hex(id)));
+    response.insert(
+        ":crux.db/content-hash".to_string(),
+        Edn::Str(version.content_hash.clone()),
+    );
+    response.insert(
+        ":crux.db/valid-time".to_string(),
+        Edn::Inst(format_time(&version.valid_time)),
+    );
+    response.insert(":crux.tx/tx-id".to_string(), Edn::UInt(version.tx_id));
+    response.insert(
+        ":crux.tx/tx-time".to_string(),
+        Edn::Inst(format_time(&version.tx_time)),
+    );
+    Edn::Map(Map::new(response))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "async")]
+    use crate::testing::support::seeded_async_client;
+    use crate::testing::support::{person, seeded_client};
+    use crate::types::http::{BatchLimit, Order, TimeHistory};
+    use crate::types::{
+        query::{Aggregate, PreparedQuery, Query},
+        response::QueryStatus,
+        Actions, CruxDocument, CruxId,
+    };
+    use chrono::Duration;
+
+    #[test]
+    fn tx_log_and_entity() {
+        let client = FakeCrux::new().client();
+        let actions = Actions::new()
+            .append_put(person("jorge-3", "Michael", "Jorge"))
+            .append_put(person("manuel-1", "Diego", "Manuel"));
+
+        let tx = client.tx_log(actions).unwrap();
+        let entity = client.entity(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(tx.tx___tx_id, 0);
+        assert_eq!(entity[":first-name"], Edn::Str("Michael".to_string()));
+        assert_eq!(entity[":crux.db/id"], Edn::Key(":jorge-3".to_string()));
+    }
+
+    #[test]
+    fn tx_logs_have_events() {
+        let client = seeded_client();
+        client
+            .tx_log(Actions::new().append_delete(CruxId::new("jorge-3")))
+            .unwrap();
+
+        let logs = client.tx_logs().unwrap().tx_events;
+        let events = logs[1].tx__event___tx_events.clone().unwrap();
+
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].tx___tx_time < logs[1].tx___tx_time);
+        assert_eq!(events[0][0], ":crux.tx/delete");
+    }
+
+    #[test]
+    fn crux_document() {
+        #[derive(Debug, Clone, PartialEq, CruxDocument)]
+        #[crux(rename_all = "kebab-case")]
+        struct Account {
+            #[crux(id)]
+            id: CruxId,
+            #[crux(key = ":account/amount")]
+            amount: isize,
+            #[crux(skip_if_none)]
+            owner: Option<String>,
+        }
+
+        let client = FakeCrux::new().client();
+        let account = Account {
+            id: CruxId::new("account-1"),
+            amount: 100,
+            owner: None,
+        };
+        client
+            .tx_log(Actions::new().append_put(account.clone()))
+            .unwrap();
+
+        let entity = client.entity(CruxId::new("account-1")).unwrap();
+
+        assert_eq!(edn_rs::from_edn::<Account>(&entity).unwrap(), account);
+        assert_eq!(entity.get(":owner"), None);
+    }
+
+    #[test]
+    fn entity_not_found() {
+        let client = seeded_client();
+        client
+            .tx_log(Actions::new().append_delete(CruxId::new("jorge-3")))
+            .unwrap();
+
+        let err = client.entity(CruxId::new("jorge-3")).unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "entity responded with 404 for id \":jorge-3\" "
+        );
+    }
+
+    #[test]
+    fn entity_timed_is_bitemporal() {
+        let client = FakeCrux::new().client();
+        let past = Utc::now() - Duration::days(10);
+        let actions = Actions::new()
+            .append_put_timed(person("jorge-3", "Michael", "Jorge"), past.into())
+            .append_put(person("jorge-3", "Mike", "Jorge"));
+        let tx = client.tx_log(actions).unwrap();
+
+        let now = client.entity(CruxId::new("jorge-3")).unwrap();
+        let before = client
+            .entity_timed(
+                CruxId::new("jorge-3"),
+                None,
+                Some((past + Duration::days(1)).into()),
+            )
+            .unwrap();
+        let before_tx = client
+            .entity_timed(
+                CruxId::new("jorge-3"),
+                Some(tx.tx___tx_time.to_datetime().unwrap() - Duration::days(1)),
+                None,
+            )
+            .unwrap_err();
+
+        assert_eq!(now[":first-name"], Edn::Str("Mike".to_string()));
+        assert_eq!(before[":first-name"], Edn::Str("Michael".to_string()));
+        assert_eq!(
+            format!("{}", before_tx),
+            "entity-timed responded with 404 for id \":jorge-3\" "
+        );
+    }
+
+    #[test]
+    fn bounded_put_covers_older_versions_in_its_range() {
+        let client = FakeCrux::new().client();
+        let day = |d: u32| Utc.with_ymd_and_hms(2020, 1, d, 0, 0, 0).unwrap();
+        client
+            .tx_log(
+                Actions::new()
+                    .append_put_timed(person("jorge-3", "Michael", "Jorge"), day(2).into()),
+            )
+            .unwrap();
+        client
+            .tx_log(Actions::new().append_put_timed_until(
+                person("jorge-3", "Mike", "Jorge"),
+                day(1).into(),
+                day(3).into(),
+            ))
+            .unwrap();
+        let name = |d: u32| {
+            client
+                .entity_timed(CruxId::new("jorge-3"), None, Some(day(d).into()))
+                .unwrap()[":first-name"]
+                .clone()
+        };
+
+        assert_eq!(name(1), Edn::Str("Mike".to_string()));
+        assert_eq!(name(2), Edn::Str("Mike".to_string()));
+        assert_eq!(name(3), Edn::Str("Michael".to_string()));
+        assert_eq!(name(4), Edn::Str("Michael".to_string()));
+    }
+
+    #[test]
+    fn entity_tx() {
+        let client = FakeCrux::new().client();
+        let tx = client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap();
+
+        let entity_tx = client.entity_tx(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(entity_tx.tx___tx_id, tx.tx___tx_id);
+        assert_eq!(entity_tx.tx___tx_time, tx.tx___tx_time);
+        assert_eq!(entity_tx.db___valid_time, tx.tx___tx_time);
+        assert_eq!(entity_tx.db___id.len(), 40);
+    }
+
+    #[test]
+    fn failed_match_discards_transaction() {
+        let client = seeded_client();
+        let actions = Actions::new()
+            .append_match_doc(CruxId::new("jorge-3"), person("jorge-3", "Mike", "Jorge"))
+            .append_put(person("jorge-3", "Diego", "Jorge"));
+        client.tx_log(actions).unwrap();
+        let actions = Actions::new()
+            .append_match_doc(
+                CruxId::new("jorge-3"),
+                person("jorge-3", "Michael", "Jorge"),
+            )
+            .append_put(person("jorge-3", "Mike", "Jorge"));
+        client.tx_log(actions).unwrap();
+
+        let entity = client.entity(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(entity[":first-name"], Edn::Str("Mike".to_string()));
+        assert_eq!(client.tx_logs().unwrap().tx_events.len(), 3);
+    }
+
+    #[test]
+    fn update_entity_retries_on_conflict() {
+        let fake = FakeCrux::new();
+        let client = fake.client();
+        let other = fake.client();
+        client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap();
+        let mut attempts = 0;
+
+        let tx = client
+            .update_entity(CruxId::new("jorge-3"), |doc| {
+                attempts += 1;
+                if attempts == 1 {
+                    // a concurrent write between the read and the transaction of the first attempt
+                    other
+                        .tx_log(Actions::new().append_put(person("jorge-3", "Mike", "Jorge")))
+                        .unwrap();
+                }
+                let first_name = match &doc[":first-name"] {
+                    Edn::Str(name) => name.clone(),
+                    _ => String::new(),
+                };
+                person("jorge-3", &format!("{}!", first_name), "Jorge")
+            })
+            .unwrap();
+
+        let entity = client.entity(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(attempts, 2);
+        assert_eq!(tx.tx___tx_id, 3);
+        assert!(client.tx_committed(1).unwrap());
+        assert!(!client.tx_committed(2).unwrap());
+        assert!(client.tx_committed(3).unwrap());
+        assert_eq!(entity[":first-name"], Edn::Str("Mike!".to_string()));
+    }
+
+    #[test]
+    fn update_entity_conflict() {
+        let fake = FakeCrux::new();
+        let client = fake.client();
+        let other = fake.client();
+        client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+            .unwrap();
+
+        let mut writes = 0;
+
+        let err = client
+            .update_entity_with_retries(CruxId::new("jorge-3"), 1, |_| {
+                writes += 1;
+                other
+                    .tx_log(Actions::new().append_put(person(
+                        "jorge-3",
+                        &format!("Mike {}", writes),
+                        "Jorge",
+                    )))
+                    .unwrap();
+                person("jorge-3", "Diego", "Jorge")
+            })
+            .unwrap_err();
+
+        assert!(matches!(err, CruxError::Conflict(_)));
+        assert_eq!(
+            format!("{}", err),
+            "update_entity of :jorge-3 was not committed after 2 attempts, the entity kept changing"
+        );
+        assert!(client.tx_committed(7).is_err());
+        assert!(client
+            .update_entity(CruxId::new("manuel-1"), |doc| doc.to_string())
+            .is_err());
+    }
+
+    // Document written as EDN, with values `edn_derive` can't produce.
+    struct RawDocument(&'static str);
+
+    impl edn_rs::Serialize for RawDocument {
+        fn serialize(self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    #[test]
+    fn update_entity_matches_tagged_values() {
+        let client = FakeCrux::new().client();
+        client
+            .tx_log(Actions::new().append_put(RawDocument(
+                "{:crux.db/id :jorge-3 :born #inst \"2020-01-01T00:00:00.000Z\" :initial \\j}",
+            )))
+            .unwrap();
+        let mut attempts = 0;
+
+        let tx = client
+            .update_entity(CruxId::new("jorge-3"), |_| {
+                attempts += 1;
+                RawDocument(
+                    "{:crux.db/id :jorge-3 :born #inst \"2020-01-01T00:00:00.000Z\" :initial \\m}",
+                )
+            })
+            .unwrap();
+        let entity = client.entity(CruxId::new("jorge-3")).unwrap();
+
+        assert_eq!(attempts, 1);
+        assert!(client.tx_committed(tx.tx___tx_id).unwrap());
+        assert_eq!(entity[":initial"], Edn::Char('m'));
+    }
+
+    #[test]
+    fn evict_removes_history() {
+        let client = seeded_client();
+        client
+            .tx_log(Actions::new().append_evict(CruxId::new("jorge-3")))
+            .unwrap();
+
+        assert!(client.entity(CruxId::new("jorge-3")).is_err());
+        assert!(client
+            .entity_history("jorge-3".to_string(), Order::Asc, false)
+            .is_err());
+    }
+
+    #[test]
+    fn entity_history() {
+        let client = FakeCrux::new().client();
+        let past = Utc::now() - Duration::days(10);
+        client
+            .tx_log(
+                Actions::new().append_put_timed(person("jorge-3", "Michael", "Jorge"), past.into()),
+            )
+            .unwrap();
+        client
+            .tx_log(Actions::new().append_put(person("jorge-3", "Mike", "Jorge")))
+            .unwrap();
+        let hash = client.entity_tx(CruxId::new("jorge-3")).unwrap().db___id;
+
+        let asc = client
+            .entity_history(hash.clone(), Order::Asc, true)
+            .unwrap()
+            .history;
+        let desc = client
+            .entity_history(hash.clone(), Order::Desc, false)
+            .unwrap()
+            .history;
+        let timed = client
+            .entity_history_timed(
+                hash,
+                Order::Asc,
+                false,
+                vec![TimeHistory::ValidTime(
+                    None,
+                    Some(Utc::now() - Duration::days(1)),
+                )],
+            )
+            .unwrap()
+            .history;
+
+        assert_eq!(asc.len(), 2);
+        assert_eq!(asc[0].tx___tx_id, 0);
+        assert_eq!(
+            asc[0].db__doc.as_ref().unwrap()[":first-name"],
+            Edn::Str("Michael".to_string())
+        );
+        assert_eq!(desc[0].tx___tx_id, 1);
+        assert_eq!(desc[0].db__doc, None);
+        assert_eq!(timed.len(), 1);
+    }
+
+    #[test]
+    fn entity_history_with_corrections() {
+        let client = FakeCrux::new().client();
+        let past = Utc::now() - Duration::days(10);
+        client
+            .tx_log(
+                Actions::new().append_put_timed(person("jorge-3", "Michael", "Jorge"), past.into()),
+            )
+            .unwrap();
+        let first = client.entity_tx(CruxId::new("jorge-3")).unwrap();
+        client
+            .tx_log(
+                Actions::new().append_put_timed(person("jorge-3", "Mike", "Jorge"), past.into()),
+            )
+            .unwrap();
+
+        let history = client
+            .entity_history_with_corrections(first.db___id.clone(), Order::Asc, false, Vec::new())
+            .unwrap()
+            .history;
+        let grid = client
+            .entity_bitemporal_grid(first.db___id, Vec::new())
+            .unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].coordinates().0, history[1].coordinates().0);
+        assert_eq!(grid.valid_times.len(), 1);
+        assert_eq!(
+            grid.get(&history[0].db___valid_time, &first.tx___tx_time)
+                .unwrap()
+                .db__doc
+                .as_ref()
+                .unwrap()[":first-name"],
+            Edn::Str("Michael".to_string())
+        );
+        assert_eq!(
+            grid.cells[0][1].as_ref().unwrap().db__doc.as_ref().unwrap()[":first-name"],
+            Edn::Str("Mike".to_string())
+        );
+    }
+
+    #[test]
+    fn query() {
+        let client = FakeCrux::new().client();
+        let actions = Actions::new()
+            .append_put(person("jorge-3", "Michael", "Jorge"))
+            .append_put(person("manuel-1", "Diego", "Manuel"))
+            .append_put(person("manuel-2", "Michael", "Manuel"));
+        client.tx_log(actions).unwrap();
+
+        let query = Query::find(vec!["?p", "?l"])
+            .unwrap()
+            .where_clause(vec!["?p :first-name ?n", "?p :last-name ?l"])
+            .unwrap()
+            .args(vec!["?n \"Michael\""])
+            .unwrap()
+            .build()
+            .unwrap();
+        let response = client.query(query).unwrap();
+
+        let expected = vec![
+            vec![":jorge-3".to_string(), "Jorge".to_string()],
+            vec![":manuel-2".to_string(), "Manuel".to_string()],
+        ];
+        assert_eq!(response.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn prepared_query() {
+        let client = FakeCrux::new().client();
+        let actions = Actions::new()
+            .append_put(person("jorge-3", "Michael", "Jorge"))
+            .append_put(person("manuel-1", "Diego", "Manuel"))
+            .append_put(person("manuel-2", "Michael", "Manuel"));
+        client.tx_log(actions).unwrap();
+        let by_name = PreparedQuery::new(
+            Query::find(vec!["?p"])
+                .unwrap()
+                .where_clause(vec!["?p :first-name ?n", "?p :last-name ?l"])
+                .unwrap()
+                .build()
+                .unwrap(),
+            vec!["?n", "?l"],
+        )
+        .unwrap();
+
+        let michael = client
+            .query(by_name.bind(&[&"Michael", &"Manuel"]).unwrap())
+            .unwrap();
+        let diego = client
+            .query(by_name.bind(&[&"Diego", &"Manuel"]).unwrap())
+            .unwrap();
+        let injected = client
+            .query(by_name.bind(&[&"Michael\" ?l \"Jorge", &"Manuel"]).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            michael.into_iter().collect::<Vec<_>>(),
+            vec![vec![":manuel-2".to_string()]]
+        );
+        assert_eq!(
+            diego.into_iter().collect::<Vec<_>>(),
+            vec![vec![":manuel-1".to_string()]]
+        );
+        assert!(injected.is_empty());
+    }
+
+    #[test]
+    fn query_order_by_limit_offset() {
+        let fake = FakeCrux::new();
+        let client = fake.client();
+        let actions = Actions::new()
+            .append_put(person("jorge-3", "Michael", "Jorge"))
+            .append_put(person("manuel-1", "Diego", "Manuel"))
+            .append_put(person("manuel-2", "Amanda", "Manuel"));
+        client.tx_log(actions).unwrap();
+
+        let query = Query::find(vec!["?n"])
+            .unwrap()
+            .where_clause(vec!["?p :first-name ?n"])
+            .unwrap()
+            .order_by(vec!["?n :desc"])
+            .unwrap()
+            .limit(2)
+            .offset(1)
+            .build()
+            .unwrap();
+        let resp =
+            CruxTransport::send(&fake, CruxRequest::post("/query", edn_rs::to_string(query)))
+                .unwrap();
+
+        assert_eq!(resp.body, "[[\"Diego\"] [\"Amanda\"]]");
+    }
+
+    #[test]
+    fn query_with_tx_is_not_committed() {
+        let client = seeded_client();
+        let query = || {
+            Query::find(vec!["?p"])
+                .unwrap()
+                .where_clause(vec!["?p :first-name \"Michael\""])
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+
+        let speculative = client
+            .query_with_tx(
+                query(),
+                Actions::new()
+                    .append_put(person("manuel-2", "Michael", "Manuel"))
+                    .append_delete(CruxId::new("jorge-3")),
+            )
+            .unwrap();
+        let committed = client.query(query()).unwrap();
+
+        assert_eq!(
+            speculative,
+            vec![vec![":manuel-2".to_string()]].into_iter().collect()
+        );
+        assert_eq!(
+            committed,
+            vec![vec![":jorge-3".to_string()]].into_iter().collect()
+        );
+        assert_eq!(client.tx_logs().unwrap().tx_events.len(), 1);
+    }
+
+    #[test]
+    fn query_with_tx_validates_actions() {
+        let client = FakeCrux::new().client();
+        let query = Query::find(vec!["?p"])
+            .unwrap()
+            .where_clause(vec!["?p :first-name \"Michael\""])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let resp = client.query_with_tx(query, Actions::new().append_evict(CruxId::new("")));
+
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn query_info() {
+        let client = seeded_client();
+        let query = |name: &str| {
+            Query::find(vec!["?p"])
+                .unwrap()
+                .where_clause(vec!["?p :first-name ?n"])
+                .unwrap()
+                .args(vec![&format!("?n \"{}\"", name)])
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+        client.query(query("Michael")).unwrap();
+        client.query(query("Diego")).unwrap();
+
+        let recent = client.recent_queries().unwrap();
+        let info = client.query_info(&query("Michael")).unwrap();
+
+        assert!(client.active_queries().unwrap().is_empty());
+        assert_eq!(recent.len(), 2);
+        assert_eq!(client.slowest_queries().unwrap().len(), 2);
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].query_id, "query-0");
+        assert_eq!(info[0].status, QueryStatus::Completed);
+        assert_eq!(info[0].error, None);
+        assert!(info[0].duration().unwrap() >= Duration::zero());
+        assert!(recent[0].is_query(&query("Diego")));
+    }
+
+    #[test]
+    fn unsupported_query() {
+        let client = FakeCrux::new().client();
+        let query = Query::find_by_aggregates(vec![Aggregate::Count("?p".to_string())])
+            .unwrap()
+            .where_clause(vec!["?p :first-name ?n"])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let err = client.query(query).unwrap_err();
+
+        assert_eq!(format!("{}", err), "query responded with 400");
+    }
+
+    fn people() -> Actions {
+        (0..5).fold(Actions::new(), |actions, i| {
+            actions.append_put(person(&format!("manuel-{}", i), "Diego", "Manuel"))
+        })
+    }
+
+    #[test]
+    fn tx_log_batched() {
+        let client = FakeCrux::new().client();
+
+        let txs = client
+            .tx_log_batched(people(), BatchLimit::Ops(2), true)
+            .unwrap();
+
+        assert_eq!(
+            txs.iter().map(|tx| tx.tx___tx_id).collect::<Vec<usize>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(client.tx_logs().unwrap().tx_events.len(), 3);
+        assert!(client.entity(CruxId::new("manuel-4")).is_ok());
+        assert_eq!(client.await_tx(2, None).unwrap(), txs[2]);
+        assert!(client.await_tx(3, None).is_err());
+    }
+
+    #[test]
+    fn tx_log_batched_validates_every_batch() {
+        let client = FakeCrux::new().client();
+
+        let txs = client.tx_log_batched(
+            people().append_evict(CruxId::new("")),
+            BatchLimit::Ops(2),
+            true,
+        );
+
+        assert!(txs.is_err());
+        assert!(client.tx_logs().unwrap().tx_events.is_empty());
+    }
+
+    // `FakeCrux` that fails its `fail`th `/tx-log` request, counting from 0, without handling it.
+    #[derive(Clone)]
+    struct FailingTxLog {
+        fake: FakeCrux,
+        fail: usize,
+        sent: Arc<Mutex<usize>>,
+    }
+
+    impl FailingTxLog {
+        fn new(fail: usize) -> Self {
+            Self {
+                fake: FakeCrux::new(),
+                fail,
+                sent: Arc::new(Mutex::new(0)),
+            }
+        }
+    }
+
+    impl CruxTransport for FailingTxLog {
+        fn send(&self, request: CruxRequest) -> Result<CruxResponse, CruxError> {
+            if request.path == "/tx-log" && request.method == Method::Post {
+                let mut sent = self.sent.lock().unwrap();
+                *sent += 1;
+                if *sent - 1 == self.fail {
+                    return Err(CruxError::TransportError("timed out".to_string()));
+                }
+            }
+            CruxTransport::send(&self.fake, request)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncCruxTransport for FailingTxLog {
+        fn send(&self, request: CruxRequest) -> TransportFuture<'_> {
+            let response = CruxTransport::send(self, request);
+            Box::pin(async move { response })
+        }
+    }
+
+    fn batch_failure(error: CruxError) -> (usize, Vec<(usize, usize)>) {
+        match error {
+            CruxError::TxLogBatchError {
+                failed, committed, ..
+            } => (
+                failed,
+                committed
+                    .iter()
+                    .map(|(batch, tx)| (*batch, tx.tx___tx_id))
+                    .collect(),
+            ),
+            e => panic!("expected TxLogBatchError, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn tx_log_batched_failure_lists_committed_batches() {
+        let transport = FailingTxLog::new(1);
+        let client = Crux::new("localhost", "3000").blocking_client_with(transport);
+
+        let err = client
+            .tx_log_batched(people(), BatchLimit::Ops(2), true)
+            .unwrap_err();
+
+        assert_eq!(batch_failure(err), (1, vec![(0, 0)]));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_tx_log_batched_failure_lists_later_committed_batches() {
+        let transport = FailingTxLog::new(0);
+        let client = Crux::new("localhost", "3000").async_client_with(transport);
+
+        let err = client
+            .tx_log_batched(people(), BatchLimit::Ops(2), 2, true)
+            .await
+            .unwrap_err();
+
+        assert!(format!("{}", err).starts_with("Batch at index 0 of 3 batches failed: timed out."));
+        assert_eq!(batch_failure(err), (0, vec![(1, 0)]));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_tx_log_batched() {
+        let client = FakeCrux::new().async_client();
+
+        let txs = client
+            .tx_log_batched(people(), BatchLimit::Ops(2), 2, true)
+            .await
+            .unwrap();
+
+        assert_eq!(txs.len(), 3);
+        assert!(client.entity(CruxId::new("manuel-4")).await.is_ok());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_update_entity() {
+        let client = seeded_async_client().await;
+
+        client
+            .update_entity(CruxId::new("jorge-3"), |_| {
+                person("jorge-3", "Mike", "Jorge")
+            })
+            .await
+            .unwrap();
+        let entity = client.entity(CruxId::new("jorge-3")).await.unwrap();
+
+        assert_eq!(entity[":first-name"], Edn::Str("Mike".to_string()));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_client() {
+        let client = seeded_async_client().await;
+
+        let entity = client.entity(CruxId::new("jorge-3")).await.unwrap();
+
+        assert_eq!(entity[":last-name"], Edn::Str("Jorge".to_string()));
+    }
+}
@@ -0,0 +1,191 @@
+use super::store::Store;
+use chrono::prelude::*;
+use edn_rs::{Edn, Set, Vector};
+use std::collections::{BTreeMap, BTreeSet};
+
+type Bindings = BTreeMap<String, Edn>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Var(String),
+    Const(Edn),
+    Blank,
+}
+
+impl From<&Edn> for Term {
+    fn from(edn: &Edn) -> Self {
+        match edn {
+            Edn::Symbol(s) if s == "_" => Term::Blank,
+            Edn::Symbol(s) if s.starts_with('?') => Term::Var(s.clone()),
+            e => Term::Const(e.clone()),
+        }
+    }
+}
+
+impl Term {
+    /// Unifies this term with `value`, returning the extended bindings or `None` on a mismatch.
+    fn unify(&self, value: &Edn, bindings: &Bindings) -> Option<Bindings> {
+        match self {
+            Term::Blank => Some(bindings.clone()),
+            Term::Const(c) if c == value => Some(bindings.clone()),
+            Term::Const(_) => None,
+            Term::Var(v) => match bindings.get(v) {
+                Some(bound) if bound == value => Some(bindings.clone()),
+                Some(_) => None,
+                None => {
+                    let mut bindings = bindings.clone();
+                    bindings.insert(v.clone(), value.clone());
+                    Some(bindings)
+                }
+            },
+        }
+    }
+}
+
+/// Answers a `/query` body with triple patterns `[e :attribute v]`, `:args`, `:order-by`,
+/// `:limit`, `:offset` and `:full-results?`. Aggregates, predicates and rules are rejected.
+pub(crate) fn run(
+    store: &Store,
+    body: &Edn,
+    valid_time: DateTime<Utc>,
+    tx_time: DateTime<Utc>,
+) -> Result<Edn, String> {
+    let query = &body[":query"];
+    let find = query[":find"]
+        .iter()
+        .ok_or_else(|| "Query requires `:find`".to_string())?
+        .map(|e| match e {
+            Edn::Symbol(s) if s.starts_with('?') => Ok(s.clone()),
+            e => Err(format!("FakeCrux does not support find element {}", e)),
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+    let clauses = query[":where"]
+        .iter()
+        .ok_or_else(|| "Query requires `:where`".to_string())?
+        .map(clause)
+        .collect::<Result<Vec<(Term, String, Term)>, String>>()?;
+
+    let docs = store
+        .entities
+        .keys()
+        .filter_map(|id| store.doc_at(id, valid_time, tx_time))
+        .collect::<Vec<&Edn>>();
+
+    let mut bindings = args(&query[":args"]);
+    for (e, attribute, v) in clauses.iter() {
+        bindings = bindings
+            .iter()
+            .flat_map(|b| {
+                docs.iter().flat_map(move |doc| {
+                    let b = e.unify(&doc[":crux.db/id"], b);
+                    values(doc, attribute)
+                        .into_iter()
+                        .filter_map(move |value| b.as_ref().and_then(|b| v.unify(value, b)))
+                })
+            })
+            .collect();
+    }
+
+    let rows = bindings
+        .iter()
+        .map(|b| {
+            find.iter()
+                .map(|var| {
+                    b.get(var)
+                        .cloned()
+                        .ok_or_else(|| format!("Find variable {} is not bound by `:where`", var))
+                })
+                .collect::<Result<Vec<Edn>, String>>()
+        })
+        .collect::<Result<BTreeSet<Vec<Edn>>, String>>()?;
+    let mut rows = rows.into_iter().collect::<Vec<Vec<Edn>>>();
+
+    let order_by = order_by(&query[":order-by"], &find)?;
+    for (index, desc) in order_by.iter().rev() {
+        rows.sort_by(|a, b| {
+            let ord = a[*index].cmp(&b[*index]);
+            if *desc {
+                ord.reverse()
+            } else {
+                ord
+            }
+        });
+    }
+
+    let offset = query[":offset"].to_uint().unwrap_or(0);
+    let limit = query[":limit"].to_uint().unwrap_or(usize::MAX);
+    let full_results = query[":full-results?"].to_bool().unwrap_or(false);
+    let rows = rows.into_iter().skip(offset).take(limit).map(|row| {
+        let row = row
+            .into_iter()
+            .map(
+                |value| match docs.iter().find(|d| d[":crux.db/id"] == value) {
+                    Some(doc) if full_results => (*doc).clone(),
+                    _ => value,
+                },
+            )
+            .collect::<Vec<Edn>>();
+        Edn::Vector(Vector::new(row))
+    });
+
+    if order_by.is_empty() {
+        Ok(Edn::Set(Set::new(rows.collect())))
+    } else {
+        Ok(Edn::Vector(Vector::new(rows.collect())))
+    }
+}
+
+fn clause(edn: &Edn) -> Result<(Term, String, Term), String> {
+    let elements = match edn {
+        Edn::Vector(v) => v.clone().to_vec(),
+        e => return Err(format!("FakeCrux does not support clause {}", e)),
+    };
+    match elements.as_slice() {
+        [e, Edn::Key(a)] => Ok((e.into(), a.clone(), Term::Blank)),
+        [e, Edn::Key(a), v] => Ok((e.into(), a.clone(), v.into())),
+        _ => Err(format!("FakeCrux does not support clause {}", edn)),
+    }
+}
+
+/// Values of `attribute` in `doc`. Vectors and sets are expanded into one value per element.
+fn values<'a>(doc: &'a Edn, attribute: &str) -> Vec<&'a Edn> {
+    match &doc[attribute] {
+        Edn::Nil => Vec::new(),
+        Edn::Vector(_) | Edn::List(_) => doc[attribute]
+            .iter()
+            .map(|i| i.collect())
+            .unwrap_or_default(),
+        Edn::Set(_) => doc[attribute]
+            .set_iter()
+            .map(|i| i.collect())
+            .unwrap_or_default(),
+        value => vec![value],
+    }
+}
+
+fn args(edn: &Edn) -> Vec<Bindings> {
+    match edn.iter() {
+        Some(args) => args
+            .filter_map(|arg| arg.map_iter())
+            .map(|arg| arg.map(|(k, v)| (k.clone(), v.clone())).collect())
+            .collect(),
+        None => vec![Bindings::new()],
+    }
+}
+
+fn order_by(edn: &Edn, find: &[String]) -> Result<Vec<(usize, bool)>, String> {
+    edn.iter()
+        .map(|order_by| {
+            order_by
+                .map(|o| {
+                    let var = o[0].to_string();
+                    let index = find
+                        .iter()
+                        .position(|f| *f == var)
+                        .ok_or_else(|| format!("Order by variable {} must be in `:find`", var))?;
+                    Ok((index, o[1] == Edn::Key(":desc".to_string())))
+                })
+                .collect()
+        })
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
@@ -0,0 +1,319 @@
+use chrono::prelude::*;
+use chrono::{Duration, SubsecRound};
+use edn_rs::Edn;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Content hash Crux uses for deleted documents.
+pub(crate) const NIL_HASH: &str = "0000000000000000000000000000000000000000";
+
+/// A single bitemporal version of an entity. `content_hash` is `NIL_HASH` for deletes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Version {
+    pub(crate) valid_time: DateTime<Utc>,
+    pub(crate) tx_time: DateTime<Utc>,
+    pub(crate) tx_id: usize,
+    pub(crate) content_hash: String,
+}
+
+/// A transaction as it appears in the tx log.
+#[derive(Debug, Clone)]
+pub(crate) struct Tx {
+    pub(crate) tx_id: usize,
+    pub(crate) tx_time: DateTime<Utc>,
+    pub(crate) events: Vec<Vec<String>>,
+    /// `false` if a `:crux.tx/match` failed and the operations were discarded.
+    pub(crate) committed: bool,
+}
+
+/// A query answered by `FakeCrux`, `info` is its `/recent-queries` entry.
+#[derive(Debug, Clone)]
+pub(crate) struct QueryRecord {
+    pub(crate) duration: Duration,
+    pub(crate) info: Edn,
+}
+
+/// In-memory bitemporal document store used by `FakeCrux`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Store {
+    pub(crate) txs: Vec<Tx>,
+    pub(crate) entities: BTreeMap<String, Vec<Version>>,
+    pub(crate) docs: HashMap<String, Edn>,
+    pub(crate) queries: Vec<QueryRecord>,
+}
+
+impl Store {
+    /// Applies a `tx-log` body to the store. The transaction is always appended to the tx log,
+    /// but its operations are discarded if a `:crux.tx/match` fails.
+    pub(crate) fn submit(&mut self, ops: &Edn) -> Result<Tx, String> {
+        let ops = ops
+            .iter()
+            .ok_or_else(|| format!("Transaction should be a vector of operations: {}", ops))?
+            .cloned()
+            .collect::<Vec<Edn>>();
+
+        let tx_id = self.txs.len();
+        let tx_time = self.next_tx_time();
+        let mut staged = self.clone();
+        let mut events = Vec::new();
+        let mut committed = true;
+
+        for op in ops.iter() {
+            let op = op
+                .iter()
+                .ok_or_else(|| format!("Operation should be a vector: {}", op))?
+                .cloned()
+                .collect::<Vec<Edn>>();
+            match op.first() {
+                Some(Edn::Key(k)) if k == ":crux.tx/put" => {
+                    events.push(staged.put(&op, tx_id, tx_time)?)
+                }
+                Some(Edn::Key(k)) if k == ":crux.tx/delete" => {
+                    events.push(staged.delete(&op, tx_id, tx_time)?)
+                }
+                Some(Edn::Key(k)) if k == ":crux.tx/evict" => events.push(staged.evict(&op)?),
+                Some(Edn::Key(k)) if k == ":crux.tx/match" => {
+                    let (event, matched) = staged.match_doc(&op, tx_time)?;
+                    events.push(event);
+                    committed = committed && matched;
+                }
+                _ => return Err(format!("Unknown transaction operation: {:?}", op)),
+            }
+        }
+
+        let tx = Tx {
+            tx_id,
+            tx_time,
+            events,
+            committed,
+        };
+        if committed {
+            self.entities = staged.entities;
+            self.docs = staged.docs;
+        }
+        self.txs.push(tx.clone());
+        Ok(tx)
+    }
+
+    /// Latest transaction time, or now if there are no transactions.
+    pub(crate) fn latest_tx_time(&self) -> DateTime<Utc> {
+        self.txs
+            .last()
+            .map(|tx| tx.tx_time)
+            .unwrap_or_else(Utc::now)
+    }
+
+    /// Entity version visible at `valid_time` as of `tx_time`. Deleted entities are `None`.
+    pub(crate) fn version_at(
+        &self,
+        id: &str,
+        valid_time: DateTime<Utc>,
+        tx_time: DateTime<Utc>,
+    ) -> Option<&Version> {
+        self.entities
+            .get(id)?
+            .iter()
+            .filter(|v| v.valid_time <= valid_time && v.tx_time <= tx_time)
+            .max_by_key(|v| (v.valid_time, v.tx_time))
+            .filter(|v| v.content_hash != NIL_HASH)
+    }
+
+    /// Document visible at `valid_time` as of `tx_time`.
+    pub(crate) fn doc_at(
+        &self,
+        id: &str,
+        valid_time: DateTime<Utc>,
+        tx_time: DateTime<Utc>,
+    ) -> Option<&Edn> {
+        self.version_at(id, valid_time, tx_time)
+            .and_then(|v| self.docs.get(&v.content_hash))
+    }
+
+    /// Finds an entity by its id, `:ivan`, its id without `:`, `ivan`, or its id hash.
+    pub(crate) fn find_entity(&self, eid: &str) -> Option<String> {
+        self.entities
+            .keys()
+            .find(|k| k.as_str() == eid || k.trim_start_matches(':') == eid || hash_hex(k) == eid)
+            .cloned()
+    }
+
+    fn next_tx_time(&self) -> DateTime<Utc> {
+        // Crux responds with millisecond precision, so tx times are kept distinct at that precision.
+        let now = Utc::now().trunc_subsecs(3);
+        match self.txs.last() {
+            Some(last) if last.tx_time >= now => last.tx_time + Duration::milliseconds(1),
+            _ => now,
+        }
+    }
+
+    fn put(
+        &mut self,
+        op: &[Edn],
+        tx_id: usize,
+        tx_time: DateTime<Utc>,
+    ) -> Result<Vec<String>, String> {
+        let doc = op
+            .get(1)
+            .filter(|d| matches!(d, Edn::Map(_)))
+            .ok_or_else(|| "`:crux.tx/put` requires a document".to_string())?;
+        let id = doc
+            .get(":crux.db/id")
+            .ok_or_else(|| format!("Document {} has no `:crux.db/id`", doc))?
+            .to_string();
+        let valid_time = op_time(op.get(2))?.unwrap_or(tx_time);
+        let end_valid_time = op_time(op.get(3))?;
+        let content_hash = hash_hex(&doc.to_string());
+
+        self.docs.insert(content_hash.clone(), doc.clone());
+        self.insert_range(
+            &id,
+            valid_time,
+            end_valid_time,
+            tx_id,
+            tx_time,
+            &content_hash,
+        );
+        Ok(vec![
+            ":crux.tx/put".to_string(),
+            hash_hex(&id),
+            content_hash,
+        ])
+    }
+
+    fn delete(
+        &mut self,
+        op: &[Edn],
+        tx_id: usize,
+        tx_time: DateTime<Utc>,
+    ) -> Result<Vec<String>, String> {
+        let id = op
+            .get(1)
+            .ok_or_else(|| "`:crux.tx/delete` requires an id".to_string())?
+            .to_string();
+        let valid_time = op_time(op.get(2))?.unwrap_or(tx_time);
+        let end_valid_time = op_time(op.get(3))?;
+
+        self.insert_range(&id, valid_time, end_valid_time, tx_id, tx_time, NIL_HASH);
+        Ok(vec![":crux.tx/delete".to_string(), hash_hex(&id)])
+    }
+
+    fn evict(&mut self, op: &[Edn]) -> Result<Vec<String>, String> {
+        let id = op
+            .get(1)
+            .ok_or_else(|| "`:crux.tx/evict` requires an id".to_string())?
+            .to_string();
+
+        self.entities.remove(&id);
+        Ok(vec![":crux.tx/evict".to_string(), hash_hex(&id)])
+    }
+
+    fn match_doc(
+        &mut self,
+        op: &[Edn],
+        tx_time: DateTime<Utc>,
+    ) -> Result<(Vec<String>, bool), String> {
+        let id = op
+            .get(1)
+            .ok_or_else(|| "`:crux.tx/match` requires an id".to_string())?
+            .to_string();
+        let expected = op.get(2).filter(|d| **d != Edn::Nil);
+        let valid_time = op_time(op.get(3))?.unwrap_or(tx_time);
+        let current = self.doc_at(&id, valid_time, tx_time);
+
+        let event = vec![
+            ":crux.tx/match".to_string(),
+            hash_hex(&id),
+            expected
+                .map(|d| hash_hex(&d.to_string()))
+                .unwrap_or_else(|| NIL_HASH.to_string()),
+        ];
+        Ok((event, current == expected))
+    }
+
+    /// Inserts a version at `start`. When `end` is defined, the versions already inside `(start, end)` are shadowed
+    /// by new versions at their valid times, as Crux does, and the state visible at `end` before this operation is
+    /// restored there, so the new version covers all of `[start, end)`.
+    fn insert_range(
+        &mut self,
+        id: &str,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        tx_id: usize,
+        tx_time: DateTime<Utc>,
+        content_hash: &str,
+    ) {
+        let restored = end.map(|end| {
+            let previous = self
+                .version_at(id, end, tx_time)
+                .map(|v| v.content_hash.clone())
+                .unwrap_or_else(|| NIL_HASH.to_string());
+            Version {
+                valid_time: end,
+                tx_time,
+                tx_id,
+                content_hash: previous,
+            }
+        });
+
+        let versions = self.entities.entry(id.to_string()).or_default();
+        let shadowed = end.map_or_else(BTreeSet::new, |end| {
+            versions
+                .iter()
+                .map(|v| v.valid_time)
+                .filter(|valid_time| start < *valid_time && *valid_time < end)
+                .collect()
+        });
+        versions.extend(
+            std::iter::once(start)
+                .chain(shadowed)
+                .map(|valid_time| Version {
+                    valid_time,
+                    tx_time,
+                    tx_id,
+                    content_hash: content_hash.to_string(),
+                }),
+        );
+        versions.extend(restored);
+    }
+}
+
+fn op_time(edn: Option<&Edn>) -> Result<Option<DateTime<Utc>>, String> {
+    match edn {
+        None | Some(Edn::Nil) => Ok(None),
+        Some(Edn::Inst(t)) | Some(Edn::Str(t)) => parse_time(t).map(Some),
+        Some(e) => Err(format!("{} is not a valid time", e)),
+    }
+}
+
+/// Parses times sent by transistor, RFC 3339 or `%Y-%m-%dT%H:%M:%S` as UTC.
+pub(crate) fn parse_time(time: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(time)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|t| Utc.from_utc_datetime(&t))
+        })
+        .map_err(|_| format!("{} is not a valid time", time))
+}
+
+/// Formats a time the way Crux does in its responses.
+pub(crate) fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Deterministic 40 hex chars hash, same shape as Crux's SHA-1 ids and content hashes.
+pub(crate) fn hash_hex(value: &str) -> String {
+    [
+        0xcbf2_9ce4_8422_2325u64,
+        0x6c62_272e_07bb_0142,
+        0x1000_0000_01b3,
+    ]
+    .iter()
+    .map(|seed| {
+        value.bytes().fold(*seed, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        })
+    })
+    .map(|hash| format!("{:016x}", hash))
+    .collect::<String>()[..40]
+        .to_string()
+}
@@ -0,0 +1,45 @@
+#[cfg(feature = "async")]
+use crate::http::AsyncHttpClient;
+use crate::http::BlockingHttpClient;
+use crate::testing::FakeCrux;
+use crate::types::{Actions, CruxDocument, CruxId};
+
+// Document and clients shared by the unit tests.
+
+/// `{:crux.db/id :jorge-3, :first-name "Michael", :last-name "Jorge"}`, written with `person`.
+#[derive(Debug, Clone, CruxDocument)]
+#[crux(rename_all = "kebab-case")]
+pub(crate) struct Person {
+    #[crux(id)]
+    pub(crate) id: CruxId,
+    pub(crate) first_name: String,
+    pub(crate) last_name: String,
+}
+
+pub(crate) fn person(id: &str, first_name: &str, last_name: &str) -> Person {
+    Person {
+        id: CruxId::new(id),
+        first_name: first_name.to_string(),
+        last_name: last_name.to_string(),
+    }
+}
+
+/// Client of a new `FakeCrux` where `person("jorge-3", "Michael", "Jorge")` was committed in transaction 0.
+pub(crate) fn seeded_client() -> BlockingHttpClient<FakeCrux> {
+    let client = FakeCrux::new().client();
+    client
+        .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+        .unwrap();
+    client
+}
+
+/// Async version of `seeded_client`.
+#[cfg(feature = "async")]
+pub(crate) async fn seeded_async_client() -> AsyncHttpClient<FakeCrux> {
+    let client = FakeCrux::new().async_client();
+    client
+        .tx_log(Actions::new().append_put(person("jorge-3", "Michael", "Jorge")))
+        .await
+        .unwrap();
+    client
+}
@@ -1,3 +1,4 @@
+use crate::types::response::TxLogResponse;
 use edn_rs::EdnError;
 use reqwest::Error;
 
@@ -10,10 +11,16 @@ pub enum CruxError {
     DeserializeError(String),
     /// Error originated by `edn_rs` crate. There was an error on iterating over an Edn structure.
     IterError(String),
+    /// Error originated by a `serde::Serialize` implementation when serializing to EDN.
+    SerializeError(String),
     /// Error originated by `reqwest` crate. Failed to make HTTP request.
     RequestError(Error),
     /// Error originated by `reqwest` crate. Failed to make HTTP request.
     BadResponse(String),
+    /// Error originated by a custom `CruxTransport`. Failed to make HTTP request.
+    TransportError(String),
+    /// A transport could not connect to the node, so the request was never sent. `ClusterTransport` fails over on it.
+    ConnectionError(String),
     /// Error originated by undefined behavior when parsing Crux response.
     ResponseFailed(String),
     /// Query response error, most likely a Clojure stacktrace from Crux response.
@@ -22,20 +29,43 @@ pub enum CruxError {
     QueryFormatError(String),
     /// Provided Actions cannot be empty.
     TxLogActionError(String),
+    /// Provided `TimeHistory` ranges are invalid.
+    TimeHistoryError(String),
+    /// Batch `failed` of `tx_log_batched` failed. `committed` has the index and `TxLogResponse` of every committed batch,
+    /// which can include batches after `failed` when they are sent concurrently.
+    TxLogBatchError {
+        message: String,
+        failed: usize,
+        committed: Vec<(usize, TxLogResponse)>,
+    },
+    /// Error returned by a `Middleware` hook.
+    MiddlewareError(String),
+    /// Every node of a `ClusterTransport` failed to respond.
+    ClusterError(String),
+    /// `update_entity` retries were exhausted because the entity kept changing.
+    Conflict(String),
 }
 
 impl std::error::Error for CruxError {
     fn description(&self) -> &str {
         match self {
-            CruxError::ParseEdnError(s) => &s,
-            CruxError::DeserializeError(s) => &s,
+            CruxError::ParseEdnError(s) => s,
+            CruxError::DeserializeError(s) => s,
+            CruxError::SerializeError(s) => s,
             CruxError::RequestError(_) => "HTTP request to Crux failed",
-            CruxError::BadResponse(s) => &s,
-            CruxError::ResponseFailed(s) => &s,
-            CruxError::QueryError(s) => &s,
-            CruxError::QueryFormatError(s) => &s,
-            CruxError::IterError(s) => &s,
-            CruxError::TxLogActionError(s) => &s,
+            CruxError::BadResponse(s) => s,
+            CruxError::TransportError(s) => s,
+            CruxError::ConnectionError(s) => s,
+            CruxError::ResponseFailed(s) => s,
+            CruxError::QueryError(s) => s,
+            CruxError::QueryFormatError(s) => s,
+            CruxError::IterError(s) => s,
+            CruxError::TxLogActionError(s) => s,
+            CruxError::TimeHistoryError(s) => s,
+            CruxError::TxLogBatchError { message, .. } => message,
+            CruxError::MiddlewareError(s) => s,
+            CruxError::ClusterError(s) => s,
+            CruxError::Conflict(s) => s,
         }
     }
 
@@ -49,13 +79,21 @@ impl std::fmt::Display for CruxError {
         match self {
             CruxError::ParseEdnError(s) => write!(f, "{}", &s),
             CruxError::DeserializeError(s) => write!(f, "{}", &s),
+            CruxError::SerializeError(s) => write!(f, "{}", &s),
             CruxError::RequestError(e) => write!(f, "{:?}", &e),
             CruxError::BadResponse(e) => write!(f, "{}", &e),
+            CruxError::TransportError(e) => write!(f, "{}", &e),
+            CruxError::ConnectionError(e) => write!(f, "{}", &e),
             CruxError::ResponseFailed(e) => write!(f, "{}", &e),
             CruxError::QueryError(s) => write!(f, "{}", &s),
             CruxError::QueryFormatError(s) => write!(f, "{}", &s),
             CruxError::IterError(s) => write!(f, "{}", &s),
             CruxError::TxLogActionError(s) => write!(f, "{}", &s),
+            CruxError::TimeHistoryError(s) => write!(f, "{}", &s),
+            CruxError::TxLogBatchError { message, .. } => write!(f, "{}", &message),
+            CruxError::MiddlewareError(s) => write!(f, "{}", &s),
+            CruxError::ClusterError(s) => write!(f, "{}", &s),
+            CruxError::Conflict(s) => write!(f, "{}", &s),
         }
     }
 }
@@ -1,19 +1,28 @@
+use crate::http::encode_query_value;
+use crate::types::error::CruxError;
+use crate::types::instant::rfc3339_millis;
 use crate::types::CruxId;
 use chrono::prelude::*;
 use edn_rs::Serialize;
-
-static ACTION_DATE_FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S%Z";
-static DATETIME_FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S";
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum Action {
-    Put(String, Option<DateTime<FixedOffset>>),
-    Delete(String, Option<DateTime<FixedOffset>>),
+    Put(
+        String,
+        Option<DateTime<FixedOffset>>,
+        Option<DateTime<FixedOffset>>,
+    ),
+    Delete(
+        String,
+        Option<DateTime<FixedOffset>>,
+        Option<DateTime<FixedOffset>>,
+    ),
     Evict(String),
     Match(String, String, Option<DateTime<FixedOffset>>),
 }
 
-/// Test enum to test and debug `Actions`. Implements `PartialEq` with `Actions`
+/// Test enum to test and debug `Actions`. Implements `PartialEq` with `Actions`, actions with an end valid time are never equal to an `ActionMock`.
 #[cfg(feature = "mock")]
 #[derive(Debug, PartialEq)]
 pub enum ActionMock {
@@ -23,18 +32,97 @@ pub enum ActionMock {
     Match(String, String, Option<DateTime<FixedOffset>>),
 }
 
+/// Read-only view of an action of [`Actions`](struct.Actions.html), returned by `Actions::iter`.
+/// Documents and ids are the EDN sent to Crux, like `{ :crux.db/id :jorge-3, :name "Michael", }` and `:jorge-3`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TxOp<'a> {
+    Put {
+        document: &'a str,
+        valid_time: Option<DateTime<FixedOffset>>,
+        end_valid_time: Option<DateTime<FixedOffset>>,
+    },
+    Delete {
+        id: &'a str,
+        valid_time: Option<DateTime<FixedOffset>>,
+        end_valid_time: Option<DateTime<FixedOffset>>,
+    },
+    Evict {
+        id: &'a str,
+    },
+    Match {
+        id: &'a str,
+        document: &'a str,
+        valid_time: Option<DateTime<FixedOffset>>,
+    },
+}
+
+impl<'a> From<&'a Action> for TxOp<'a> {
+    fn from(action: &'a Action) -> Self {
+        match action {
+            Action::Put(document, valid_time, end_valid_time) => TxOp::Put {
+                document,
+                valid_time: *valid_time,
+                end_valid_time: *end_valid_time,
+            },
+            Action::Delete(id, valid_time, end_valid_time) => TxOp::Delete {
+                id,
+                valid_time: *valid_time,
+                end_valid_time: *end_valid_time,
+            },
+            Action::Evict(id) => TxOp::Evict { id },
+            Action::Match(id, document, valid_time) => TxOp::Match {
+                id,
+                document,
+                valid_time: *valid_time,
+            },
+        }
+    }
+}
+
+impl From<TxOp<'_>> for Action {
+    fn from(op: TxOp<'_>) -> Self {
+        match op {
+            TxOp::Put {
+                document,
+                valid_time,
+                end_valid_time,
+            } => Action::Put(document.to_string(), valid_time, end_valid_time),
+            TxOp::Delete {
+                id,
+                valid_time,
+                end_valid_time,
+            } => Action::Delete(id.to_string(), valid_time, end_valid_time),
+            TxOp::Evict { id } => Action::Evict(id.to_string()),
+            TxOp::Match {
+                id,
+                document,
+                valid_time,
+            } => Action::Match(id.to_string(), document.to_string(), valid_time),
+        }
+    }
+}
+
 /// Actions to perform in Crux. It is a builder struct to help you create a `Vec<Action>` for `tx_log`.
 ///
 /// Allowed actions:
-/// * `PUT` - Write a version of a document. Functions are `append_put` and `append_put_timed`.
-/// * `Delete` - Deletes the specific document at a given valid time. Functions are `append_delete` and `append_delete_timed`.
+/// * `PUT` - Write a version of a document. Functions are `append_put`, `append_put_timed` and `append_put_timed_until`.
+/// * `Delete` - Deletes the specific document at a given valid time. Functions are `append_delete`, `append_delete_timed` and `append_delete_timed_until`.
 /// * `Evict` - Evicts a document entirely, including all historical versions (receives only the ID to evict). Function is `append_evict`.
 /// * `Match` - Matches the current state of an entity, if the state doesn't match the provided document, the transaction will not continue. Functions are `append_match` and `append_match_timed`.
+///
+/// Actions can be read with `len` and `iter`, merged with `append` or `Extend` and collected from `TxOp`s or other `Actions`.
+/// `Display` gives the EDN sent by `tx_log`.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Actions {
     actions: Vec<Action>,
 }
 
+impl Default for Actions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Actions {
     pub fn new() -> Self {
         Self {
@@ -42,10 +130,26 @@ impl Actions {
         }
     }
 
-    pub(crate) fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.actions.is_empty()
     }
 
+    /// Number of actions.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Iterates over the actions, in the order they will be sent, as [`TxOp`](enum.TxOp.html)s.
+    pub fn iter(&self) -> impl Iterator<Item = TxOp<'_>> {
+        self.actions.iter().map(TxOp::from)
+    }
+
+    /// Appends every action of `other` after the actions of `self`, so several builders can be sent as one transaction.
+    pub fn append(mut self, other: Actions) -> Self {
+        self.actions.extend(other.actions);
+        self
+    }
+
     /// Appends an `Action::Put` enforcing types for `action` field to be a `T: Serialize`
     pub fn append_put<T: Serialize>(mut self, action: T) -> Self {
         self.actions.push(Action::put(action));
@@ -62,6 +166,18 @@ impl Actions {
         self
     }
 
+    /// Appends an `Action::Put` valid from `start` until `end`, enforcing types for `action` field to be a `T: Serialize` and `start` and `end` to be `DateTime<FixedOffset>`.
+    pub fn append_put_timed_until<T: Serialize>(
+        mut self,
+        action: T,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Self {
+        self.actions
+            .push(Action::put(action).with_valid_dates(start, end));
+        self
+    }
+
     /// Appends an `Action::Delete` enforcing types for `id` field to be a `CruxId`
     pub fn append_delete(mut self, id: CruxId) -> Self {
         self.actions.push(Action::delete(id));
@@ -74,6 +190,18 @@ impl Actions {
         self
     }
 
+    /// Appends an `Action::Delete` from `start` until `end` enforcing types for `id` field to be a `CruxId` and `start` and `end` to be `DateTime<FixedOffset>`.
+    pub fn append_delete_timed_until(
+        mut self,
+        id: CruxId,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Self {
+        self.actions
+            .push(Action::delete(id).with_valid_dates(start, end));
+        self
+    }
+
     /// Appends an `Action::Evict` enforcing types for `id` field to be a `CruxId`
     pub fn append_evict(mut self, id: CruxId) -> Self {
         self.actions.push(Action::evict(id));
@@ -98,27 +226,174 @@ impl Actions {
         self
     }
 
+    /// Whether any action is an `Evict`, which removes documents from every basis.
+    pub(crate) fn has_evict(&self) -> bool {
+        self.actions
+            .iter()
+            .any(|action| matches!(action, Action::Evict(_)))
+    }
+
     pub(crate) fn build(self) -> String {
         edn_rs::to_string(self.actions)
     }
+
+    /// Function `validate` checks the actions before they are sent, `tx_log` runs it automatically.
+    /// Returns a `CruxError::TxLogActionError` listing each invalid action by index:
+    /// * `Put` and `Match` documents without `:crux.db/id`. A `nil` `Match` document, `None::<T>`, asserts that the entity does not exist and is valid.
+    /// * `Evict` of an id that is not a keyword, which would not be sent at all.
+    /// * `Match` of an id that differs from the `:crux.db/id` of its document.
+    /// * `Put` and `Delete` with an end valid time earlier than their start valid time.
+    pub fn validate(&self) -> Result<(), CruxError> {
+        if self.is_empty() {
+            return Err(CruxError::TxLogActionError(
+                "Actions cannot be empty.".to_string(),
+            ));
+        }
+
+        let errors = self
+            .actions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, action)| {
+                action
+                    .invalid()
+                    .map(|error| format!("action {}: {}", index, error))
+            })
+            .collect::<Vec<String>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CruxError::TxLogActionError(format!(
+                "Invalid actions, {}",
+                errors.join("; ")
+            )))
+        }
+    }
+
+    /// Splits the actions in order into batches within `limit`, each one sent as its own transaction.
+    /// A `Match` only guards the transaction it is part of, so actions with a `Match` cannot be split.
+    pub(crate) fn batches(self, limit: BatchLimit) -> Result<Vec<Actions>, CruxError> {
+        if self.is_empty() {
+            return Err(CruxError::TxLogActionError(
+                "Actions cannot be empty.".to_string(),
+            ));
+        }
+        let max = match limit {
+            BatchLimit::Ops(max) | BatchLimit::Bytes(max) if max > 0 => max,
+            _ => {
+                return Err(CruxError::TxLogActionError(format!(
+                    "Batch limit must be positive, found {:?}",
+                    limit
+                )))
+            }
+        };
+        let has_match = self.actions.iter().any(|a| matches!(a, Action::Match(..)));
+
+        let mut batches = Vec::new();
+        let mut batch = Vec::new();
+        let mut size = 0;
+        for action in self.actions {
+            // `[a, b]`, each action costs its length and a separator or bracket pair.
+            let cost = match limit {
+                BatchLimit::Ops(_) => 1,
+                BatchLimit::Bytes(_) => edn_rs::to_string(action.clone()).len() + 2,
+            };
+            if cost > max {
+                return Err(CruxError::TxLogActionError(format!(
+                    "Action {} exceeds the batch limit of {:?}",
+                    edn_rs::to_string(action),
+                    limit
+                )));
+            }
+            if size + cost > max {
+                batches.push(Actions {
+                    actions: std::mem::take(&mut batch),
+                });
+                size = 0;
+            }
+            size += cost;
+            batch.push(action);
+        }
+        batches.push(Actions { actions: batch });
+
+        if has_match && batches.len() > 1 {
+            return Err(CruxError::TxLogActionError(format!(
+                "Actions with a Match cannot be split into {} batches",
+                batches.len()
+            )));
+        }
+        Ok(batches)
+    }
+}
+
+impl std::fmt::Display for Actions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.clone().build())
+    }
+}
+
+impl Extend<Actions> for Actions {
+    fn extend<I: IntoIterator<Item = Actions>>(&mut self, iter: I) {
+        self.actions
+            .extend(iter.into_iter().flat_map(|actions| actions.actions));
+    }
+}
+
+impl<'a> Extend<TxOp<'a>> for Actions {
+    fn extend<I: IntoIterator<Item = TxOp<'a>>>(&mut self, iter: I) {
+        self.actions.extend(iter.into_iter().map(Action::from));
+    }
+}
+
+impl std::iter::FromIterator<Actions> for Actions {
+    fn from_iter<I: IntoIterator<Item = Actions>>(iter: I) -> Self {
+        let mut actions = Actions::new();
+        actions.extend(iter);
+        actions
+    }
+}
+
+impl<'a> std::iter::FromIterator<TxOp<'a>> for Actions {
+    fn from_iter<I: IntoIterator<Item = TxOp<'a>>>(iter: I) -> Self {
+        let mut actions = Actions::new();
+        actions.extend(iter);
+        actions
+    }
+}
+
+/// `BatchLimit` is the maximum size of each transaction sent by `tx_log_batched`.
+/// * `Ops(n)` - at most `n` actions per transaction.
+/// * `Bytes(n)` - at most `n` bytes of EDN body per transaction, JSON and transit bodies can be larger.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BatchLimit {
+    Ops(usize),
+    Bytes(usize),
 }
 
 impl Action {
     fn put<T: Serialize>(action: T) -> Action {
-        Action::Put(edn_rs::to_string(action), None)
+        Action::Put(edn_rs::to_string(action), None, None)
     }
 
     fn with_valid_date(self, date: DateTime<FixedOffset>) -> Action {
         match self {
-            Action::Put(action, _) => Action::Put(action, Some(date)),
-            Action::Delete(action, _) => Action::Delete(action, Some(date)),
+            Action::Put(action, _, end) => Action::Put(action, Some(date), end),
+            Action::Delete(action, _, end) => Action::Delete(action, Some(date), end),
             Action::Match(id, action, _) => Action::Match(id, action, Some(date)),
             action => action,
         }
     }
 
+    fn with_valid_dates(self, start: DateTime<FixedOffset>, end: DateTime<FixedOffset>) -> Action {
+        match self {
+            Action::Put(action, _, _) => Action::Put(action, Some(start), Some(end)),
+            Action::Delete(action, _, _) => Action::Delete(action, Some(start), Some(end)),
+            action => action,
+        }
+    }
+
     fn delete(id: CruxId) -> Action {
-        Action::Delete(edn_rs::to_string(id), None)
+        Action::Delete(edn_rs::to_string(id), None, None)
     }
 
     fn evict(id: CruxId) -> Action {
@@ -128,22 +403,91 @@ impl Action {
     fn match_doc<T: Serialize>(id: CruxId, action: T) -> Action {
         Action::Match(edn_rs::to_string(id), edn_rs::to_string(action), None)
     }
+
+    /// Reason why the action is invalid, `None` if it is valid.
+    fn invalid(&self) -> Option<String> {
+        match self {
+            Action::Put(doc, _, _) if document_id(doc).is_none() => {
+                Some(format!("Put document {} has no :crux.db/id", doc))
+            }
+            Action::Put(_, Some(start), Some(end)) | Action::Delete(_, Some(start), Some(end))
+                if end < start =>
+            {
+                Some(format!(
+                    "end valid time {} is earlier than start valid time {}",
+                    rfc3339_millis(end),
+                    rfc3339_millis(start)
+                ))
+            }
+            Action::Evict(id) if !is_keyword(id) => {
+                Some(format!("Evict id `{}` is not a keyword", id))
+            }
+            // a `nil` document matches an entity that does not exist
+            Action::Match(_, doc, _) if doc.trim() == "nil" => None,
+            Action::Match(id, doc, _) => match document_id(doc) {
+                None => Some(format!("Match document {} has no :crux.db/id", doc)),
+                Some(doc_id) if &doc_id != id => Some(format!(
+                    "Match id {} differs from document id {}",
+                    id, doc_id
+                )),
+                Some(_) => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// `:crux.db/id` of a serialized document, `None` if it is not a map or has no id.
+fn document_id(doc: &str) -> Option<String> {
+    match edn_rs::Edn::from_str(doc).ok()? {
+        edn_rs::Edn::Map(map) => map
+            .to_map()
+            .get(":crux.db/id")
+            .filter(|id| !matches!(id, edn_rs::Edn::Nil))
+            // the edn parser keeps the separator after a keyword or symbol.
+            .map(|id| id.to_string().trim_end_matches(',').to_string()),
+        _ => None,
+    }
+}
+
+/// Keywords start with a single `:` followed by a name without whitespace or delimiters.
+pub(crate) fn is_keyword(id: &str) -> bool {
+    match id.strip_prefix(':') {
+        Some(name) => {
+            !name.is_empty()
+                && !name.starts_with(':')
+                && !name
+                    .chars()
+                    .any(|c| c.is_whitespace() || "()[]{}\",;".contains(c))
+        }
+        None => false,
+    }
 }
 
 impl Serialize for Action {
     fn serialize(self) -> String {
         match self {
-            Action::Put(edn, None) => format!("[:crux.tx/put {}]", edn),
-            Action::Put(edn, Some(date)) => format!(
-                "[:crux.tx/put {} #inst \"{}\"]",
+            Action::Put(edn, None, _) => format!("[:crux.tx/put {}]", edn),
+            Action::Put(edn, Some(date), None) => {
+                format!("[:crux.tx/put {} #inst \"{}\"]", edn, rfc3339_millis(&date))
+            }
+            Action::Put(edn, Some(start), Some(end)) => format!(
+                "[:crux.tx/put {} #inst \"{}\" #inst \"{}\"]",
                 edn,
-                date.format(ACTION_DATE_FORMAT).to_string()
+                rfc3339_millis(&start),
+                rfc3339_millis(&end)
             ),
-            Action::Delete(id, None) => format!("[:crux.tx/delete {}]", id),
-            Action::Delete(id, Some(date)) => format!(
+            Action::Delete(id, None, _) => format!("[:crux.tx/delete {}]", id),
+            Action::Delete(id, Some(date), None) => format!(
                 "[:crux.tx/delete {} #inst \"{}\"]",
                 id,
-                date.format(ACTION_DATE_FORMAT).to_string()
+                rfc3339_millis(&date)
+            ),
+            Action::Delete(id, Some(start), Some(end)) => format!(
+                "[:crux.tx/delete {} #inst \"{}\" #inst \"{}\"]",
+                id,
+                rfc3339_millis(&start),
+                rfc3339_millis(&end)
             ),
             Action::Evict(id) => {
                 if id.starts_with(":") {
@@ -157,14 +501,14 @@ impl Serialize for Action {
                 "[:crux.tx/match {} {} #inst \"{}\"]",
                 id,
                 edn,
-                date.format(ACTION_DATE_FORMAT).to_string()
+                rfc3339_millis(&date)
             ),
         }
     }
 }
 
 /// `Order` enum to define how the `entity_history` response will be ordered. Options are `Asc` and `Desc`.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Order {
     Asc,
     Desc,
@@ -180,7 +524,8 @@ impl Serialize for Order {
 }
 
 /// enum `TimeHistory` is used as an argument in the function `entity_history_timed`. It is responsible for defining `valid-time` and `transaction-times` ranges for the query.
-/// The possible options are `ValidTime` and `TransactionTime`, both of them receive two `Option<DateTime<Utc>>`. The first parameter will transform into an start time and the second into and end-time, and they will be formated as `%Y-%m-%dT%H:%M:%S`.
+/// The possible options are `ValidTime` and `TransactionTime`, both of them receive two `Option<DateTime<Utc>>`. The first parameter will transform into an start time and the second into and end-time, and they will be formated as RFC 3339 instants with milliseconds, `2014-11-28T12:00:09.000Z`.
+/// Like in Crux, the start is inclusive and the end exclusive in the `Order` of the history, so with `Order::Desc` the start is the latest time and the end the earliest.
 /// The query params will become:
 /// * ValidTime(Some(start), Some(end)) => "&start-valid-time={}&end-valid-time={}"
 /// * ValidTime(None, Some(end)) => "&end-valid-time={}"
@@ -190,7 +535,7 @@ impl Serialize for Order {
 /// * TransactionTime(None, Some(end)) => "&end-transaction-time={}"
 /// * TransactionTime(Some(start), None) => "&start-transaction-time={}"
 /// * TransactionTime(None, None) => "",
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TimeHistory {
     ValidTime(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
     TransactionTime(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
@@ -204,52 +549,72 @@ impl Serialize for TimeHistory {
         match self {
             ValidTime(Some(start), Some(end)) => format!(
                 "&start-valid-time={}&end-valid-time={}",
-                start.format(DATETIME_FORMAT).to_string(),
-                end.format(DATETIME_FORMAT).to_string()
-            ),
-            ValidTime(None, Some(end)) => format!(
-                "&end-valid-time={}",
-                end.format(DATETIME_FORMAT).to_string()
-            ),
-            ValidTime(Some(start), None) => format!(
-                "&start-valid-time={}",
-                start.format(DATETIME_FORMAT).to_string()
+                time_param(&start),
+                time_param(&end)
             ),
-            ValidTime(None, None) => format!(""),
+            ValidTime(None, Some(end)) => {
+                format!("&end-valid-time={}", time_param(&end))
+            }
+            ValidTime(Some(start), None) => {
+                format!("&start-valid-time={}", time_param(&start))
+            }
+            ValidTime(None, None) => String::new(),
 
             TransactionTime(Some(start), Some(end)) => format!(
                 "&start-transaction-time={}&end-transaction-time={}",
-                start.format(DATETIME_FORMAT).to_string(),
-                end.format(DATETIME_FORMAT).to_string()
-            ),
-            TransactionTime(None, Some(end)) => format!(
-                "&end-transaction-time={}",
-                end.format(DATETIME_FORMAT).to_string()
+                time_param(&start),
+                time_param(&end)
             ),
-            TransactionTime(Some(start), None) => format!(
-                "&start-transaction-time={}",
-                start.format(DATETIME_FORMAT).to_string()
-            ),
-            TransactionTime(None, None) => format!(""),
+            TransactionTime(None, Some(end)) => {
+                format!("&end-transaction-time={}", time_param(&end))
+            }
+            TransactionTime(Some(start), None) => {
+                format!("&start-transaction-time={}", time_param(&start))
+            }
+            TransactionTime(None, None) => String::new(),
         }
     }
 }
 
-#[doc(hidden)]
-pub trait VecSer {
-    fn serialize(self) -> String;
+fn time_param(time: &DateTime<Utc>) -> String {
+    encode_query_value(&rfc3339_millis(time))
 }
-#[doc(hidden)]
-impl VecSer for Vec<TimeHistory> {
-    fn serialize(self) -> String {
-        if self.len() > 2 || self.len() == 0 {
-            String::new()
-        } else {
-            self.into_iter()
-                .map(edn_rs::to_string)
-                .collect::<Vec<String>>()
-                .join("")
+
+impl TimeHistory {
+    /// Query params of `times` for a history in `order`. At most one `ValidTime` and one `TransactionTime` are allowed and their start cannot be
+    /// after their end with `Order::Asc`, or before it with `Order::Desc`.
+    pub(crate) fn to_params(times: Vec<TimeHistory>, order: Order) -> Result<String, CruxError> {
+        let valid = times
+            .iter()
+            .filter(|t| matches!(t, TimeHistory::ValidTime(..)))
+            .count();
+        if valid > 1 || times.len() - valid > 1 {
+            return Err(CruxError::TimeHistoryError(format!(
+                "At most one ValidTime and one TransactionTime are allowed, found {:?}",
+                times
+            )));
+        }
+        if let Some(t) = times.iter().find(|t| match (t, order) {
+            (TimeHistory::ValidTime(Some(start), Some(end)), Order::Asc)
+            | (TimeHistory::TransactionTime(Some(start), Some(end)), Order::Asc) => start > end,
+            (TimeHistory::ValidTime(Some(start), Some(end)), Order::Desc)
+            | (TimeHistory::TransactionTime(Some(start), Some(end)), Order::Desc) => start < end,
+            _ => false,
+        }) {
+            return Err(CruxError::TimeHistoryError(match order {
+                Order::Asc => format!("Start time cannot be after end time in {:?}", t),
+                Order::Desc => format!(
+                    "Start time cannot be before end time in descending order in {:?}",
+                    t
+                ),
+            }));
         }
+
+        Ok(times
+            .into_iter()
+            .map(edn_rs::to_string)
+            .collect::<Vec<String>>()
+            .join(""))
     }
 }
 
@@ -259,10 +624,14 @@ impl std::cmp::PartialEq<Vec<ActionMock>> for Actions {
         self.actions
             .iter()
             .zip(other.iter())
-            .map(|(acs, acm)| match (acs, acm) {
-                (Action::Put(ap, tp), ActionMock::Put(am, tm)) if ap == am && tp == tm => true,
+            .all(|(acs, acm)| match (acs, acm) {
+                (Action::Put(ap, tp, None), ActionMock::Put(am, tm)) if ap == am && tp == tm => {
+                    true
+                }
                 (Action::Evict(id), ActionMock::Evict(idm)) if id == idm => true,
-                (Action::Delete(id, tp), ActionMock::Delete(idm, tm)) if id == idm && tp == tm => {
+                (Action::Delete(id, tp, None), ActionMock::Delete(idm, tm))
+                    if id == idm && tp == tm =>
+                {
                     true
                 }
                 (Action::Match(id, a, tp), ActionMock::Match(idm, am, tm))
@@ -272,35 +641,22 @@ impl std::cmp::PartialEq<Vec<ActionMock>> for Actions {
                 }
                 _ => false,
             })
-            .fold(true, |acc, e| acc && e)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::support::{person, Person};
     use crate::types::CruxId;
-    use edn_derive::Serialize;
 
     #[test]
     fn actions() {
-        let person1 = Person {
-            crux__db___id: CruxId::new("jorge-3"),
-            first_name: "Michael".to_string(),
-            last_name: "Jorge".to_string(),
-        };
+        let person1 = person("jorge-3", "Michael", "Jorge");
 
-        let person2 = Person {
-            crux__db___id: CruxId::new("manuel-1"),
-            first_name: "Diego".to_string(),
-            last_name: "Manuel".to_string(),
-        };
+        let person2 = person("manuel-1", "Diego", "Manuel");
 
-        let person3 = Person {
-            crux__db___id: CruxId::new("manuel-1"),
-            first_name: "Diego".to_string(),
-            last_name: "Manuel".to_string(),
-        };
+        let person3 = person("manuel-1", "Diego", "Manuel");
 
         let timed = "2014-11-28T21:00:09-09:00"
             .parse::<DateTime<FixedOffset>>()
@@ -309,31 +665,222 @@ mod tests {
         let actions = Actions::new()
             .append_put_timed(person1.clone(), timed)
             .append_put(person2.clone())
-            .append_evict(person1.crux__db___id)
-            .append_delete(person2.crux__db___id)
-            .append_match_doc(person3.clone().crux__db___id, person3);
+            .append_evict(person1.id)
+            .append_delete(person2.id)
+            .append_match_doc(person3.clone().id, person3);
 
         assert_eq!(actions.clone(), expected_actions());
     }
 
-    fn expected_actions() -> Actions {
-        let person1 = Person {
-            crux__db___id: CruxId::new("jorge-3"),
-            first_name: "Michael".to_string(),
-            last_name: "Jorge".to_string(),
-        };
+    #[test]
+    fn timed_actions_keep_milliseconds() {
+        let timed = "2014-11-28T21:00:09.123+09:00"
+            .parse::<DateTime<FixedOffset>>()
+            .unwrap();
 
-        let person2 = Person {
-            crux__db___id: CruxId::new("manuel-1"),
-            first_name: "Diego".to_string(),
-            last_name: "Manuel".to_string(),
-        };
+        let actions = Actions::new()
+            .append_put_timed(person("jorge-3", "Michael", "Jorge"), timed)
+            .append_delete_timed(CruxId::new("manuel-1"), timed.with_timezone(&Utc).into());
+
+        assert_eq!(
+            actions.build(),
+            "[[:crux.tx/put { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", } #inst \"2014-11-28T21:00:09.123+09:00\"], [:crux.tx/delete :manuel-1 #inst \"2014-11-28T12:00:09.123Z\"]]"
+        );
+    }
+
+    #[test]
+    fn time_history_params() {
+        let start = "2014-11-28T21:00:09.5+09:00"
+            .parse::<DateTime<Utc>>()
+            .unwrap();
+
+        assert_eq!(
+            edn_rs::to_string(TimeHistory::ValidTime(Some(start), None)),
+            "&start-valid-time=2014-11-28T12:00:09.500Z"
+        );
+    }
 
-        let person3 = Person {
-            crux__db___id: CruxId::new("manuel-1"),
-            first_name: "Diego".to_string(),
-            last_name: "Manuel".to_string(),
+    #[test]
+    fn batches() {
+        let actions = || {
+            (0..5).fold(Actions::new(), |actions, i| {
+                actions.append_delete(CruxId::new(&format!("person-{}", i)))
+            })
+        };
+        let sizes = |batches: Vec<Actions>| {
+            batches
+                .into_iter()
+                .map(|b| b.actions.len())
+                .collect::<Vec<usize>>()
         };
+        let one = Actions::new()
+            .append_delete(CruxId::new("person-0"))
+            .build()
+            .len();
+
+        assert_eq!(
+            sizes(actions().batches(BatchLimit::Ops(2)).unwrap()),
+            vec![2, 2, 1]
+        );
+        assert_eq!(
+            sizes(actions().batches(BatchLimit::Bytes(one * 3)).unwrap()),
+            vec![3, 2]
+        );
+        assert!(actions()
+            .batches(BatchLimit::Ops(2))
+            .unwrap()
+            .into_iter()
+            .all(|b| b.build().len() <= one * 2 + 2));
+        assert!(actions().batches(BatchLimit::Bytes(one - 1)).is_err());
+        assert!(actions().batches(BatchLimit::Ops(0)).is_err());
+        assert!(actions()
+            .append_match_doc(CruxId::new("person-0"), CruxId::new("person-0"))
+            .batches(BatchLimit::Ops(2))
+            .is_err());
+    }
+
+    #[test]
+    fn time_history_validation() {
+        let start = "2014-11-28T12:00:09Z".parse::<DateTime<Utc>>().unwrap();
+        let end = "2014-11-29T12:00:09Z".parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            TimeHistory::to_params(
+                vec![
+                    TimeHistory::ValidTime(Some(start), None),
+                    TimeHistory::TransactionTime(None, Some(end)),
+                ],
+                Order::Asc
+            )
+            .unwrap(),
+            "&start-valid-time=2014-11-28T12:00:09.000Z&end-transaction-time=2014-11-29T12:00:09.000Z"
+        );
+        assert!(TimeHistory::to_params(
+            vec![
+                TimeHistory::ValidTime(Some(start), None),
+                TimeHistory::ValidTime(None, Some(end)),
+            ],
+            Order::Asc
+        )
+        .is_err());
+        assert!(TimeHistory::to_params(
+            vec![TimeHistory::TransactionTime(Some(end), Some(start))],
+            Order::Asc
+        )
+        .is_err());
+        assert!(TimeHistory::to_params(
+            vec![TimeHistory::TransactionTime(Some(end), Some(start))],
+            Order::Desc
+        )
+        .is_ok());
+        assert!(TimeHistory::to_params(
+            vec![TimeHistory::ValidTime(Some(start), Some(end))],
+            Order::Desc
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn validation() {
+        let start = "2014-11-28T21:00:09Z"
+            .parse::<DateTime<FixedOffset>>()
+            .unwrap();
+        let end = start + chrono::Duration::days(1);
+        let valid = Actions::new()
+            .append_put_timed_until(person("jorge-3", "Michael", "Jorge"), start, end)
+            .append_delete_timed_until(CruxId::new("jorge-3"), start, end)
+            .append_evict(CruxId::new("manuel-1"))
+            .append_match_doc(
+                CruxId::new("jorge-3"),
+                person("jorge-3", "Michael", "Jorge"),
+            );
+
+        let err = Actions::new()
+            .append_put(Anonymous {
+                name: "Michael".to_string(),
+            })
+            .append_put(person("jorge-3", "Michael", "Jorge"))
+            .append_evict(CruxId::new(""))
+            .append_match_doc(
+                CruxId::new("manuel-1"),
+                person("jorge-3", "Michael", "Jorge"),
+            )
+            .append_delete_timed_until(CruxId::new("jorge-3"), end, start)
+            .validate()
+            .unwrap_err();
+
+        assert!(valid.validate().is_ok());
+        assert_eq!(
+            format!("{}", err),
+            "Invalid actions, action 0: Put document { :name \"Michael\", } has no :crux.db/id; \
+             action 2: Evict id `:` is not a keyword; \
+             action 3: Match id :manuel-1 differs from document id :jorge-3; \
+             action 4: end valid time 2014-11-28T21:00:09.000Z is earlier than start valid time 2014-11-29T21:00:09.000Z"
+        );
+        assert!(Actions::new().validate().is_err());
+        assert_eq!(
+            valid.build(),
+            "[[:crux.tx/put { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", } #inst \"2014-11-28T21:00:09.000Z\" #inst \"2014-11-29T21:00:09.000Z\"], \
+             [:crux.tx/delete :jorge-3 #inst \"2014-11-28T21:00:09.000Z\" #inst \"2014-11-29T21:00:09.000Z\"], \
+             [:crux.tx/evict :manuel-1], \
+             [:crux.tx/match :jorge-3 { :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", }]]"
+        );
+    }
+
+    #[test]
+    fn match_nil_document() {
+        let actions = Actions::new()
+            .append_match_doc(CruxId::new("jorge-3"), None::<Person>)
+            .append_put(person("jorge-3", "Michael", "Jorge"));
+
+        assert!(actions.validate().is_ok());
+        assert!(actions
+            .build()
+            .starts_with("[[:crux.tx/match :jorge-3 nil], [:crux.tx/put"));
+    }
+
+    #[test]
+    fn introspection_and_composition() {
+        let timed = "2014-11-28T21:00:09Z"
+            .parse::<DateTime<FixedOffset>>()
+            .unwrap();
+        let users = Actions::new().append_put_timed(person("jorge-3", "Michael", "Jorge"), timed);
+        let audit = Actions::new().append_evict(CruxId::new("manuel-1"));
+
+        let mut shared = Actions::new();
+        shared.extend(vec![users.clone(), audit.clone()]);
+        let merged = users.clone().append(audit.clone());
+        let evicts = merged
+            .iter()
+            .filter(|op| matches!(op, TxOp::Evict { .. }))
+            .collect::<Actions>();
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(shared, merged);
+        assert_eq!(vec![users, audit].into_iter().collect::<Actions>(), merged);
+        assert_eq!(
+            merged.iter().next(),
+            Some(TxOp::Put {
+                document:
+                    "{ :crux.db/id :jorge-3, :first-name \"Michael\", :last-name \"Jorge\", }",
+                valid_time: Some(timed),
+                end_valid_time: None,
+            })
+        );
+        assert_eq!(
+            evicts.iter().collect::<Vec<_>>(),
+            vec![TxOp::Evict { id: ":manuel-1" }]
+        );
+        assert_eq!(format!("{}", evicts), "[[:crux.tx/evict :manuel-1]]");
+        assert_eq!(format!("{}", merged), merged.clone().build());
+    }
+
+    fn expected_actions() -> Actions {
+        let person1 = person("jorge-3", "Michael", "Jorge");
+
+        let person2 = person("manuel-1", "Diego", "Manuel");
+
+        let person3 = person("manuel-1", "Diego", "Manuel");
 
         Actions {
             actions: vec![
@@ -344,24 +891,18 @@ mod tests {
                             .parse::<DateTime<FixedOffset>>()
                             .unwrap(),
                     ),
-                ),
-                Action::Put(person2.clone().serialize(), None),
-                Action::Evict(person1.crux__db___id.serialize()),
-                Action::Delete(person2.crux__db___id.serialize(), None),
-                Action::Match(
-                    person3.clone().crux__db___id.serialize(),
-                    person3.serialize(),
                     None,
                 ),
+                Action::Put(person2.clone().serialize(), None, None),
+                Action::Evict(person1.id.serialize()),
+                Action::Delete(person2.id.serialize(), None, None),
+                Action::Match(person3.clone().id.serialize(), person3.serialize(), None),
             ],
         }
     }
 
-    #[derive(Debug, Clone, Serialize)]
-    #[allow(non_snake_case)]
-    pub struct Person {
-        crux__db___id: CruxId,
-        first_name: String,
-        last_name: String,
+    #[derive(edn_derive::Serialize)]
+    struct Anonymous {
+        name: String,
     }
 }
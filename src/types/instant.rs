@@ -0,0 +1,244 @@
+use crate::types::error::CruxError;
+use chrono::prelude::*;
+use edn_rs::{Deserialize, Edn, EdnError, Serialize};
+use std::cmp::Ordering;
+#[cfg(feature = "time")]
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Time returned by Crux, like `:crux.tx/tx-time` and `:crux.db/valid-time`.
+/// `CruxInstant` keeps the string sent by Crux and only parses it when converted, so a malformed time
+/// is an error on conversion instead of a failed response.
+/// Two instants are equal when they represent the same time, regardless of their offsets.
+/// Malformed instants are only equal to the same string and are ordered after every valid instant.
+/// Conversion to `time::OffsetDateTime` requires feature `time`.
+#[derive(Debug, Clone)]
+pub struct CruxInstant(String);
+
+impl CruxInstant {
+    /// `CruxInstant::new` keeps `instant` as is, it is only parsed on conversion.
+    pub fn new(instant: &str) -> Self {
+        Self(instant.to_string())
+    }
+
+    /// Original string sent by Crux.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Parses the instant into a `DateTime<FixedOffset>`.
+    pub fn to_datetime(&self) -> Result<DateTime<FixedOffset>, CruxError> {
+        self.0.parse::<DateTime<FixedOffset>>().map_err(|_| {
+            CruxError::DeserializeError(format!("Unable to parse instant \"{}\"", self.0))
+        })
+    }
+
+    /// Parses the instant into a `DateTime<Utc>`.
+    pub fn to_utc(&self) -> Result<DateTime<Utc>, CruxError> {
+        self.to_datetime().map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Parses the instant into a `time::OffsetDateTime`. Feature `time` is required.
+    #[cfg(feature = "time")]
+    pub fn to_offset_datetime(&self) -> Result<time::OffsetDateTime, CruxError> {
+        let rfc3339 = self.to_datetime()?.to_rfc3339();
+        time::OffsetDateTime::parse(&rfc3339, &time::format_description::well_known::Rfc3339)
+            .map_err(|_| {
+                CruxError::DeserializeError(format!("Unable to parse instant \"{}\"", self.0))
+            })
+    }
+}
+
+/// RFC 3339 instant with millisecond precision, `2014-11-28T21:00:09.000+09:00`.
+/// Format of every time sent to Crux, in `#inst` values and in query params.
+pub(crate) fn rfc3339_millis<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for CruxInstant
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fn from(time: DateTime<Tz>) -> Self {
+        Self(rfc3339_millis(&time))
+    }
+}
+
+/// Fails if `time` or its offset can't be represented by `chrono`.
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for CruxInstant {
+    type Error = CruxError;
+
+    fn try_from(time: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        let error = || {
+            CruxError::DeserializeError(format!("Unable to convert time {} to an instant", time))
+        };
+        let millis = i64::try_from(time.unix_timestamp_nanos() / 1_000_000).map_err(|_| error())?;
+        let utc = DateTime::from_timestamp_millis(millis).ok_or_else(error)?;
+        let offset = FixedOffset::east_opt(time.offset().whole_seconds()).ok_or_else(error)?;
+        Ok(Self::from(utc.with_timezone(&offset)))
+    }
+}
+
+impl FromStr for CruxInstant {
+    type Err = CruxError;
+
+    /// Unlike `CruxInstant::new`, `from_str` fails if `s` is not a valid instant.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let instant = Self::new(s);
+        instant.to_datetime()?;
+        Ok(instant)
+    }
+}
+
+impl std::fmt::Display for CruxInstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq for CruxInstant {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CruxInstant {}
+
+impl PartialOrd for CruxInstant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CruxInstant {
+    /// Compares the parsed instants. Malformed instants come after every valid one and are compared by their strings.
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.to_datetime(), other.to_datetime()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => self.0.cmp(&other.0),
+        }
+    }
+}
+
+impl Serialize for CruxInstant {
+    fn serialize(self) -> String {
+        format!("#inst \"{}\"", self.0)
+    }
+}
+
+impl Deserialize for CruxInstant {
+    fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
+        match edn {
+            Edn::Str(s) | Edn::Inst(s) => Ok(Self::new(s)),
+            _ => Err(EdnError::Deserialize(format!(
+                "couldn't convert {} into CruxInstant",
+                edn
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CruxInstant {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CruxInstant {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let instant = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(Self(instant))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_lazily() {
+        let instant: CruxInstant = edn_rs::from_str("\"not a time\"").unwrap();
+
+        assert_eq!(instant.as_str(), "not a time");
+        assert!(instant.to_datetime().is_err());
+        assert!("not a time".parse::<CruxInstant>().is_err());
+    }
+
+    #[test]
+    fn to_chrono() {
+        let instant = CruxInstant::new("2020-07-16T21:53:14.628-00:00");
+
+        assert_eq!(
+            instant.to_datetime().unwrap(),
+            "2020-07-16T21:53:14.628-00:00"
+                .parse::<DateTime<FixedOffset>>()
+                .unwrap()
+        );
+        assert_eq!(
+            instant.to_utc().unwrap().to_rfc3339(),
+            "2020-07-16T21:53:14.628+00:00"
+        );
+    }
+
+    #[test]
+    fn equal_instants_with_different_offsets() {
+        let zulu = CruxInstant::new("2020-07-16T21:53:14.628Z");
+        let brt = CruxInstant::new("2020-07-16T18:53:14.628-03:00");
+
+        assert_eq!(zulu, brt);
+        assert!(CruxInstant::new("2020-07-16T21:53:14.629Z") > brt);
+        assert_eq!(
+            CruxInstant::from(brt.to_datetime().unwrap()).as_str(),
+            "2020-07-16T18:53:14.628-03:00"
+        );
+    }
+
+    #[test]
+    fn malformed_instants_sort_last() {
+        let mut instants = [
+            CruxInstant::new("b"),
+            CruxInstant::new("2020-07-16T21:53:14.628Z"),
+            CruxInstant::new("2020-07-16T23:53:14.628+03:00"),
+            CruxInstant::new("a"),
+            CruxInstant::new("2020-07-16T21:53:14.628-03:00"),
+        ];
+        instants.sort();
+
+        assert_eq!(
+            instants.iter().map(|i| i.as_str()).collect::<Vec<_>>(),
+            vec![
+                "2020-07-16T23:53:14.628+03:00",
+                "2020-07-16T21:53:14.628Z",
+                "2020-07-16T21:53:14.628-03:00",
+                "a",
+                "b"
+            ]
+        );
+        assert!(CruxInstant::new("2020-07-16T21:53:14.628Z") < CruxInstant::new("1"));
+        assert_ne!(CruxInstant::new("a"), CruxInstant::new("b"));
+        assert_eq!(CruxInstant::new("a"), CruxInstant::new("a"));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn to_time() {
+        let instant = CruxInstant::new("2020-07-16T18:53:14.628-03:00");
+        let time = instant.to_offset_datetime().unwrap();
+
+        assert_eq!(time.unix_timestamp(), 1594936394);
+        assert_eq!(time.offset().whole_hours(), -3);
+        assert_eq!(CruxInstant::try_from(time).unwrap(), instant);
+        assert!(CruxInstant::try_from(
+            time.to_offset(time::UtcOffset::from_whole_seconds(25 * 3600).unwrap())
+        )
+        .is_err());
+    }
+}
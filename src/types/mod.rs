@@ -1,7 +1,11 @@
 pub mod error;
 pub mod http;
+pub mod instant;
 pub mod query;
 pub mod response;
+/// Serializes `serde::Serialize` documents to EDN, feature `serde` is required.
+#[cfg(feature = "serde")]
+pub mod serde_edn;
 
 use edn_rs::{Deserialize, Edn, EdnError, Serialize};
 
@@ -13,7 +17,7 @@ impl Serialize for CruxId {
     fn serialize(mut self) -> String {
         self.0.insert(0, ':');
 
-        format!("{}", self.0.replace(" ", "-"))
+        self.0.replace(" ", "-").to_string()
     }
 }
 
@@ -35,10 +39,62 @@ impl CruxId {
     /// `CruxId::new("Jorge da Silva") -> Edn::Key(":Jorge-da-Silva")`
     pub fn new(id: &str) -> Self {
         let clean_id = id.replace(":", "");
-        Self {
-            0: clean_id.to_string(),
+        Self(clean_id.to_string())
+    }
+}
+
+/// EDN string of `value`, quoted with `"`, `\\` and control characters escaped.
+pub(crate) fn edn_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
         }
     }
+    escaped.push('"');
+    escaped
+}
+
+/// EDN text of `edn` that can be read back as the same value. Elements are separated by spaces, `#inst` and `#uuid` keep their tags
+/// and chars are written as `\c`, which `Display` of `Edn` writes as a symbol.
+pub(crate) fn edn_text(edn: &Edn) -> String {
+    fn join(forms: Vec<String>) -> String {
+        forms.join(" ")
+    }
+    match edn {
+        Edn::Vector(v) => format!(
+            "[{}]",
+            join(v.clone().to_vec().iter().map(edn_text).collect())
+        ),
+        Edn::List(l) => format!(
+            "({})",
+            join(l.clone().to_vec().iter().map(edn_text).collect())
+        ),
+        Edn::Set(s) => format!(
+            "#{{{}}}",
+            join(s.clone().to_set().iter().map(edn_text).collect())
+        ),
+        Edn::Map(m) => format!(
+            "{{{}}}",
+            join(
+                m.clone()
+                    .to_map()
+                    .iter()
+                    .map(|(key, value)| format!("{} {}", key, edn_text(value)))
+                    .collect()
+            )
+        ),
+        Edn::Char(c) => format!("\\{}", c),
+        edn => edn.to_string(),
+    }
 }
 
-pub use http::{Actions, Order};
+pub use http::{Actions, BatchLimit, Order, TxOp};
+pub use instant::CruxInstant;
+pub use transistor_derive::CruxDocument;
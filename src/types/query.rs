@@ -1,8 +1,14 @@
+use crate::types::edn_text;
 use crate::types::error::CruxError;
-use edn_rs::Serialize;
-use std::collections::BTreeSet;
+use edn_rs::{Edn, Serialize};
+use std::collections::{BTreeSet, HashMap};
 use std::convert::TryFrom;
 
+mod prepared;
+mod reader;
+pub use prepared::{Keyword, PreparedQuery, QueryParam};
+use reader::{Form, Kind};
+
 /// A [`Query`](https://opencrux.com/reference/queries.html) is a special kind of body that we submit to the `query` function. It has the following fields:
 /// * `find` is responsible for defining which elements of the query you want shown in the response, it is **required**. Argument is a vector with elements to be queried, `vec!["a", "b", "c"]`. It is parsed as `:find [a b c]`, qhere `a, b, c` are the elements defined in `where` clause.
 /// * `where_clause` is responsible for defining which rules will be applied to filter elements, it is **required**. Argument is a vector with the strings containing the filtering function, `vec!["a :db-key1 b", "a :db-key2 c", "a :db-key3 <some value>"]`. It is parsed as `:where [ [a :db-key1 b] [a :db-key2 c] [a :db-key3 <some value>] ]`.
@@ -10,11 +16,14 @@ use std::convert::TryFrom;
 /// * `order_by` is responsible for defining the order in which the response will be represented, **optional**. Argument is a vector with strings containing the element and how to order (`:asc` or `:desc`) `vec!["time :desc", "device-id :asc"]`.
 /// * `limit` is responsible for defining the limit size of the response, **optional**. Argument is a usize.
 /// * `offset` is responsible for defining the offset of the response, **optional**. Argument is a usize.
+///
+/// Queries written in EDN, with `:rules` too, are parsed by [`from_edn_str`](#method.from_edn_str).
 #[derive(Clone, Debug)]
 pub struct Query {
     find: Find,
     aggregates: Option<std::collections::HashSet<String>>,
     where_: Option<Where>,
+    rules: Option<Rules>,
     args: Option<Args>,
     order_by: Option<OrderBy>,
     limit: Option<Limit>,
@@ -26,6 +35,8 @@ struct Find(Vec<String>);
 #[derive(Clone, Debug)]
 struct Where(Vec<String>);
 #[derive(Clone, Debug)]
+struct Rules(Vec<String>);
+#[derive(Clone, Debug)]
 struct Args(Vec<String>);
 #[derive(Clone, Debug)]
 struct OrderBy(Vec<String>);
@@ -89,6 +100,45 @@ impl Aggregate {
             Aggregate::NonAggregative(s) => s.to_string(),
         }
     }
+
+    /// Aggregate of a `:find` element, a logic var or a form like `(count ?e)` or `(max 5 ?e)`.
+    fn from_form(form: &Form) -> Result<Self, CruxError> {
+        let list = match &form.kind {
+            Kind::Atom => return Ok(Aggregate::NonAggregative(form.raw.to_string())),
+            Kind::List(list) if list.iter().all(|f| f.kind == Kind::Atom) => list,
+            _ => return Err(unsupported_aggregate(form)),
+        };
+        let n = |n: &Form| {
+            n.raw
+                .parse::<usize>()
+                .map_err(|_| unsupported_aggregate(form))
+        };
+        let aggregate = match list.iter().map(|f| f.raw).collect::<Vec<&str>>()[..] {
+            ["sum", v] => Aggregate::Sum(v.to_string()),
+            ["min", v] => Aggregate::Min(v.to_string()),
+            ["max", v] => Aggregate::Max(v.to_string()),
+            ["min", _, v] => Aggregate::MinN(n(&list[1])?, v.to_string()),
+            ["max", _, v] => Aggregate::MaxN(n(&list[1])?, v.to_string()),
+            ["count", v] => Aggregate::Count(v.to_string()),
+            ["count-distinct", v] => Aggregate::CountDistinct(v.to_string()),
+            ["avg", v] => Aggregate::Avg(v.to_string()),
+            ["median", v] => Aggregate::Median(v.to_string()),
+            ["variance", v] => Aggregate::Variance(v.to_string()),
+            ["stddev", v] => Aggregate::Stddev(v.to_string()),
+            ["rand", _, v] => Aggregate::Rand(n(&list[1])?, v.to_string()),
+            ["sample", _, v] => Aggregate::Sample(n(&list[1])?, v.to_string()),
+            ["distinct", v] => Aggregate::Distinct(v.to_string()),
+            _ => return Err(unsupported_aggregate(form)),
+        };
+        Ok(aggregate)
+    }
+}
+
+fn unsupported_aggregate(form: &Form) -> CruxError {
+    CruxError::QueryFormatError(format!(
+        "Find element {} is not supported, it should be a logic var or an aggregate like (count ?e)",
+        form.raw
+    ))
 }
 
 impl std::fmt::Display for Aggregate {
@@ -131,11 +181,10 @@ impl Query {
         }
 
         Ok(Self {
-            find: Find {
-                0: find.into_iter().map(String::from).collect::<Vec<String>>(),
-            },
+            find: Find(find.into_iter().map(String::from).collect::<Vec<String>>()),
             aggregates: None,
             where_: None,
+            rules: None,
             args: None,
             order_by: None,
             limit: None,
@@ -184,14 +233,14 @@ impl Query {
         );
 
         Ok(Self {
-            find: Find {
-                0: find
-                    .into_iter()
+            find: Find(
+                find.into_iter()
                     .map(|a| a.to_string())
                     .collect::<Vec<String>>(),
-            },
-            aggregates: aggregates,
+            ),
+            aggregates,
             where_: None,
+            rules: None,
             args: None,
             order_by: None,
             limit: None,
@@ -209,13 +258,19 @@ impl Query {
     /// Error cases:
     /// * All elements present in find clause should be present in where clause. If your find clause is `"?p", "?n", "?s"`, and your where clause is `"?p1 :alpha ?n", "?p1 :beta true"` an error `Not all element of find, `"?p", "?n", "?s"`, are present in the where clause, ?s is missing` is thrown.
     pub fn where_clause(mut self, where_: Vec<&str>) -> Result<Self, CruxError> {
-        if self.aggregates.is_none() && self.find.0.iter().any(|e| !where_.join(" ").contains(e)) {
-            let error = self
-                .find
-                .0
-                .iter()
-                .find(|e| !where_.join(" ").contains(*e))
-                .unwrap();
+        self.check_where(&where_.join(" "))?;
+
+        let w = where_
+            .iter()
+            .map(|s| format!("[{}]", s.replace("[", "").replace("]", "")))
+            .collect::<Vec<String>>();
+        self.where_ = Some(Where(w));
+        Ok(self)
+    }
+
+    fn check_where(&self, where_: &str) -> Result<(), CruxError> {
+        if self.aggregates.is_none() && self.find.0.iter().any(|e| !where_.contains(e)) {
+            let error = self.find.0.iter().find(|e| !where_.contains(*e)).unwrap();
             return Err(CruxError::QueryFormatError(format!(
                 "Not all element of find, {}, are present in the where clause, {} is missing",
                 self.find.0.join(", "),
@@ -227,22 +282,16 @@ impl Query {
             && self
                 .aggregates
                 .clone()
-                .unwrap_or(std::collections::HashSet::new())
+                .unwrap_or_default()
                 .iter()
-                .any(|e| !where_.join(" ").contains(e))
+                .any(|e| !where_.contains(e))
         {
             return Err(CruxError::QueryFormatError(format!(
                 "Not all element of find, {}, are present in the where clause",
                 self.find.0.join(", "),
             )));
         }
-
-        let w = where_
-            .iter()
-            .map(|s| s.replace("[", "").replace("]", ""))
-            .collect::<Vec<String>>();
-        self.where_ = Some(Where { 0: w });
-        Ok(self)
+        Ok(())
     }
 
     /// `args` is the function responsible for defining the optional `:args` key in the query.
@@ -301,7 +350,7 @@ impl Query {
             .iter()
             .map(|s| s.replace("[", "").replace("]", ""))
             .collect::<Vec<String>>();
-        self.order_by = Some(OrderBy { 0: o });
+        self.order_by = Some(OrderBy(o));
         Ok(self)
     }
 
@@ -309,7 +358,7 @@ impl Query {
     /// Input is a usize with the query limit size.
     /// `.limit(5usize)` Becomes: `:limit 5`.
     pub fn limit(mut self, limit: usize) -> Self {
-        self.limit = Some(Limit { 0: limit });
+        self.limit = Some(Limit(limit));
         self
     }
 
@@ -317,7 +366,7 @@ impl Query {
     /// Input is a usize with the query offset.
     /// `.offset(5usize)` Becomes: `:offset 5`.
     pub fn offset(mut self, offset: usize) -> Self {
-        self.offset = Some(Offset { 0: offset });
+        self.offset = Some(Offset(offset));
         self
     }
 
@@ -337,33 +386,244 @@ impl Query {
             Ok(self)
         }
     }
+
+    /// `from_edn_str` parses a query written in EDN, `{:find [...] :where [...]}` or the `{:query {...}}` body sent to Crux, into a `Query`.
+    /// Supported keys are `:find`, `:where`, `:rules`, `:args`, `:order-by`, `:limit`, `:offset` and `:full-results?`. `:find` accepts logic vars and
+    /// the forms of [`Aggregate`](enum.Aggregate.html), and `:where` accepts rule invocations, `not` and `or` lists besides vector clauses.
+    /// The query is validated like the builder functions and serializes back to the same EDN as a `Query` built with them.
+    ///
+    /// Error cases:
+    /// * Every error of the builder functions.
+    /// * Text that is not a single EDN map, unknown or repeated keys and values of the wrong type return `CruxError::QueryFormatError`.
+    pub fn from_edn_str(edn: &str) -> Result<Self, CruxError> {
+        let form = reader::read(edn)?;
+        let query = match form.children() {
+            [key, query] if key.raw == ":query" => query,
+            _ => &form,
+        };
+        let fields = query_fields(query)?;
+        let field = |key: &str| fields.get(key).copied();
+
+        let find = field(":find")
+            .ok_or_else(|| CruxError::QueryFormatError("Find clause is required".to_string()))?;
+        let find = vector(find, ":find")?;
+        let mut query = if find.iter().any(|f| matches!(f.kind, Kind::List(_))) {
+            Query::find_by_aggregates(
+                find.iter()
+                    .map(Aggregate::from_form)
+                    .collect::<Result<Vec<Aggregate>, CruxError>>()?,
+            )?
+        } else {
+            Query::find(find.iter().map(|f| f.raw).collect())?
+        };
+
+        if let Some(where_) = field(":where") {
+            let clauses = vector(where_, ":where")?
+                .iter()
+                .map(|clause| match clause.kind {
+                    Kind::Vector(_) | Kind::List(_) => Ok(clause.raw.to_string()),
+                    _ => Err(invalid(":where", "vector or list clauses", clause.raw)),
+                })
+                .collect::<Result<Vec<String>, CruxError>>()?;
+            query.check_where(&clauses.join(" "))?;
+            query.where_ = Some(Where(clauses));
+        }
+        let query = query.build()?;
+
+        query
+            .with_fields(field(":rules"), field(":args"), field(":order-by"))?
+            .with_options(field(":limit"), field(":offset"), field(":full-results?"))
+    }
+
+    fn with_fields(
+        mut self,
+        rules: Option<&Form>,
+        args: Option<&Form>,
+        order_by: Option<&Form>,
+    ) -> Result<Self, CruxError> {
+        if let Some(rules) = rules {
+            let rules = vector(rules, ":rules")?
+                .iter()
+                .map(
+                    |rule| match rule.children().first().map(|head| &head.kind) {
+                        Some(Kind::List(head))
+                            if matches!(rule.kind, Kind::Vector(_))
+                                && head.first().is_some_and(|name| name.kind == Kind::Atom) =>
+                        {
+                            Ok(rule.raw.to_string())
+                        }
+                        _ => Err(invalid(
+                            ":rules",
+                            "vectors starting with a rule head like (rule-name ?e)",
+                            rule.raw,
+                        )),
+                    },
+                )
+                .collect::<Result<Vec<String>, CruxError>>()?;
+            self.rules = Some(Rules(rules));
+        }
+
+        if let Some(args) = args {
+            let where_ = self.where_.clone().unwrap().0.join(" ");
+            let mut keys = BTreeSet::new();
+            let mut maps = Vec::new();
+            for map in vector(args, ":args")? {
+                match &map.kind {
+                    Kind::Map(entries) if entries.len() % 2 == 0 => {
+                        keys.extend(entries.iter().step_by(2).map(|key| key.raw.to_string()));
+                        maps.push(map.inner().to_string());
+                    }
+                    _ => return Err(invalid(":args", "maps of logic vars to values", map.raw)),
+                }
+            }
+            check_args_keys(&keys, &where_)?;
+            self.args = Some(Args(maps));
+        }
+
+        if let Some(order_by) = order_by {
+            let order_by = vector(order_by, ":order-by")?
+                .iter()
+                .map(|order| match (&order.kind, order.children()) {
+                    (Kind::Vector(_), [element, direction]) => {
+                        Ok(format!("{} {}", element.raw, direction.raw))
+                    }
+                    _ => Err(invalid(
+                        ":order-by",
+                        "[element :asc] or [element :desc]",
+                        order.raw,
+                    )),
+                })
+                .collect::<Result<Vec<String>, CruxError>>()?;
+            self = self.order_by(order_by.iter().map(String::as_str).collect())?;
+        }
+        Ok(self)
+    }
+
+    fn with_options(
+        mut self,
+        limit: Option<&Form>,
+        offset: Option<&Form>,
+        full_results: Option<&Form>,
+    ) -> Result<Self, CruxError> {
+        let number = |form: &Form, key: &str| {
+            form.raw
+                .parse::<usize>()
+                .map_err(|_| invalid(key, "a positive integer", form.raw))
+        };
+        if let Some(limit) = limit {
+            self = self.limit(number(limit, ":limit")?);
+        }
+        if let Some(offset) = offset {
+            self = self.offset(number(offset, ":offset")?);
+        }
+        match full_results.map(|form| form.raw) {
+            None | Some("false") => (),
+            Some("true") => self = self.with_full_results(),
+            Some(other) => return Err(invalid(":full-results?", "a boolean", other)),
+        }
+        Ok(self)
+    }
 }
 
-impl Serialize for Query {
-    fn serialize(self) -> String {
-        let mut q = String::from("{:query\n {");
+const QUERY_KEYS: [&str; 8] = [
+    ":find",
+    ":where",
+    ":rules",
+    ":args",
+    ":order-by",
+    ":limit",
+    ":offset",
+    ":full-results?",
+];
+
+/// Values of the keys of a query map.
+fn query_fields<'f, 'a>(query: &'f Form<'a>) -> Result<HashMap<&'a str, &'f Form<'a>>, CruxError> {
+    let entries = match &query.kind {
+        Kind::Map(entries) if entries.len() % 2 == 0 => entries,
+        _ => {
+            return Err(CruxError::QueryFormatError(format!(
+                "Query should be a map like {{:find [...] :where [...]}}, found {}",
+                query.raw
+            )))
+        }
+    };
+
+    let mut fields = HashMap::new();
+    for entry in entries.chunks(2) {
+        let key = entry[0].raw;
+        if !QUERY_KEYS.contains(&key) {
+            return Err(CruxError::QueryFormatError(format!(
+                "Unknown query key {}, supported keys are {}",
+                key,
+                QUERY_KEYS.join(", ")
+            )));
+        }
+        if fields.insert(key, &entry[1]).is_some() {
+            return Err(CruxError::QueryFormatError(format!(
+                "Query key {} is repeated",
+                key
+            )));
+        }
+    }
+    Ok(fields)
+}
+
+fn vector<'f, 'a>(form: &'f Form<'a>, key: &str) -> Result<&'f [Form<'a>], CruxError> {
+    match form.kind {
+        Kind::Vector(_) => Ok(form.children()),
+        _ => Err(invalid(key, "a vector", form.raw)),
+    }
+}
+
+fn invalid(key: &str, expected: &str, found: &str) -> CruxError {
+    CruxError::QueryFormatError(format!("{} should be {}, found {}", key, expected, found))
+}
+
+impl TryFrom<Edn> for Query {
+    type Error = CruxError;
+
+    /// Parses `edn` like [`Query::from_edn_str`](struct.Query.html#method.from_edn_str). `Edn::Map` is sorted by key,
+    /// so `:args` maps are serialized in that order.
+    fn try_from(edn: Edn) -> Result<Self, Self::Error> {
+        Query::from_edn_str(&edn_text(&edn))
+    }
+}
+
+impl Query {
+    /// The query map, `{:find [..] :where [..] ..}`, that is sent as `:query` to `/query`.
+    pub(crate) fn query_map(self) -> String {
+        let mut q = String::from("{");
         q.push_str(&edn_rs::to_string(self.find));
         q.push_str(&edn_rs::to_string(self.where_.unwrap()));
-        if self.args.is_some() {
-            q.push_str(&edn_rs::to_string(self.args.unwrap()));
+        if let Some(rules) = self.rules {
+            q.push_str(&edn_rs::to_string(rules));
         }
-        if self.order_by.is_some() {
-            q.push_str(&edn_rs::to_string(self.order_by.unwrap()));
+        if let Some(args) = self.args {
+            q.push_str(&edn_rs::to_string(args));
         }
-        if self.limit.is_some() {
-            q.push_str(&edn_rs::to_string(self.limit.unwrap()));
+        if let Some(order_by) = self.order_by {
+            q.push_str(&edn_rs::to_string(order_by));
         }
-        if self.offset.is_some() {
-            q.push_str(&edn_rs::to_string(self.offset.unwrap()));
+        if let Some(limit) = self.limit {
+            q.push_str(&edn_rs::to_string(limit));
         }
-        if self.full_results == true {
+        if let Some(offset) = self.offset {
+            q.push_str(&edn_rs::to_string(offset));
+        }
+        if self.full_results {
             q.push_str(" :full-results? true\n")
         }
-        q.push_str("}}");
+        q.push('}');
         q
     }
 }
 
+impl Serialize for Query {
+    fn serialize(self) -> String {
+        format!("{{:query\n {}}}", self.query_map())
+    }
+}
+
 impl Serialize for Find {
     fn serialize(self) -> String {
         let mut q = String::from(":find [");
@@ -375,9 +635,18 @@ impl Serialize for Find {
 
 impl Serialize for Where {
     fn serialize(self) -> String {
-        let mut q = String::from(":where [[");
-        q.push_str(&self.0.join("]\n["));
-        q.push_str("]]\n");
+        let mut q = String::from(":where [");
+        q.push_str(&self.0.join("\n"));
+        q.push_str("]\n");
+        q
+    }
+}
+
+impl Serialize for Rules {
+    fn serialize(self) -> String {
+        let mut q = String::from(":rules [");
+        q.push_str(&self.0.join("\n"));
+        q.push_str("]\n");
         q
     }
 }
@@ -404,7 +673,7 @@ impl Serialize for Limit {
     fn serialize(self) -> String {
         let mut q = String::from(":limit ");
         q.push_str(&self.0.to_string());
-        q.push_str("\n");
+        q.push('\n');
         q
     }
 }
@@ -413,7 +682,7 @@ impl Serialize for Offset {
     fn serialize(self) -> String {
         let mut q = String::from(":offset ");
         q.push_str(&self.0.to_string());
-        q.push_str("\n");
+        q.push('\n');
         q
     }
 }
@@ -426,18 +695,65 @@ impl TryFrom<RawArgsWithWhere<'_>> for Args {
     fn try_from(value: RawArgsWithWhere) -> Result<Self, Self::Error> {
         let (args, where_) = value;
 
-        let args_key_set = args_key_bset(&args);
-
-        let all_elements_in_where = args_key_set.iter().any(|e| !where_.contains(e));
-        let has_question = args_key_set.iter().any(|e| !e.starts_with("?"));
+        check_args_keys(&args_key_bset(&args), &where_)?;
+        Ok(Args(
+            args.iter()
+                .map(|s| s.replace("{", "").replace("}", ""))
+                .collect::<Vec<String>>(),
+        ))
+    }
+}
 
-        match (all_elements_in_where, has_question) {
-            (true, false) =>  Err(CruxError::QueryFormatError("All elements should be present in where clause".to_string())),
-            (false, true) =>  Err(CruxError::QueryFormatError("All elements should start with '?'".to_string())),
-            (true, true) =>  Err(CruxError::QueryFormatError("All elements should be present in where clause and all elements should start with '?'".to_string())),
-            (false, false) => Ok(Args{0: args.iter().map(|s| s.replace("{", "").replace("}", "")).collect::<Vec<String>>()}),
+/// Logic vars, symbols starting with `?`, of the where clauses in `where_`.
+fn logic_vars(where_: &str) -> Result<BTreeSet<String>, CruxError> {
+    fn collect<'a>(form: &Form<'a>, vars: &mut BTreeSet<&'a str>) {
+        match form.kind {
+            Kind::Atom if form.raw.starts_with('?') => {
+                vars.insert(form.raw);
+            }
+            _ => form
+                .children()
+                .iter()
+                .for_each(|child| collect(child, vars)),
         }
     }
+
+    let where_ = format!("[{}]", where_);
+    let mut vars = BTreeSet::new();
+    collect(&reader::read(&where_)?, &mut vars);
+    Ok(vars.into_iter().map(String::from).collect())
+}
+
+fn check_args_keys(args_key_set: &BTreeSet<String>, where_: &str) -> Result<(), CruxError> {
+    check_keys(args_key_set, |e| where_.contains(e))
+}
+
+/// Like `check_args_keys`, but params should be logic vars of the where clause, `?n` is not present in `?name`.
+fn check_param_keys(params: &BTreeSet<String>, where_: &str) -> Result<(), CruxError> {
+    let where_vars = logic_vars(where_)?;
+    check_keys(params, |e| !e.starts_with('?') || where_vars.contains(e))
+}
+
+fn check_keys(
+    args_key_set: &BTreeSet<String>,
+    in_where: impl Fn(&str) -> bool,
+) -> Result<(), CruxError> {
+    let all_elements_in_where = args_key_set.iter().any(|e| !in_where(e));
+    let has_question = args_key_set.iter().any(|e| !e.starts_with("?"));
+
+    match (all_elements_in_where, has_question) {
+        (true, false) => Err(CruxError::QueryFormatError(
+            "All elements should be present in where clause".to_string(),
+        )),
+        (false, true) => Err(CruxError::QueryFormatError(
+            "All elements should start with '?'".to_string(),
+        )),
+        (true, true) => Err(CruxError::QueryFormatError(
+            "All elements should be present in where clause and all elements should start with '?'"
+                .to_string(),
+        )),
+        (false, false) => Ok(()),
+    }
 }
 
 fn args_key_bset(args: &Vec<&str>) -> BTreeSet<String> {
@@ -455,6 +771,9 @@ fn args_key_bset(args: &Vec<&str>) -> BTreeSet<String> {
 mod test {
     use super::{Aggregate, Query};
     use crate::client::Crux;
+    use edn_rs::Serialize;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
 
     #[test]
     fn query_with_find_and_where() {
@@ -472,7 +791,7 @@ mod test {
     #[test]
     #[should_panic(expected = "Where clause is required")]
     fn expect_query_format_error() {
-        let client = Crux::new("", "").http_client();
+        let client = Crux::new("", "").blocking_client();
         let query_where_is_none = Query::find(vec!["?p1", "?n"]).unwrap().build().unwrap();
 
         let _ = client.query(query_where_is_none).unwrap();
@@ -555,7 +874,7 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Order element should be \\\':asc\\\' or \\\':desc\\\'")]
+    #[should_panic(expected = "Order element should be ':asc' or ':desc'")]
     fn order_should_panic_for_unknow_order_element() {
         let _query = Query::find(vec!["?p1", "?n", "?s"])
             .unwrap()
@@ -593,7 +912,7 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "All elements should start with \\\'?\\\'")]
+    #[should_panic(expected = "All elements should start with '?'")]
     fn all_args_should_start_with_question() {
         let _query = Query::find(vec!["?p1", "?n"])
             .unwrap()
@@ -640,7 +959,7 @@ mod test {
 
     #[test]
     #[should_panic(
-        expected = "All elements of find clause should start with \\\'?\\\', element \\\'(min e)\\\' doesn\\\'t conform"
+        expected = "All elements of find clause should start with '?', element '(min e)' doesn't conform"
     )]
     fn query_with_aggregates_error() {
         let _ = Query::find_by_aggregates(vec![
@@ -655,4 +974,125 @@ mod test {
         .unwrap()
         .build();
     }
+
+    #[test]
+    fn from_edn_str_serializes_back_identically() {
+        let queries = vec![
+            Query::find(vec!["?p1"])
+                .unwrap()
+                .where_clause(vec!["?p1 :first-name n", "?p1 :last-name ?n"])
+                .unwrap()
+                .args(vec!["?n \"Jorge\""])
+                .unwrap()
+                .order_by(vec!["?p1 :Asc"])
+                .unwrap()
+                .limit(5)
+                .offset(10)
+                .with_full_results()
+                .build()
+                .unwrap(),
+            Query::find_by_aggregates(vec![
+                Aggregate::MinN(5, "?e".to_string()),
+                Aggregate::CountDistinct("?e".to_string()),
+                Aggregate::NonAggregative("?e".to_string()),
+            ])
+            .unwrap()
+            .where_clause(vec!["?e :type :burger"])
+            .unwrap()
+            .build()
+            .unwrap(),
+        ];
+
+        for query in queries {
+            let edn = query.serialize();
+            assert_eq!(Query::from_edn_str(&edn).unwrap().serialize(), edn);
+        }
+    }
+
+    #[test]
+    fn from_edn_str_with_rules() {
+        let edn = r#"
+            ;; shared with the Clojure services
+            {:find [?name (count ?friend)]
+             :where [(adult ?p)
+                     [?p :name ?name]
+                     (not [?p :banned? true])
+                     [?p :friend ?friend]]
+             :rules [[(adult ?p) [?p :age ?age] [(>= ?age 18)]]]
+             :order-by [[?name :asc]]
+             :limit 10}"#;
+        let expected = "{:query\n {:find [?name (count ?friend)]\n:where [(adult ?p)\n[?p :name ?name]\n(not [?p :banned? true])\n[?p :friend ?friend]]\n:rules [[(adult ?p) [?p :age ?age] [(>= ?age 18)]]]\n:order-by [[?name :asc]]\n:limit 10\n}}";
+
+        let query = Query::from_edn_str(edn).unwrap();
+
+        assert_eq!(query.serialize(), expected);
+        assert_eq!(Query::from_edn_str(expected).unwrap().serialize(), expected);
+    }
+
+    #[test]
+    fn from_edn_str_args() {
+        let query = Query::from_edn_str(
+            "{:find [?p] :where [[?p :name ?n] [?p :born ?b]] :args [{?n \"Ivan Ivanov\" ?b #inst \"2020-01-01T00:00:00Z\"}]}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.serialize(),
+            "{:query\n {:find [?p]\n:where [[?p :name ?n]\n[?p :born ?b]]\n:args [{?n \"Ivan Ivanov\" ?b #inst \"2020-01-01T00:00:00Z\"}]\n}}"
+        );
+    }
+
+    #[test]
+    fn try_from_edn() {
+        let edn = edn_rs::Edn::from_str(
+            "{:find [?p (max 3 ?a)] :where [[?p :age ?a]] :args [{?a 30}] :offset 2}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Query::try_from(edn).unwrap().serialize(),
+            "{:query\n {:find [?p (max 3 ?a)]\n:where [[?p :age ?a]]\n:args [{?a 30}]\n:offset 2\n}}"
+        );
+    }
+
+    #[test]
+    fn from_edn_str_errors() {
+        let error = |edn: &str| format!("{}", Query::from_edn_str(edn).unwrap_err());
+
+        assert_eq!(
+            error("{:find [?p1 ?n] :where [[?p1 :name ?g]]}"),
+            "Not all element of find, ?p1, ?n, are present in the where clause, ?n is missing"
+        );
+        assert_eq!(error("{:find [?p1]}"), "Where clause is required");
+        assert_eq!(error("{:where [[?p :a ?b]]}"), "Find clause is required");
+        assert_eq!(
+            error("{:find [?p] :where [[?p :a ?b]] :args [{?c 1}]}"),
+            "All elements should be present in where clause"
+        );
+        assert_eq!(
+            error("{:find [?p] :where [[?p :a ?b]] :order-by [[?p :eq]]}"),
+            "Order element should be ':asc' or ':desc'"
+        );
+        assert_eq!(
+            error("{:find [?p] :where [[?p :a ?b]] :order-by [[?p]]}"),
+            ":order-by should be [element :asc] or [element :desc], found [?p]"
+        );
+        assert_eq!(
+            error("{:find [(pull ?p [*])] :where [[?p :a ?b]]}"),
+            "Find element (pull ?p [*]) is not supported, it should be a logic var or an aggregate like (count ?e)"
+        );
+        assert_eq!(
+            error("{:find [?p] :where [[?p :a ?b]] :limit -1}"),
+            ":limit should be a positive integer, found -1"
+        );
+        assert_eq!(
+            error("{:find [?p] :where [[?p :a ?b]] :rules [(adult ?p)]}"),
+            ":rules should be vectors starting with a rule head like (rule-name ?e), found (adult ?p)"
+        );
+        assert!(error("{:find [?p] :where [[?p :a ?b]] :in [$ ?b]}")
+            .starts_with("Unknown query key :in"));
+        assert!(error("{:find [?p] :find [?p]}").contains("repeated"));
+        assert!(error("[:find ?p :where [?p :a ?b]]").starts_with("Query should be a map"));
+        assert!(error("{:find [?p] :where [[?p :a ?b]").starts_with("Unbalanced"));
+    }
 }
@@ -0,0 +1,376 @@
+use super::{check_param_keys, Args, Query};
+use crate::types::error::CruxError;
+use crate::types::http::is_keyword;
+use crate::types::instant::rfc3339_millis;
+use crate::types::{edn_string, CruxId, CruxInstant};
+use chrono::prelude::*;
+use std::collections::BTreeSet;
+
+/// `QueryParam` is a value bound to a param of a [`PreparedQuery`](struct.PreparedQuery.html), written as EDN.
+/// It is implemented for strings, numbers, `bool`, `CruxId`, `Keyword`, `DateTime`, `CruxInstant` and `Option`, which is `nil` when `None`.
+pub trait QueryParam {
+    /// EDN of the value. Strings are quoted and escaped, times are `#inst` and ids and keywords are validated,
+    /// so a value can't change the query it is bound to.
+    fn to_edn(&self) -> Result<String, CruxError>;
+}
+
+/// `Keyword` is an EDN keyword param, `Keyword::new("status/active")` is bound as `:status/active`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keyword(String);
+
+impl Keyword {
+    /// `Keyword::new` adds the leading `:` to `name` if it is missing.
+    pub fn new(name: &str) -> Self {
+        if name.starts_with(':') {
+            Self(name.to_string())
+        } else {
+            Self(format!(":{}", name))
+        }
+    }
+}
+
+fn keyword(keyword: String) -> Result<String, CruxError> {
+    if is_keyword(&keyword) {
+        Ok(keyword)
+    } else {
+        Err(CruxError::QueryFormatError(format!(
+            "Param {} is not a valid keyword",
+            keyword
+        )))
+    }
+}
+
+impl QueryParam for Keyword {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        keyword(self.0.clone())
+    }
+}
+
+impl QueryParam for CruxId {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        keyword(edn_rs::to_string(self.clone()))
+    }
+}
+
+impl QueryParam for str {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        Ok(edn_string(self))
+    }
+}
+
+impl QueryParam for String {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        Ok(edn_string(self))
+    }
+}
+
+impl QueryParam for bool {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        Ok(self.to_string())
+    }
+}
+
+macro_rules! integer_params {
+    ($($ty:ty),*) => {
+        $(impl QueryParam for $ty {
+            fn to_edn(&self) -> Result<String, CruxError> {
+                Ok(self.to_string())
+            }
+        })*
+    };
+}
+
+integer_params!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl QueryParam for f64 {
+    // `Debug` keeps the decimal point, `1.0` instead of `1`, so Crux reads a double.
+    fn to_edn(&self) -> Result<String, CruxError> {
+        Ok(match self {
+            n if n.is_nan() => "##NaN".to_string(),
+            n if n.is_infinite() && n.is_sign_positive() => "##Inf".to_string(),
+            n if n.is_infinite() => "##-Inf".to_string(),
+            n => format!("{:?}", n),
+        })
+    }
+}
+
+impl QueryParam for f32 {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        f64::from(*self).to_edn()
+    }
+}
+
+impl<Tz: TimeZone> QueryParam for DateTime<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fn to_edn(&self) -> Result<String, CruxError> {
+        Ok(format!("#inst \"{}\"", rfc3339_millis(self)))
+    }
+}
+
+impl QueryParam for CruxInstant {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        Ok(format!("#inst {}", edn_string(self.as_str())))
+    }
+}
+
+impl<T: QueryParam> QueryParam for Option<T> {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        match self {
+            Some(value) => value.to_edn(),
+            None => Ok("nil".to_string()),
+        }
+    }
+}
+
+impl<T: QueryParam + ?Sized> QueryParam for &T {
+    fn to_edn(&self) -> Result<String, CruxError> {
+        (**self).to_edn()
+    }
+}
+
+/// `PreparedQuery` is a built `Query` with declared params, logic vars of its `where_clause` that are bound to values
+/// when the query is run, instead of with `Query::args` strings. It is validated once and can be bound many times:
+/// ```rust
+/// use transistor::types::query::{Keyword, PreparedQuery, Query};
+///
+/// let by_name = PreparedQuery::new(
+///     Query::find(vec!["?p"])?
+///         .where_clause(vec!["?p :first-name ?n", "?p :status ?s"])?
+///         .build()?,
+///     vec!["?n", "?s"],
+/// )?;
+///
+/// let query = by_name.bind(&[&"Ivan \"The Terrible\"", &Keyword::new("active")])?;
+/// assert!(edn_rs::to_string(query).contains(":args [{?n \"Ivan \\\"The Terrible\\\"\" ?s :active}]"));
+/// # Ok::<(), transistor::types::error::CruxError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct PreparedQuery {
+    query: Query,
+    params: Vec<String>,
+}
+
+impl PreparedQuery {
+    /// `PreparedQuery::new` declares the `params` of `query`, in the order their values are bound.
+    ///
+    /// Error cases:
+    /// * `query` without a where clause or with `args` returns `CruxError::QueryFormatError`.
+    /// * Params are validated like the keys of `Query::args`, they should start with `?` and be logic vars of the where clause, so `?n` does not match `?name`. Repeated params are an error.
+    pub fn new(query: Query, params: Vec<&str>) -> Result<Self, CruxError> {
+        let where_ = match (&query.where_, &query.args) {
+            (None, _) => {
+                return Err(CruxError::QueryFormatError(
+                    "Where clause is required".to_string(),
+                ))
+            }
+            (_, Some(_)) => {
+                return Err(CruxError::QueryFormatError(
+                    "Prepared query can't have args, they are bound with `bind`".to_string(),
+                ))
+            }
+            (Some(where_), None) => where_.0.join(" "),
+        };
+        if params.is_empty() {
+            return Err(CruxError::QueryFormatError(
+                "Prepared query should declare at least one param".to_string(),
+            ));
+        }
+
+        let keys = params
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<BTreeSet<String>>();
+        if keys.len() != params.len() {
+            return Err(CruxError::QueryFormatError(format!(
+                "Params should be declared once, {} has repeated params",
+                params.join(", ")
+            )));
+        }
+        check_param_keys(&keys, &where_)?;
+
+        Ok(Self {
+            query,
+            params: params.into_iter().map(String::from).collect(),
+        })
+    }
+
+    /// Declared params, in the order their values are bound.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// `bind` returns the `Query` with `values` bound to the params, in the order they were declared.
+    /// Becomes: `:args [{?n "Ivan" ?s :active}]`.
+    ///
+    /// Error cases:
+    /// * The number of `values` should be the number of params.
+    /// * Invalid `CruxId` and `Keyword` values, like `Keyword::new("a b")`, return `CruxError::QueryFormatError`.
+    pub fn bind(&self, values: &[&dyn QueryParam]) -> Result<Query, CruxError> {
+        self.bind_all(&[values])
+    }
+
+    /// `bind_all` binds each row of `rows` to the params, like `bind`, and returns a `Query` with an `:args` map per row.
+    /// Crux returns the results of every row.
+    pub fn bind_all(&self, rows: &[&[&dyn QueryParam]]) -> Result<Query, CruxError> {
+        if rows.is_empty() {
+            return Err(CruxError::QueryFormatError(
+                "At least one row of params should be bound".to_string(),
+            ));
+        }
+
+        let args = rows
+            .iter()
+            .map(|values| self.args_map(values))
+            .collect::<Result<Vec<String>, CruxError>>()?;
+        let mut query = self.query.clone();
+        query.args = Some(Args(args));
+        Ok(query)
+    }
+
+    fn args_map(&self, values: &[&dyn QueryParam]) -> Result<String, CruxError> {
+        if values.len() != self.params.len() {
+            return Err(CruxError::QueryFormatError(format!(
+                "Prepared query expects {} params, {}, but {} values were bound",
+                self.params.len(),
+                self.params.join(", "),
+                values.len()
+            )));
+        }
+
+        Ok(self
+            .params
+            .iter()
+            .zip(values)
+            .map(|(param, value)| Ok(format!("{} {}", param, value.to_edn()?)))
+            .collect::<Result<Vec<String>, CruxError>>()?
+            .join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edn_rs::Serialize;
+
+    fn prepared() -> PreparedQuery {
+        PreparedQuery::new(
+            Query::find(vec!["?p"])
+                .unwrap()
+                .where_clause(vec!["?p :name ?n", "?p :age ?a", "?p :born ?b"])
+                .unwrap()
+                .build()
+                .unwrap(),
+            vec!["?n", "?a", "?b"],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn binds_typed_values() {
+        let born = FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2020, 1, 2, 3, 4, 5)
+            .unwrap();
+
+        let query = prepared()
+            .bind(&[&"Ivan \"\\\n", &30u8, &born])
+            .unwrap()
+            .serialize();
+
+        assert_eq!(
+            query,
+            "{:query\n {:find [?p]\n:where [[?p :name ?n]\n[?p :age ?a]\n[?p :born ?b]]\n:args [{?n \"Ivan \\\"\\\\\\n\" ?a 30 ?b #inst \"2020-01-02T03:04:05.000+01:00\"}]\n}}"
+        );
+    }
+
+    #[test]
+    fn param_values() {
+        assert_eq!(CruxId::new("jorge 3").to_edn().unwrap(), ":jorge-3");
+        assert_eq!(
+            Keyword::new("status/active").to_edn().unwrap(),
+            ":status/active"
+        );
+        assert_eq!(Keyword::new(":active").to_edn().unwrap(), ":active");
+        assert_eq!(1f64.to_edn().unwrap(), "1.0");
+        assert_eq!((-2.5f32).to_edn().unwrap(), "-2.5");
+        assert_eq!(f64::NAN.to_edn().unwrap(), "##NaN");
+        assert_eq!(f64::NEG_INFINITY.to_edn().unwrap(), "##-Inf");
+        assert_eq!((-7i64).to_edn().unwrap(), "-7");
+        assert_eq!(true.to_edn().unwrap(), "true");
+        assert_eq!(None::<String>.to_edn().unwrap(), "nil");
+        assert_eq!(
+            CruxInstant::new("2020-01-01T00:00:00Z").to_edn().unwrap(),
+            "#inst \"2020-01-01T00:00:00Z\""
+        );
+        assert_eq!(
+            format!("{}", Keyword::new("a] [?p").to_edn().unwrap_err()),
+            "Param :a] [?p is not a valid keyword"
+        );
+        assert!(CruxId::new("a}").to_edn().is_err());
+    }
+
+    #[test]
+    fn bind_all_and_reuse() {
+        let prepared = prepared();
+        let now = Utc::now();
+
+        let first = prepared.bind(&[&"Ivan", &1, &now]).unwrap();
+        let all = prepared
+            .bind_all(&[&[&"Ivan", &1, &now], &[&"Petr".to_string(), &2, &now]])
+            .unwrap();
+
+        assert!(first
+            .serialize()
+            .contains(":args [{?n \"Ivan\" ?a 1 ?b #inst"));
+        assert_eq!(all.serialize().matches("{?n ").count(), 2);
+        assert_eq!(prepared.params(), &["?n", "?a", "?b"]);
+    }
+
+    #[test]
+    fn errors() {
+        let query = || {
+            Query::find(vec!["?p"])
+                .unwrap()
+                .where_clause(vec!["?p :name ?n"])
+                .unwrap()
+        };
+        let error = |result: Result<PreparedQuery, CruxError>| format!("{}", result.unwrap_err());
+
+        assert_eq!(
+            error(PreparedQuery::new(query(), vec!["?x"])),
+            "All elements should be present in where clause"
+        );
+        assert_eq!(
+            error(PreparedQuery::new(
+                Query::find(vec!["?p"])
+                    .unwrap()
+                    .where_clause(vec!["?p :name ?name"])
+                    .unwrap(),
+                vec!["?n"]
+            )),
+            "All elements should be present in where clause"
+        );
+        assert_eq!(
+            error(PreparedQuery::new(query(), vec!["n"])),
+            "All elements should start with '?'"
+        );
+        assert_eq!(
+            error(PreparedQuery::new(query(), vec!["?n", "?n"])),
+            "Params should be declared once, ?n, ?n has repeated params"
+        );
+        assert_eq!(
+            error(PreparedQuery::new(
+                query().args(vec!["?n \"Ivan\""]).unwrap(),
+                vec!["?n"]
+            )),
+            "Prepared query can't have args, they are bound with `bind`"
+        );
+        assert_eq!(
+            format!("{}", prepared().bind(&[&"Ivan"]).unwrap_err()),
+            "Prepared query expects 3 params, ?n, ?a, ?b, but 1 values were bound"
+        );
+        assert!(prepared().bind_all(&[]).is_err());
+    }
+}
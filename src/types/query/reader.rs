@@ -0,0 +1,198 @@
+use crate::types::error::CruxError;
+
+// Minimal EDN reader for `Query::from_edn_str`. It keeps the source text of every form, so clauses, args and rules
+// are stored as they were written and map entries keep their order, which `edn_rs::Edn::Map` does not.
+
+/// A form and its source text.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Form<'a> {
+    pub(super) raw: &'a str,
+    pub(super) kind: Kind<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Kind<'a> {
+    Vector(Vec<Form<'a>>),
+    List(Vec<Form<'a>>),
+    Map(Vec<Form<'a>>),
+    Set(Vec<Form<'a>>),
+    /// Symbols, keywords, strings, numbers and tagged literals like `#inst "..."`.
+    Atom,
+}
+
+impl<'a> Form<'a> {
+    /// Source text between the delimiters of a collection, `?e :name ?n` for `[?e :name ?n]`.
+    pub(super) fn inner(&self) -> &'a str {
+        let open = if matches!(self.kind, Kind::Set(_)) {
+            2
+        } else {
+            1
+        };
+        match self.kind {
+            Kind::Atom => self.raw,
+            _ => self.raw[open..self.raw.len() - 1].trim(),
+        }
+    }
+
+    pub(super) fn children(&self) -> &[Form<'a>] {
+        match &self.kind {
+            Kind::Vector(forms) | Kind::List(forms) | Kind::Map(forms) | Kind::Set(forms) => forms,
+            Kind::Atom => &[],
+        }
+    }
+}
+
+fn error(message: String) -> CruxError {
+    CruxError::QueryFormatError(message)
+}
+
+/// Reads exactly one form from `text`.
+pub(super) fn read(text: &str) -> Result<Form<'_>, CruxError> {
+    let mut reader = Reader { text, pos: 0 };
+    let form = reader
+        .form()?
+        .ok_or_else(|| error("Query EDN is empty".to_string()))?;
+    reader.skip();
+    if reader.pos < text.len() {
+        return Err(error(format!(
+            "Unexpected text after the query: {}",
+            &text[reader.pos..]
+        )));
+    }
+    Ok(form)
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    /// Skips whitespace, commas and `;` comments.
+    fn skip(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == ',' {
+                self.pos += c.len_utf8();
+            } else if c == ';' {
+                self.pos = self.text[self.pos..]
+                    .find('\n')
+                    .map_or(self.text.len(), |end| self.pos + end);
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Next form, `None` at a closing delimiter or at the end of the text.
+    fn form(&mut self) -> Result<Option<Form<'a>>, CruxError> {
+        self.skip();
+        let start = self.pos;
+        let kind = match self.peek() {
+            None | Some(')') | Some(']') | Some('}') => return Ok(None),
+            Some('[') => Kind::Vector(self.collection(1, ']')?),
+            Some('(') => Kind::List(self.collection(1, ')')?),
+            Some('{') => Kind::Map(self.collection(1, '}')?),
+            Some('#') if self.text[self.pos..].starts_with("#{") => {
+                Kind::Set(self.collection(2, '}')?)
+            }
+            Some('#') => {
+                // tagged literal, the tag and the tagged form are a single atom
+                self.token();
+                if self.form()?.is_none() {
+                    return Err(error(format!("Tag {} has no value", &self.text[start..])));
+                }
+                Kind::Atom
+            }
+            Some('"') => {
+                self.string()?;
+                Kind::Atom
+            }
+            Some(_) => {
+                self.token();
+                Kind::Atom
+            }
+        };
+        Ok(Some(Form {
+            raw: &self.text[start..self.pos],
+            kind,
+        }))
+    }
+
+    fn collection(&mut self, open: usize, close: char) -> Result<Vec<Form<'a>>, CruxError> {
+        let start = self.pos;
+        self.pos += open;
+        let mut forms = Vec::new();
+        while let Some(form) = self.form()? {
+            forms.push(form);
+        }
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            Ok(forms)
+        } else {
+            Err(error(format!(
+                "Unbalanced delimiters, expected `{}` to close {}",
+                close,
+                &self.text[start..self.pos]
+            )))
+        }
+    }
+
+    fn string(&mut self) -> Result<(), CruxError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        for (i, c) in self.text[self.pos..].char_indices() {
+            match c {
+                '"' if !escaped => {
+                    self.pos += i + 1;
+                    return Ok(());
+                }
+                '\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+        Err(error(format!(
+            "Unterminated string {}",
+            &self.text[start..]
+        )))
+    }
+
+    fn token(&mut self) {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || "()[]{}\",;".contains(c))
+            .unwrap_or(rest.len());
+        // `#` of a tag is always consumed
+        self.pos += len.max(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keeps_source_text() {
+        let form = read("{:find [?e] ; comment\n :where [[?e :name \"a ] b\"]\n (rule ?e)] :args [{?b #inst \"2020\" ?a 1}]}").unwrap();
+        let entries = form.children();
+
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[3].children()[0].inner(), "?e :name \"a ] b\"");
+        assert_eq!(entries[3].children()[1].raw, "(rule ?e)");
+        assert_eq!(entries[5].children()[0].inner(), "?b #inst \"2020\" ?a 1");
+        assert_eq!(entries[5].children()[0].children().len(), 4);
+    }
+
+    #[test]
+    fn invalid_text() {
+        assert!(read("").is_err());
+        assert!(read("{:find [?e]").is_err());
+        assert!(read("{:find [?e]} [?e]").is_err());
+        assert!(read("[\"abc]").is_err());
+        assert!(read("[#inst]").is_err());
+    }
+}
@@ -1,47 +1,36 @@
 use crate::types::error::CruxError;
-use chrono::prelude::*;
-use edn_rs::{Deserialize, Edn, EdnError};
+use crate::types::instant::CruxInstant;
+use crate::types::query::Query;
+use edn_rs::{Deserialize, Edn, EdnError, Map, Vector};
 use std::collections::BTreeSet;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone)]
 #[allow(non_snake_case)]
 /// Definition for the response of a `POST` at `tx-log` endpoint
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TxLogResponse {
     pub tx___tx_id: usize,
-    #[cfg(feature = "time_as_str")]
-    pub tx___tx_time: String,
-    #[cfg(not(feature = "time_as_str"))]
-    pub tx___tx_time: DateTime<FixedOffset>,
+    pub tx___tx_time: CruxInstant,
     pub tx__event___tx_events: Option<Vec<Vec<String>>>,
 }
 
 impl Deserialize for TxLogResponse {
     fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
-        #[cfg(not(feature = "time_as_str"))]
-        let tx_time: String = edn_rs::from_edn(&edn[":crux.tx/tx-time"])?;
-
         Ok(Self {
             tx___tx_id: edn_rs::from_edn(&edn[":crux.tx/tx-id"]).unwrap_or(0usize),
-            #[cfg(feature = "time_as_str")]
             tx___tx_time: edn_rs::from_edn(&edn[":crux.tx/tx-time"])?,
-            #[cfg(not(feature = "time_as_str"))]
-            tx___tx_time: tx_time
-                .parse::<DateTime<FixedOffset>>()
-                .map_err(|_| EdnError::Deserialize("Unable to deserialize `:crux.tx/tx-time`, verify if the transaction time you're sending is correct".to_string()))?,
             tx__event___tx_events: edn_rs::from_edn(&edn[":crux.tx.event/tx-events"])?,
         })
     }
 }
 
-impl TxLogResponse {
-    #[cfg(test)]
-    pub fn default() -> Self {
+#[cfg(test)]
+impl Default for TxLogResponse {
+    fn default() -> Self {
         Self {
             tx___tx_id: 8usize,
-            tx___tx_time: "2020-07-16T21:53:14.628-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            tx___tx_time: CruxInstant::new("2020-07-16T21:53:14.628-00:00"),
             tx__event___tx_events: None,
         }
     }
@@ -50,6 +39,7 @@ impl TxLogResponse {
 #[derive(Debug, PartialEq, Clone)]
 #[allow(non_snake_case)]
 /// Definition for the response of a `GET` at `tx-log` endpoint
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TxLogsResponse {
     pub tx_events: Vec<TxLogResponse>,
 }
@@ -80,18 +70,13 @@ impl Deserialize for TxLogsResponse {
 #[derive(Debug, PartialEq, Clone)]
 #[allow(non_snake_case)]
 /// Definition for the response of a `POST` at `/entity-tx` endpoint
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityTxResponse {
     pub db___id: String,
     pub db___content_hash: String,
-    #[cfg(feature = "time_as_str")]
-    pub db___valid_time: String,
-    #[cfg(not(feature = "time_as_str"))]
-    pub db___valid_time: DateTime<FixedOffset>,
+    pub db___valid_time: CruxInstant,
     pub tx___tx_id: usize,
-    #[cfg(feature = "time_as_str")]
-    pub tx___tx_time: String,
-    #[cfg(not(feature = "time_as_str"))]
-    pub tx___tx_time: DateTime<FixedOffset>,
+    pub tx___tx_time: CruxInstant,
 }
 
 impl FromStr for EntityTxResponse {
@@ -102,80 +87,71 @@ impl FromStr for EntityTxResponse {
     }
 }
 
-impl EntityTxResponse {
-    #[cfg(test)]
-    pub fn default() -> Self {
+#[cfg(test)]
+impl Default for EntityTxResponse {
+    fn default() -> Self {
         Self {
             db___id: "d72ccae848ce3a371bd313865cedc3d20b1478ca".to_string(),
             db___content_hash: "1828ebf4466f98ea3f5252a58734208cd0414376".to_string(),
-            db___valid_time: "2020-07-19T04:12:13.788-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            db___valid_time: CruxInstant::new("2020-07-19T04:12:13.788-00:00"),
             tx___tx_id: 28usize,
-            tx___tx_time: "2020-07-19T04:12:13.788-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            tx___tx_time: CruxInstant::new("2020-07-19T04:12:13.788-00:00"),
         }
     }
 }
 
 impl Deserialize for EntityTxResponse {
     fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
-        #[cfg(not(feature = "time_as_str"))]
-        let valid_time: String = edn_rs::from_edn(&edn[":crux.db/valid-time"])?;
-        #[cfg(not(feature = "time_as_str"))]
-        let tx_time: String = edn_rs::from_edn(&edn[":crux.tx/tx-time"])?;
-
         Ok(Self {
             db___id: edn_rs::from_edn(&edn[":crux.db/id"])?,
             db___content_hash: edn_rs::from_edn(&edn[":crux.db/content-hash"])?,
-            #[cfg(feature = "time_as_str")]
-            db___valid_time: edn_rs::from_edn(&edn[":crux.db/valid-time"]),
-            #[cfg(not(feature = "time_as_str"))]
-            db___valid_time: valid_time.parse::<DateTime<FixedOffset>>().unwrap(),
+            db___valid_time: edn_rs::from_edn(&edn[":crux.db/valid-time"])?,
             tx___tx_id: edn_rs::from_edn(&edn[":crux.tx/tx-id"]).unwrap_or(0usize),
-            #[cfg(feature = "time_as_str")]
-            tx___tx_time: edn_rs::from_edn(&edn[":crux.tx/tx-time"]),
-            #[cfg(not(feature = "time_as_str"))]
-            tx___tx_time: tx_time.parse::<DateTime<FixedOffset>>().unwrap(),
+            tx___tx_time: edn_rs::from_edn(&edn[":crux.tx/tx-time"])?,
         })
     }
 }
 
 #[doc(hidden)]
-#[cfg(not(feature = "async"))]
 pub(crate) struct QueryResponse(pub(crate) BTreeSet<Vec<String>>);
 
-#[cfg(not(feature = "async"))]
 impl Deserialize for QueryResponse {
     fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
         if edn.set_iter().is_some() {
             Ok(Self(
                 edn.set_iter()
-                    .ok_or(EdnError::Deserialize(format!(
-                        "The following Edn cannot be deserialized to BTreeSet: {:?}",
-                        edn
-                    )))?
-                    .map(|e| {
-                        e.to_vec().ok_or(EdnError::Deserialize(format!(
-                            "The following Edn cannot be deserialized to Vec: {:?}",
+                    .ok_or_else(|| {
+                        EdnError::Deserialize(format!(
+                            "The following Edn cannot be deserialized to BTreeSet: {:?}",
                             edn
-                        )))
+                        ))
+                    })?
+                    .map(|e| {
+                        e.to_vec().ok_or_else(|| {
+                            EdnError::Deserialize(format!(
+                                "The following Edn cannot be deserialized to Vec: {:?}",
+                                edn
+                            ))
+                        })
                     })
                     .collect::<Result<BTreeSet<Vec<String>>, EdnError>>()?,
             ))
         } else {
             Ok(Self(
                 edn.iter()
-                    .ok_or(EdnError::Deserialize(format!(
-                        "The following Edn cannot be deserialized to BTreeSet: {:?}",
-                        edn
-                    )))?
-                    .map(|e| {
-                        e.to_vec().ok_or(EdnError::Deserialize(format!(
-                            "The following Edn cannot be deserialized to Vec: {:?}",
+                    .ok_or_else(|| {
+                        EdnError::Deserialize(format!(
+                            "The following Edn cannot be deserialized to BTreeSet: {:?}",
                             edn
-                        )))
+                        ))
+                    })?
+                    .map(|e| {
+                        e.to_vec().ok_or_else(|| {
+                            EdnError::Deserialize(format!(
+                                "The following Edn cannot be deserialized to Vec: {:?}",
+                                edn
+                            ))
+                        })
                     })
                     .collect::<Result<BTreeSet<Vec<String>>, EdnError>>()?,
             ))
@@ -185,113 +161,73 @@ impl Deserialize for QueryResponse {
 
 #[cfg(feature = "async")]
 #[derive(Clone, Debug, PartialEq)]
-/// When feature `async` is enabled this is the response type for endpoint `/query`.
+/// When feature `async` is enabled this is the response type for endpoint `/query` on `AsyncHttpClient`.
 pub struct QueryAsyncResponse(pub(crate) BTreeSet<Vec<String>>);
 
 #[cfg(feature = "async")]
 impl Deserialize for QueryAsyncResponse {
     fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
-        if edn.set_iter().is_some() {
-            Ok(Self {
-                0: edn
-                    .set_iter()
-                    .ok_or(EdnError::Deserialize(format!(
-                        "The following Edn cannot be deserialize to BTreeSet: {:?}",
-                        edn
-                    )))?
-                    .map(|e| {
-                        e.to_vec().ok_or(EdnError::Deserialize(format!(
-                            "The following Edn cannot be deserialized to Vec: {:?}",
-                            edn
-                        )))
-                    })
-                    .collect::<Result<BTreeSet<Vec<String>>, EdnError>>()?,
-            })
-        } else {
-            Ok(Self {
-                0: edn
-                    .iter()
-                    .ok_or(EdnError::Deserialize(format!(
-                        "The following Edn cannot be deserialize to BTreeSet: {:?}",
-                        edn
-                    )))?
-                    .map(|e| {
-                        e.to_vec().ok_or(EdnError::Deserialize(format!(
-                            "The following Edn cannot be deserialized to Vec: {:?}",
-                            edn
-                        )))
-                    })
-                    .collect::<Result<BTreeSet<Vec<String>>, EdnError>>()?,
-            })
-        }
+        let QueryResponse(set) = QueryResponse::deserialize(edn)?;
+        Ok(Self(set))
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 #[allow(non_snake_case)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityHistoryElement {
-    #[cfg(feature = "time_as_str")]
-    pub db___valid_time: String,
-    #[cfg(not(feature = "time_as_str"))]
-    pub db___valid_time: DateTime<FixedOffset>,
+    pub db___valid_time: CruxInstant,
     pub tx___tx_id: usize,
-    #[cfg(feature = "time_as_str")]
-    pub tx___tx_time: String,
-    #[cfg(not(feature = "time_as_str"))]
-    pub tx___tx_time: DateTime<FixedOffset>,
+    pub tx___tx_time: CruxInstant,
     pub db___content_hash: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::types::serde_edn::edn_value", default)
+    )]
     pub db__doc: Option<Edn>,
 }
 
 impl Deserialize for EntityHistoryElement {
     fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
-        #[cfg(not(feature = "time_as_str"))]
-        let valid_time: String = edn_rs::from_edn(&edn[":crux.db/valid-time"])?;
-        #[cfg(not(feature = "time_as_str"))]
-        let tx_time: String = edn_rs::from_edn(&edn[":crux.tx/tx-time"])?;
-
         Ok(Self {
             db___content_hash: edn_rs::from_edn(&edn[":crux.db/content-hash"])?,
-            #[cfg(feature = "time_as_str")]
             db___valid_time: edn_rs::from_edn(&edn[":crux.db/valid-time"])?,
-            #[cfg(not(feature = "time_as_str"))]
-            db___valid_time: valid_time.parse::<DateTime<FixedOffset>>().unwrap(),
             tx___tx_id: edn_rs::from_edn(&edn[":crux.tx/tx-id"]).unwrap_or(0usize),
-            #[cfg(feature = "time_as_str")]
             tx___tx_time: edn_rs::from_edn(&edn[":crux.tx/tx-time"])?,
-            #[cfg(not(feature = "time_as_str"))]
-            tx___tx_time: tx_time.parse::<DateTime<FixedOffset>>().unwrap(),
             db__doc: edn.get(":crux.db/doc").map(|d| d.to_owned()),
         })
     }
 }
 
-#[cfg(test)]
 impl EntityHistoryElement {
-    pub fn default() -> Self {
+    /// Bitemporal coordinates of the element, its `(valid_time, tx_time)` pair.
+    /// With corrections, the same valid time appears once for each transaction that wrote it.
+    pub fn coordinates(&self) -> (&CruxInstant, &CruxInstant) {
+        (&self.db___valid_time, &self.tx___tx_time)
+    }
+}
+
+#[cfg(test)]
+impl Default for EntityHistoryElement {
+    fn default() -> Self {
         Self {
             db___content_hash: "1828ebf4466f98ea3f5252a58734208cd0414376".to_string(),
-            db___valid_time: "2020-07-19T04:12:13.788-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            db___valid_time: CruxInstant::new("2020-07-19T04:12:13.788-00:00"),
             tx___tx_id: 28usize,
-            tx___tx_time: "2020-07-19T04:12:13.788-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            tx___tx_time: CruxInstant::new("2020-07-19T04:12:13.788-00:00"),
             db__doc: None,
         }
     }
+}
 
+#[cfg(test)]
+impl EntityHistoryElement {
     pub fn default_docs() -> Self {
         Self {
             db___content_hash: "1828ebf4466f98ea3f5252a58734208cd0414376".to_string(),
-            db___valid_time: "2020-07-19T04:12:13.788-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            db___valid_time: CruxInstant::new("2020-07-19T04:12:13.788-00:00"),
             tx___tx_id: 28usize,
-            tx___tx_time: "2020-07-19T04:12:13.788-00:00"
-                .parse::<DateTime<FixedOffset>>()
-                .unwrap(),
+            tx___tx_time: CruxInstant::new("2020-07-19T04:12:13.788-00:00"),
             db__doc: Some(Edn::Key(":docs".to_string())),
         }
     }
@@ -299,6 +235,7 @@ impl EntityHistoryElement {
 
 /// Definition for the response of a `GET` at `/entity-history` endpoint. This returns a Vec of  `EntityHistoryElement`.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityHistoryResponse {
     pub history: Vec<EntityHistoryElement>,
 }
@@ -325,3 +262,334 @@ impl Deserialize for EntityHistoryResponse {
         })
     }
 }
+
+/// Status of a query in [`QueryInfo`](struct.QueryInfo.html), `:in-progress`, `:completed` or `:failed`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueryStatus {
+    InProgress,
+    Completed,
+    Failed,
+}
+
+impl Deserialize for QueryStatus {
+    fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
+        // JSON responses have the keyword as a string, `"in-progress"`
+        let status = match edn {
+            Edn::Key(k) | Edn::Str(k) => k.trim_start_matches(':'),
+            _ => "",
+        };
+        match status {
+            "in-progress" => Ok(QueryStatus::InProgress),
+            "completed" => Ok(QueryStatus::Completed),
+            "failed" => Ok(QueryStatus::Failed),
+            _ => Err(EdnError::Deserialize(format!(
+                "couldn't convert {} into QueryStatus",
+                edn
+            ))),
+        }
+    }
+}
+
+/// Definition for each query of a `GET` at `/active-queries`, `/recent-queries` and `/slowest-queries`.
+/// `finished_at` is `None` while the query is in progress and `error` is the message of a failed query.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryInfo {
+    pub query_id: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::types::serde_edn::edn_value::required")
+    )]
+    pub query: Edn,
+    pub started_at: CruxInstant,
+    pub finished_at: Option<CruxInstant>,
+    pub status: QueryStatus,
+    pub error: Option<String>,
+}
+
+impl Deserialize for QueryInfo {
+    fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
+        let text = |edn: &Edn| match edn {
+            Edn::Nil => None,
+            Edn::Str(s) | Edn::Uuid(s) => Some(s.to_owned()),
+            edn => Some(edn.to_string()),
+        };
+        let error = match &edn[":error"] {
+            Edn::Map(_) => text(&edn[":error"][":crux.error/message"])
+                .or_else(|| text(&edn[":error"][":message"]))
+                .or_else(|| text(&edn[":error"])),
+            error => text(error),
+        };
+
+        Ok(Self {
+            query_id: text(&edn[":query-id"]).unwrap_or_default(),
+            query: edn[":query"].to_owned(),
+            started_at: edn_rs::from_edn(&edn[":started-at"])?,
+            finished_at: match &edn[":finished-at"] {
+                Edn::Nil => None,
+                finished_at => Some(edn_rs::from_edn(finished_at)?),
+            },
+            status: edn_rs::from_edn(&edn[":status"])?,
+            error,
+        })
+    }
+}
+
+impl QueryInfo {
+    /// Time the query took to run, `None` while it is in progress or if its times are malformed.
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let started_at = self.started_at.to_datetime().ok()?;
+        let finished_at = self.finished_at.as_ref()?.to_datetime().ok()?;
+        Some(finished_at - started_at)
+    }
+
+    /// Returns `true` if this is the entry of `query`, every clause of `query` must be equal to the one Crux received.
+    /// Clauses are compared as plain data, keywords and symbols by their names, so entries received in any `WireFormat` match.
+    pub fn is_query(&self, query: &Query) -> bool {
+        let sent = match Edn::from_str(&edn_rs::to_string(query.clone())) {
+            Ok(edn) => plain(&edn[":query"]),
+            Err(_) => return false,
+        };
+        let received = plain(&self.query);
+        match (&sent, &received) {
+            (Edn::Map(_), Edn::Map(_)) => sent
+                .map_iter()
+                .into_iter()
+                .flatten()
+                .all(|(clause, value)| &received[clause.as_str()] == value),
+            _ => false,
+        }
+    }
+}
+
+// Query clauses as plain data, so they are equal in every `WireFormat`: JSON writes keywords and symbols as strings
+// and lists and sets as arrays. `edn_rs` also reads the comma after a symbol, `[?p, ?n]`, as part of the symbol.
+fn plain(edn: &Edn) -> Edn {
+    let name = |s: &str| s.trim_end_matches(',').trim_start_matches(':').to_string();
+    match edn {
+        Edn::Symbol(s) | Edn::Key(s) => Edn::Str(name(s)),
+        Edn::Inst(s) => Edn::Str(s.to_owned()),
+        Edn::Vector(_) | Edn::List(_) => Edn::Vector(Vector::new(
+            edn.iter().into_iter().flatten().map(plain).collect(),
+        )),
+        Edn::Set(_) => Edn::Vector(Vector::new(
+            edn.set_iter().into_iter().flatten().map(plain).collect(),
+        )),
+        Edn::Map(_) => Edn::Map(Map::new(
+            edn.map_iter()
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| (name(k), plain(v)))
+                .collect(),
+        )),
+        edn => edn.to_owned(),
+    }
+}
+
+/// Definition for the response of a `GET` at `/active-queries`, `/recent-queries` and `/slowest-queries`.
+pub(crate) struct QueryInfoResponse(pub(crate) Vec<QueryInfo>);
+
+impl Deserialize for QueryInfoResponse {
+    fn deserialize(edn: &Edn) -> Result<Self, EdnError> {
+        Ok(Self(
+            edn.iter()
+                .ok_or(EdnError::Deserialize(format!(
+                    "The following Edn cannot be deserialize to query info: {:?}",
+                    edn
+                )))?
+                .map(edn_rs::from_edn)
+                .collect::<Result<Vec<QueryInfo>, EdnError>>()?,
+        ))
+    }
+}
+
+impl EntityHistoryResponse {
+    /// Builds the [`BitemporalGrid`](struct.BitemporalGrid.html) of the history. It should contain corrections, see `entity_history_with_corrections`.
+    pub fn bitemporal_grid(&self) -> BitemporalGrid {
+        let mut valid_times = self
+            .history
+            .iter()
+            .map(|e| e.db___valid_time.clone())
+            .collect::<Vec<CruxInstant>>();
+        valid_times.sort();
+        valid_times.dedup();
+        let mut tx_times = self
+            .history
+            .iter()
+            .map(|e| e.tx___tx_time.clone())
+            .collect::<Vec<CruxInstant>>();
+        tx_times.sort();
+        tx_times.dedup();
+
+        let cells = valid_times
+            .iter()
+            .map(|valid_time| {
+                tx_times
+                    .iter()
+                    .map(|tx_time| {
+                        self.history
+                            .iter()
+                            .filter(|e| {
+                                &e.db___valid_time <= valid_time && &e.tx___tx_time <= tx_time
+                            })
+                            .max_by(|a, b| {
+                                (&a.db___valid_time, &a.tx___tx_time, a.tx___tx_id).cmp(&(
+                                    &b.db___valid_time,
+                                    &b.tx___tx_time,
+                                    b.tx___tx_id,
+                                ))
+                            })
+                            .cloned()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        BitemporalGrid {
+            valid_times,
+            tx_times,
+            cells,
+        }
+    }
+}
+
+/// `BitemporalGrid` is the valid time × transaction time view of an entity history.
+/// Rows are the sorted valid times and columns the sorted transaction times of the history.
+/// Each cell is the version of the entity at that valid time as known at that transaction time, `None` if it was unknown.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitemporalGrid {
+    pub valid_times: Vec<CruxInstant>,
+    pub tx_times: Vec<CruxInstant>,
+    pub cells: Vec<Vec<Option<EntityHistoryElement>>>,
+}
+
+impl BitemporalGrid {
+    /// Version of the entity at `valid_time` as known at `tx_time`, both must be axes of the grid.
+    pub fn get(
+        &self,
+        valid_time: &CruxInstant,
+        tx_time: &CruxInstant,
+    ) -> Option<&EntityHistoryElement> {
+        let row = self.valid_times.binary_search(valid_time).ok()?;
+        let column = self.tx_times.binary_search(tx_time).ok()?;
+        self.cells[row][column].as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn element(valid_time: &str, tx_id: usize, tx_time: &str) -> EntityHistoryElement {
+        EntityHistoryElement {
+            db___valid_time: CruxInstant::new(valid_time),
+            tx___tx_id: tx_id,
+            tx___tx_time: CruxInstant::new(tx_time),
+            db___content_hash: format!("hash-{}", tx_id),
+            db__doc: None,
+        }
+    }
+
+    #[test]
+    fn query_info() {
+        let edn = Edn::from_str(
+            "({:query-id \"ae17c599-dcd2-47aa-8b2b-6ec8d4c6a7c1\", :query {:find [?e] :where [[?e :name ?n]]}, \
+               :started-at \"2020-10-16T13:37:11.071-00:00\", :finished-at nil, :status :in-progress, :error nil} \
+              {:query-id \"5f3ab2c3-2b1c-4a8e-9a8e-11d2c7a0c0b1\", :query {:find [?e] :where [[?e :age ?a]]}, \
+               :started-at \"2020-10-16T13:37:11.071-00:00\", :finished-at \"2020-10-16T13:37:11.571-00:00\", \
+               :status :failed, :error {:crux.error/message \"Query didn't match expected structure\"}})",
+        )
+        .unwrap();
+
+        let QueryInfoResponse(queries) = edn_rs::from_edn(&edn).unwrap();
+
+        assert_eq!(queries[0].status, QueryStatus::InProgress);
+        assert_eq!(queries[0].duration(), None);
+        assert_eq!(queries[1].status, QueryStatus::Failed);
+        assert_eq!(
+            queries[1].duration(),
+            Some(chrono::Duration::milliseconds(500))
+        );
+        assert_eq!(
+            queries[1].error.as_deref(),
+            Some("Query didn't match expected structure")
+        );
+        assert_eq!(
+            queries[1].query[":where"],
+            Edn::from_str("[[?e :age ?a]]").unwrap()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn is_query_in_every_format() {
+        use crate::http::WireFormat;
+
+        let query = Query::find(vec!["?e"])
+            .unwrap()
+            .where_clause(vec!["?e :name ?n"])
+            .unwrap()
+            .args(vec!["?n \"Ivan\""])
+            .unwrap()
+            .build()
+            .unwrap();
+        let recent = "({:query-id \"ae17c599-dcd2-47aa-8b2b-6ec8d4c6a7c1\", \
+                        :query {:find [?e] :where [[?e :name ?n]] :args [{?n \"Ivan\"}]}, \
+                        :started-at \"2020-10-16T13:37:11.071-00:00\", :finished-at nil, :status :in-progress, :error nil})";
+
+        for format in [WireFormat::Edn, WireFormat::Json, WireFormat::TransitJson] {
+            let body = format.encode(recent).unwrap();
+            let QueryInfoResponse(queries) =
+                edn_rs::from_edn(&format.decode(&body).unwrap()).unwrap();
+
+            assert!(queries[0].is_query(&query), "{:?}", format);
+            assert!(!queries[0].is_query(&query.clone().args(vec!["?n \"Petr\""]).unwrap()));
+        }
+    }
+
+    #[test]
+    fn bitemporal_grid() {
+        let history = EntityHistoryResponse {
+            history: vec![
+                element("2020-01-01T00:00:00.000Z", 0, "2020-01-01T00:00:00.000Z"),
+                element("2020-02-01T00:00:00.000Z", 1, "2020-02-01T00:00:00.000Z"),
+                element("2020-01-01T00:00:00.000Z", 2, "2020-03-01T00:00:00.000Z"),
+            ],
+        };
+
+        let grid = history.bitemporal_grid();
+        let hash = |valid: &str, tx: &str| {
+            grid.get(&CruxInstant::new(valid), &CruxInstant::new(tx))
+                .map(|e| e.db___content_hash.as_str())
+        };
+
+        assert_eq!(grid.valid_times.len(), 2);
+        assert_eq!(grid.tx_times.len(), 3);
+        assert_eq!(
+            history.history[2].coordinates(),
+            (&grid.valid_times[0], &grid.tx_times[2])
+        );
+        assert_eq!(
+            hash("2020-01-01T00:00:00.000Z", "2020-01-01T00:00:00.000Z"),
+            Some("hash-0")
+        );
+        assert_eq!(
+            hash("2020-01-01T00:00:00.000Z", "2020-03-01T00:00:00.000Z"),
+            Some("hash-2")
+        );
+        assert_eq!(
+            hash("2020-02-01T00:00:00.000Z", "2020-01-01T00:00:00.000Z"),
+            Some("hash-0")
+        );
+        assert_eq!(
+            hash("2020-02-01T00:00:00.000Z", "2020-03-01T00:00:00.000Z"),
+            Some("hash-1")
+        );
+        assert_eq!(
+            hash("2019-02-01T00:00:00.000Z", "2020-03-01T00:00:00.000Z"),
+            None
+        );
+    }
+}
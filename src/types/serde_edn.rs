@@ -0,0 +1,814 @@
+use crate::types::error::CruxError;
+use crate::types::instant::rfc3339_millis;
+use crate::types::{edn_string, CruxId};
+use chrono::prelude::*;
+use serde::ser::{self, Serialize};
+use std::fmt::Display;
+
+/// Newtype struct name used by `inst` to mark a string as `#inst`.
+const INST_TOKEN: &str = "$transistor::Inst";
+/// Newtype struct name used by `CruxId` to mark a string as a keyword.
+const KEYWORD_TOKEN: &str = "$transistor::Keyword";
+
+/// Serializes any `serde::Serialize` value into Crux compatible EDN.
+/// * Struct fields and string map keys become keywords with the same naming as `edn-derive`, `first_name` is `:first-name` and `crux__db___id` is `:crux.db/id`.
+///   Names starting with `:` or containing `/` are kept, so `#[serde(rename = "crux.db/id")]` is also `:crux.db/id`.
+/// * `CruxId` is serialized as a keyword.
+/// * `chrono` times annotated with `#[serde(with = "transistor::types::serde_edn::inst")]` are serialized as `#inst`.
+/// * Unit enum variants become keywords, `None` and `()` become `nil`.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, CruxError> {
+    let mut serializer = EdnSerializer::new(Mode::Value);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// `SerdeDocument` is a document serialized with [`to_string`](fn.to_string.html).
+/// It implements `edn_rs::Serialize`, so it can be used with `Actions::append_put` and `Actions::append_match_doc`.
+/// ```rust
+/// use serde::Serialize;
+/// use transistor::types::{serde_edn::SerdeDocument, Actions, CruxId};
+///
+/// #[derive(Serialize)]
+/// struct Person {
+///     crux__db___id: CruxId,
+///     first_name: String,
+/// }
+///
+/// let person = Person { crux__db___id: CruxId::new("jorge-3"), first_name: "Michael".to_string() };
+/// let actions = Actions::new().append_put(SerdeDocument::new(&person).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerdeDocument(String);
+
+impl SerdeDocument {
+    pub fn new<T: Serialize + ?Sized>(document: &T) -> Result<Self, CruxError> {
+        Ok(Self(to_string(document)?))
+    }
+}
+
+impl edn_rs::Serialize for SerdeDocument {
+    fn serialize(self) -> String {
+        self.0
+    }
+}
+
+impl Serialize for CruxId {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer
+            .serialize_newtype_struct(KEYWORD_TOKEN, &edn_rs::Serialize::serialize(self.clone()))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for CruxId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Ok(CruxId::new(&id))
+    }
+}
+
+/// `#[serde(with = "transistor::types::serde_edn::inst")]` serializes a `chrono::DateTime` as `#inst` in EDN and as an RFC 3339 string in other formats,
+/// both with millisecond precision like every time sent to Crux, `2014-11-28T21:00:09.000+09:00`.
+pub mod inst {
+    use super::*;
+
+    pub fn serialize<S, Tz>(time: &DateTime<Tz>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        serializer.serialize_newtype_struct(INST_TOKEN, &rfc3339_millis(time))
+    }
+
+    pub fn deserialize<'de, D, Tz>(deserializer: D) -> Result<DateTime<Tz>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        Tz: TimeZone,
+        DateTime<Tz>: serde::Deserialize<'de>,
+    {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    /// Same as `inst` for `Option<DateTime<Tz>>`.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S, Tz>(
+            time: &Option<DateTime<Tz>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: ser::Serializer,
+            Tz: TimeZone,
+            Tz::Offset: Display,
+        {
+            match time {
+                Some(time) => serializer.serialize_some(&Inst(time)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D, Tz>(deserializer: D) -> Result<Option<DateTime<Tz>>, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+            Tz: TimeZone,
+            DateTime<Tz>: serde::Deserialize<'de>,
+        {
+            serde::Deserialize::deserialize(deserializer)
+        }
+
+        struct Inst<'a, Tz: TimeZone>(&'a DateTime<Tz>);
+
+        impl<'a, Tz: TimeZone> Serialize for Inst<'a, Tz>
+        where
+            Tz::Offset: Display,
+        {
+            fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::serialize(self.0, serializer)
+            }
+        }
+    }
+}
+
+/// `#[serde(with = "...")]` for `Option<Edn>` fields of the response types.
+/// Values are exported as plain data, keywords and instants are strings.
+pub(crate) mod edn_value {
+    use edn_rs::{Edn, Map, Vector};
+    use serde::de::{self, MapAccess, SeqAccess, Visitor};
+    use serde::ser::{SerializeMap, SerializeSeq};
+    use serde::{Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+    use std::fmt;
+
+    struct Value<'a>(&'a Edn);
+
+    impl<'a> Serialize for Value<'a> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.0 {
+                Edn::Vector(_) | Edn::List(_) | Edn::Set(_) => {
+                    let items = match self.0 {
+                        Edn::Set(_) => self.0.set_iter().map(|i| i.collect::<Vec<&Edn>>()),
+                        _ => self.0.iter().map(|i| i.collect::<Vec<&Edn>>()),
+                    }
+                    .unwrap_or_default();
+                    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                    for item in items {
+                        seq.serialize_element(&Value(item))?;
+                    }
+                    seq.end()
+                }
+                Edn::Map(_) => {
+                    let mut map = serializer.serialize_map(None)?;
+                    for (k, v) in self.0.map_iter().into_iter().flatten() {
+                        map.serialize_entry(k, &Value(v))?;
+                    }
+                    map.end()
+                }
+                Edn::NamespacedMap(ns, m) => {
+                    let mut map = serializer.serialize_map(None)?;
+                    for (k, v) in m.clone().to_map() {
+                        map.serialize_entry(
+                            &format!(":{}/{}", ns, k.trim_start_matches(':')),
+                            &Value(&v),
+                        )?;
+                    }
+                    map.end()
+                }
+                Edn::Key(s) | Edn::Symbol(s) | Edn::Str(s) | Edn::Inst(s) | Edn::Uuid(s) => {
+                    serializer.serialize_str(s)
+                }
+                Edn::Int(i) => serializer.serialize_i64(*i as i64),
+                Edn::UInt(u) => serializer.serialize_u64(*u as u64),
+                Edn::Double(_) | Edn::Rational(_) => {
+                    serializer.serialize_f64(self.0.to_float().unwrap_or_default())
+                }
+                Edn::Char(c) => serializer.serialize_char(*c),
+                Edn::Bool(b) => serializer.serialize_bool(*b),
+                Edn::Nil | Edn::Empty => serializer.serialize_none(),
+            }
+        }
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        edn: &Option<Edn>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match edn {
+            Some(edn) => serializer.serialize_some(&Value(edn)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    struct EdnVisitor;
+
+    impl<'de> Visitor<'de> for EdnVisitor {
+        type Value = Edn;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an EDN compatible value")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<Edn, E> {
+            Ok(Edn::Bool(v))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Edn, E> {
+            Ok(Edn::Int(v as isize))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Edn, E> {
+            Ok(Edn::UInt(v as usize))
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Edn, E> {
+            Ok(Edn::Double(v.into()))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Edn, E> {
+            if v.starts_with(':') {
+                Ok(Edn::Key(v.to_string()))
+            } else {
+                Ok(Edn::Str(v.to_string()))
+            }
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Edn, E> {
+            Ok(Edn::Nil)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Edn, E> {
+            Ok(Edn::Nil)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Edn, D::Error> {
+            deserializer.deserialize_any(EdnVisitor)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Edn, A::Error> {
+            let mut items = Vec::new();
+            while let Some(item) = seq.next_element_seed(EdnSeed)? {
+                items.push(item);
+            }
+            Ok(Edn::Vector(Vector::new(items)))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Edn, A::Error> {
+            let mut entries = BTreeMap::new();
+            while let Some(key) = map.next_key::<String>()? {
+                entries.insert(key, map.next_value_seed(EdnSeed)?);
+            }
+            Ok(Edn::Map(Map::new(entries)))
+        }
+    }
+
+    struct EdnSeed;
+
+    impl<'de> de::DeserializeSeed<'de> for EdnSeed {
+        type Value = Edn;
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Edn, D::Error> {
+            deserializer.deserialize_any(EdnVisitor)
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Edn>, D::Error> {
+        match deserializer.deserialize_option(EdnVisitor)? {
+            Edn::Nil => Ok(None),
+            edn => Ok(Some(edn)),
+        }
+    }
+
+    /// `edn_value` for `Edn` fields that are always present.
+    pub(crate) mod required {
+        use super::{EdnVisitor, Value};
+        use edn_rs::Edn;
+        use serde::{Deserializer, Serialize, Serializer};
+
+        pub(crate) fn serialize<S: Serializer>(
+            edn: &Edn,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            Value(edn).serialize(serializer)
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Edn, D::Error> {
+            deserializer.deserialize_any(EdnVisitor)
+        }
+    }
+}
+
+/// Keyword for a struct field or a string map key.
+fn keyword(name: &str) -> String {
+    if name.starts_with(':') {
+        name.to_string()
+    } else if name.contains('/') {
+        format!(":{}", name)
+    } else {
+        format!(
+            ":{}",
+            name.replace("___", "/")
+                .replace("__", ".")
+                .replace('_', "-")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Value,
+    /// Strings are map keys and become keywords.
+    Key,
+    /// Strings are written without quotes.
+    Raw,
+}
+
+struct EdnSerializer {
+    output: String,
+    mode: Mode,
+}
+
+impl EdnSerializer {
+    fn new(mode: Mode) -> Self {
+        Self {
+            output: String::new(),
+            mode,
+        }
+    }
+
+    fn separator(&mut self, first: &mut bool) {
+        if !*first {
+            self.output.push_str(", ");
+        }
+        *first = false;
+    }
+}
+
+/// Error while serializing to EDN, converted to `CruxError::SerializeError`.
+#[derive(Debug)]
+struct Error(String);
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl From<Error> for CruxError {
+    fn from(err: Error) -> Self {
+        CruxError::SerializeError(err.0)
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $ty:ty),*) => {
+        $(fn $method(self, v: $ty) -> Result<(), Error> {
+            self.output.push_str(&v.to_string());
+            Ok(())
+        })*
+    };
+}
+
+impl<'a> ser::Serializer for &'a mut EdnSerializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    serialize_display!(
+        serialize_bool: bool, serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64
+    );
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        let number = if v.is_nan() {
+            "##NaN".to_string()
+        } else if v.is_infinite() && v > 0. {
+            "##Inf".to_string()
+        } else if v.is_infinite() {
+            "##-Inf".to_string()
+        } else if v.fract() == 0. && v.abs() < 1e16 {
+            format!("{:.1}", v)
+        } else {
+            v.to_string()
+        };
+        self.output.push_str(&number);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        let c = match v {
+            '\n' => "newline".to_string(),
+            '\r' => "return".to_string(),
+            ' ' => "space".to_string(),
+            '\t' => "tab".to_string(),
+            c => c.to_string(),
+        };
+        self.output.push('\\');
+        self.output.push_str(&c);
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        match self.mode {
+            Mode::Value => self.output.push_str(&edn_string(v)),
+            Mode::Key => self.output.push_str(&keyword(v)),
+            Mode::Raw => self.output.push_str(v),
+        }
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        use ser::SerializeSeq;
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for b in v {
+            seq.serialize_element(b)?;
+        }
+        seq.end()
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.output.push_str("nil");
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.output.push_str(&keyword(variant));
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        match name {
+            KEYWORD_TOKEN => {
+                let mut raw = EdnSerializer::new(Mode::Raw);
+                value.serialize(&mut raw)?;
+                self.output.push_str(&raw.output);
+                Ok(())
+            }
+            INST_TOKEN => {
+                self.output.push_str("#inst ");
+                let mut inst = EdnSerializer::new(Mode::Value);
+                value.serialize(&mut inst)?;
+                self.output.push_str(&inst.output);
+                Ok(())
+            }
+            _ => value.serialize(self),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.output.push('{');
+        self.output.push_str(&keyword(variant));
+        self.output.push(' ');
+        value.serialize(&mut *self)?;
+        self.output.push('}');
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, Error> {
+        self.output.push('[');
+        Ok(Compound::new(self, "]"))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.output.push('{');
+        self.output.push_str(&keyword(variant));
+        self.output.push_str(" [");
+        Ok(Compound::new(self, "]}"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, Error> {
+        self.output.push('{');
+        Ok(Compound::new(self, "}"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.output.push('{');
+        self.output.push_str(&keyword(variant));
+        self.output.push_str(" {");
+        Ok(Compound::new(self, "}}"))
+    }
+}
+
+/// Sequences, maps and structs being serialized, `end` closes them with `close`.
+struct Compound<'a> {
+    serializer: &'a mut EdnSerializer,
+    first: bool,
+    close: &'static str,
+}
+
+impl<'a> Compound<'a> {
+    fn new(serializer: &'a mut EdnSerializer, close: &'static str) -> Self {
+        Self {
+            serializer,
+            first: true,
+            close,
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serializer.separator(&mut self.first);
+        let mode = self.serializer.mode;
+        self.serializer.mode = Mode::Value;
+        let result = value.serialize(&mut *self.serializer);
+        self.serializer.mode = mode;
+        result
+    }
+
+    fn key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.serializer.separator(&mut self.first);
+        let mut serializer = EdnSerializer::new(Mode::Key);
+        key.serialize(&mut serializer)?;
+        self.serializer.output.push_str(&serializer.output);
+        Ok(())
+    }
+
+    fn value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serializer.output.push(' ');
+        let mode = self.serializer.mode;
+        self.serializer.mode = Mode::Value;
+        let result = value.serialize(&mut *self.serializer);
+        self.serializer.mode = mode;
+        result
+    }
+
+    fn close(self) -> Result<(), Error> {
+        self.serializer.output.push_str(self.close);
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.value(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.key(key)?;
+        self.value(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.key(key)?;
+        self.value(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::response::{EntityHistoryElement, EntityTxResponse};
+    use edn_rs::Edn;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    #[derive(Debug, Serialize)]
+    #[allow(non_snake_case)]
+    struct Person {
+        crux__db___id: CruxId,
+        first_name: String,
+        age: u8,
+        height: f64,
+        nickname: Option<String>,
+        tags: Vec<String>,
+        status: Status,
+        #[serde(with = "inst")]
+        born: DateTime<FixedOffset>,
+        #[serde(rename = "person/account")]
+        account: BTreeMap<String, i32>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Account {
+        #[serde(rename = "crux.db/id")]
+        id: CruxId,
+        #[serde(with = "inst::option")]
+        closed: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Debug, Serialize)]
+    enum Status {
+        Active,
+    }
+
+    #[test]
+    fn serialize_document() {
+        let mut account = BTreeMap::new();
+        account.insert("checking_amount".to_string(), 10);
+        let person = Person {
+            crux__db___id: CruxId::new("jorge-3"),
+            first_name: "Mi\"chael".to_string(),
+            age: 30,
+            height: 1.0,
+            nickname: None,
+            tags: vec!["a".to_string(), "b".to_string()],
+            status: Status::Active,
+            born: "2014-11-28T21:00:09+09:00".parse().unwrap(),
+            account,
+        };
+
+        let edn = to_string(&person).unwrap();
+
+        assert_eq!(edn, "{:crux.db/id :jorge-3, :first-name \"Mi\\\"chael\", :age 30, :height 1.0, :nickname nil, :tags [\"a\", \"b\"], :status :Active, :born #inst \"2014-11-28T21:00:09.000+09:00\", :person/account {:checking-amount 10}}");
+        assert!(Edn::from_str(&edn).is_ok());
+    }
+
+    #[test]
+    fn serialize_renamed_id() {
+        let account = Account {
+            id: CruxId::new("account-1"),
+            closed: Some("2014-11-28T12:00:09Z".parse().unwrap()),
+        };
+
+        let edn = SerdeDocument::new(&account).unwrap();
+
+        assert_eq!(
+            edn_rs::Serialize::serialize(edn),
+            "{:crux.db/id :account-1, :closed #inst \"2014-11-28T12:00:09.000Z\"}"
+        );
+    }
+
+    #[test]
+    fn responses_to_json() {
+        let entity_tx = EntityTxResponse::default();
+        let history = EntityHistoryElement::default_docs();
+
+        let entity_tx_json = serde_json::to_string(&entity_tx).unwrap();
+        let history_json = serde_json::to_string(&history).unwrap();
+
+        assert_eq!(entity_tx_json, "{\"db___id\":\"d72ccae848ce3a371bd313865cedc3d20b1478ca\",\"db___content_hash\":\"1828ebf4466f98ea3f5252a58734208cd0414376\",\"db___valid_time\":\"2020-07-19T04:12:13.788-00:00\",\"tx___tx_id\":28,\"tx___tx_time\":\"2020-07-19T04:12:13.788-00:00\"}");
+        assert_eq!(
+            serde_json::from_str::<EntityTxResponse>(&entity_tx_json).unwrap(),
+            entity_tx
+        );
+        assert_eq!(
+            serde_json::from_str::<EntityHistoryElement>(&history_json).unwrap(),
+            history
+        );
+    }
+}
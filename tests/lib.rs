@@ -24,7 +24,7 @@ mod integration {
 
         assert_eq!(
             format!("{:?}", body),
-            String::from("TxLogResponse { tx___tx_id: 8, tx___tx_time: 2020-07-16T21:53:14.628+00:00, tx__event___tx_events: None }")
+            String::from("TxLogResponse { tx___tx_id: 8, tx___tx_time: CruxInstant(\"2020-07-16T21:53:14.628-00:00\"), tx__event___tx_events: None }")
         );
     }
 
@@ -43,7 +43,7 @@ mod integration {
             .unwrap();
 
         assert_eq!(
-            body.tx___tx_time,
+            body.tx___tx_time.to_datetime().unwrap(),
             "2020-07-16T21:53:14.628-00:00"
                 .parse::<DateTime<FixedOffset>>()
                 .unwrap()
@@ -69,7 +69,7 @@ mod integration {
             .unwrap();
 
         let actual = format!("{:?}", body);
-        let expected = "EntityHistoryResponse { history: [EntityHistoryElement { db___valid_time: 2020-07-19T04:12:13.788+00:00, tx___tx_id: 28, tx___tx_time: 2020-07-19T04:12:13.788+00:00, db___content_hash: \"1828ebf4466f98ea3f5252a58734208cd0414376\", db__doc: None }] }";
+        let expected = "EntityHistoryResponse { history: [EntityHistoryElement { db___valid_time: CruxInstant(\"2020-07-19T04:12:13.788-00:00\"), tx___tx_id: 28, tx___tx_time: CruxInstant(\"2020-07-19T04:12:13.788-00:00\"), db___content_hash: \"1828ebf4466f98ea3f5252a58734208cd0414376\", db__doc: None }] }";
         assert_eq!(actual, expected);
     }
 
@@ -90,7 +90,7 @@ mod integration {
             .unwrap();
 
         let actual = format!("{:?}", body);
-        let expected = "EntityTxResponse { db___id: \"d72ccae848ce3a371bd313865cedc3d20b1478ca\", db___content_hash: \"1828ebf4466f98ea3f5252a58734208cd0414376\", db___valid_time: 2020-07-19T04:12:13.788+00:00, tx___tx_id: 28, tx___tx_time: 2020-07-19T04:12:13.788+00:00 }";
+        let expected = "EntityTxResponse { db___id: \"d72ccae848ce3a371bd313865cedc3d20b1478ca\", db___content_hash: \"1828ebf4466f98ea3f5252a58734208cd0414376\", db___valid_time: CruxInstant(\"2020-07-19T04:12:13.788-00:00\"), tx___tx_id: 28, tx___tx_time: CruxInstant(\"2020-07-19T04:12:13.788-00:00\") }";
 
         assert_eq!(actual, expected);
     }
@@ -100,7 +100,7 @@ mod integration {
         let date = "2014-11-28T21:00:09+09:00"
             .parse::<DateTime<Utc>>()
             .unwrap();
-        let m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false&start-transaction-time=2014-11-28T12:00:09&end-transaction-time=2014-11-28T12:00:09")
+        let m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false&start-transaction-time=2014-11-28T12:00:09.000Z&end-transaction-time=2014-11-28T12:00:09.000Z")
             .create();
 
         let _ = Crux::new("localhost", "3000")
@@ -120,7 +120,7 @@ mod integration {
         let date = "2014-11-28T21:00:09+09:00"
             .parse::<DateTime<Utc>>()
             .unwrap();
-        let m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false&end-transaction-time=2014-11-28T12:00:09")
+        let m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false&end-transaction-time=2014-11-28T12:00:09.000Z")
             .create();
 
         let _ = Crux::new("localhost", "3000")
@@ -140,7 +140,7 @@ mod integration {
         let date = "2014-11-28T21:00:09+09:00"
             .parse::<DateTime<Utc>>()
             .unwrap();
-        let m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false&start-valid-time=2014-11-28T12:00:09")
+        let m = mock("GET", "/entity-history/ecc6475b7ef9acf689f98e479d539e869432cb5e?sort-order=asc&with-docs=false&start-valid-time=2014-11-28T12:00:09.000Z")
             .create();
 
         let _ = Crux::new("localhost", "3000")
@@ -233,3 +233,138 @@ mod integration {
         last_name: String,
     }
 }
+
+#[cfg(feature = "testing")]
+mod fake {
+    use edn_derive::Serialize;
+    use transistor::client::Crux;
+    use transistor::edn_rs::Edn;
+    use transistor::testing::FakeCrux;
+    use transistor::types::http::{Actions, Order};
+    use transistor::types::{query::Query, CruxId};
+
+    #[test]
+    fn put_query_and_history() {
+        let fake = FakeCrux::new();
+        let client = Crux::new("localhost", "3000")
+            .with_authorization("auth")
+            .blocking_client_with(fake.clone());
+        let mut person = Person {
+            crux__db___id: CruxId::new("jorge-3"),
+            first_name: "Michael".to_string(),
+            last_name: "Jorge".to_string(),
+        };
+        client
+            .tx_log(Actions::new().append_put(person.clone()))
+            .unwrap();
+        person.first_name = "Mike".to_string();
+        fake.client()
+            .tx_log(Actions::new().append_put(person))
+            .unwrap();
+
+        let query = Query::find(vec!["?p", "?n"])
+            .unwrap()
+            .where_clause(vec!["?p :last-name \"Jorge\"", "?p :first-name ?n"])
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows = client.query(query).unwrap();
+        let history = client
+            .entity_history("jorge-3".to_string(), Order::Desc, true)
+            .unwrap()
+            .history;
+
+        assert_eq!(
+            rows.into_iter().collect::<Vec<_>>(),
+            vec![vec![":jorge-3".to_string(), "Mike".to_string()]]
+        );
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[1].db__doc.as_ref().unwrap()[":first-name"],
+            Edn::Str("Michael".to_string())
+        );
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    #[allow(non_snake_case)]
+    pub struct Person {
+        crux__db___id: CruxId,
+        first_name: String,
+        last_name: String,
+    }
+}
+
+mod document {
+    use transistor::edn_rs::{self, Edn, Serialize};
+    use transistor::types::{CruxDocument, CruxId};
+
+    #[derive(Debug, Clone, PartialEq, CruxDocument)]
+    #[crux(rename_all = "kebab-case")]
+    pub struct Person {
+        #[crux(id)]
+        id: CruxId,
+        #[crux(key = ":person/name")]
+        name: String,
+        last_name: String,
+        #[crux(skip_if_none)]
+        nickname: Option<String>,
+        age: Option<usize>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, CruxDocument)]
+    #[crux(namespace = "account")]
+    #[allow(non_snake_case)]
+    pub struct Account {
+        crux__db___id: CruxId,
+        amount: isize,
+    }
+
+    fn person(nickname: Option<&str>) -> Person {
+        Person {
+            id: CruxId::new("jorge-3"),
+            name: "Michael".to_string(),
+            last_name: "Jorge".to_string(),
+            nickname: nickname.map(String::from),
+            age: None,
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            person(None).serialize(),
+            "{ :crux.db/id :jorge-3, :person/name \"Michael\", :last-name \"Jorge\", :age nil, }"
+        );
+        assert_eq!(
+            person(Some("Mike")).serialize(),
+            "{ :crux.db/id :jorge-3, :person/name \"Michael\", :last-name \"Jorge\", :nickname \"Mike\", :age nil, }"
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        let edn: Edn = "{:crux.db/id :jorge-3, :person/name \"Michael\", :last-name \"Jorge\", :nickname \"Mike\"}"
+            .parse()
+            .unwrap();
+
+        let actual: Person = edn_rs::from_edn(&edn).unwrap();
+
+        assert_eq!(actual, person(Some("Mike")));
+    }
+
+    #[test]
+    fn round_trip_with_namespace() {
+        let account = Account {
+            crux__db___id: CruxId::new("account-1"),
+            amount: -10,
+        };
+        let serialized = account.clone().serialize();
+        let deserialized: Account = edn_rs::from_str(&serialized).unwrap();
+
+        assert_eq!(
+            serialized,
+            "{ :crux.db/id :account-1, :account/amount -10, }"
+        );
+        assert_eq!(deserialized, account);
+    }
+}
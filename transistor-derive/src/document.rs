@@ -0,0 +1,174 @@
+use crate::keyword::{field_to_keyword, Case};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Error, Field, Fields, LitStr};
+
+const CRUX_ID: &str = ":crux.db/id";
+
+struct Container {
+    case: Case,
+    namespace: Option<String>,
+}
+
+struct DocumentField<'a> {
+    field: &'a Field,
+    keyword: String,
+    skip_if_none: bool,
+}
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "CruxDocument can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "CruxDocument can only be derived for structs",
+            ))
+        }
+    };
+
+    let container = container_attributes(&input.attrs)?;
+    let fields = fields
+        .iter()
+        .map(|f| document_field(f, &container))
+        .collect::<Result<Vec<DocumentField>, Error>>()?;
+
+    let ids = fields.iter().filter(|f| f.keyword == CRUX_ID).count();
+    if ids != 1 {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!(
+                "CruxDocument requires exactly one field with `#[crux(id)]`, found {}",
+                ids
+            ),
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let serialized = fields.iter().map(|f| {
+        let ident = &f.field.ident;
+        let keyword = &f.keyword;
+        if f.skip_if_none {
+            quote! {
+                if self.#ident.is_some() {
+                    s.push_str(&format!("{} {}, ", #keyword, ::transistor::edn_rs::Serialize::serialize(self.#ident)));
+                }
+            }
+        } else {
+            quote! {
+                s.push_str(&format!("{} {}, ", #keyword, ::transistor::edn_rs::Serialize::serialize(self.#ident)));
+            }
+        }
+    });
+    let deserialized = fields.iter().map(|f| {
+        let ident = &f.field.ident;
+        let keyword = &f.keyword;
+        quote! {
+            #ident: ::transistor::edn_rs::from_edn(&edn[#keyword])?,
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::transistor::edn_rs::Serialize for #name #ty_generics #where_clause {
+            fn serialize(self) -> ::std::string::String {
+                let mut s = ::std::string::String::from("{ ");
+                #(#serialized)*
+                s.push('}');
+                s
+            }
+        }
+
+        impl #impl_generics ::transistor::edn_rs::Deserialize for #name #ty_generics #where_clause {
+            fn deserialize(edn: &::transistor::edn_rs::Edn) -> ::std::result::Result<Self, ::transistor::edn_rs::EdnError> {
+                ::std::result::Result::Ok(Self {
+                    #(#deserialized)*
+                })
+            }
+        }
+    })
+}
+
+fn container_attributes(attrs: &[Attribute]) -> Result<Container, Error> {
+    let mut container = Container {
+        case: Case::EdnDerive,
+        namespace: None,
+    };
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("crux")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let case: LitStr = meta.value()?.parse()?;
+                container.case = Case::parse(&case.value()).ok_or_else(|| {
+                    Error::new_spanned(
+                        &case,
+                        "unknown case, expected `kebab-case`, `snake_case`, `camelCase` or `lowercase`",
+                    )
+                })?;
+                Ok(())
+            } else if meta.path.is_ident("namespace") {
+                let namespace: LitStr = meta.value()?.parse()?;
+                container.namespace = Some(namespace.value().trim_start_matches(':').to_string());
+                Ok(())
+            } else {
+                Err(meta.error("unknown crux attribute, expected `rename_all` or `namespace`"))
+            }
+        })?;
+    }
+    Ok(container)
+}
+
+fn document_field<'a>(field: &'a Field, container: &Container) -> Result<DocumentField<'a>, Error> {
+    let ident = field
+        .ident
+        .as_ref()
+        .ok_or_else(|| Error::new_spanned(field, "CruxDocument fields must be named"))?;
+    let mut keyword = None;
+    let mut skip_if_none = false;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("crux")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                keyword = Some(CRUX_ID.to_string());
+                Ok(())
+            } else if meta.path.is_ident("key") {
+                let key: LitStr = meta.value()?.parse()?;
+                if !key.value().starts_with(':') {
+                    return Err(Error::new_spanned(
+                        &key,
+                        "key must be a keyword starting with `:`",
+                    ));
+                }
+                keyword = Some(key.value());
+                Ok(())
+            } else if meta.path.is_ident("skip_if_none") {
+                skip_if_none = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown crux attribute, expected `id`, `key` or `skip_if_none`"))
+            }
+        })?;
+    }
+
+    let keyword = keyword.unwrap_or_else(|| {
+        let default = field_to_keyword(&ident.to_string(), container.case, None);
+        match (&container.namespace, default == CRUX_ID) {
+            (Some(ns), false) => field_to_keyword(&ident.to_string(), container.case, Some(ns)),
+            _ => default,
+        }
+    });
+    Ok(DocumentField {
+        field,
+        keyword,
+        skip_if_none,
+    })
+}
@@ -0,0 +1,96 @@
+/// Field naming applied by `#[crux(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Case {
+    /// `edn-derive` naming, `crux__db___id` becomes `crux.db/id`.
+    EdnDerive,
+    Kebab,
+    Snake,
+    Camel,
+    Lower,
+}
+
+impl Case {
+    pub fn parse(case: &str) -> Option<Self> {
+        match case {
+            "kebab-case" => Some(Case::Kebab),
+            "snake_case" => Some(Case::Snake),
+            "camelCase" => Some(Case::Camel),
+            "lowercase" => Some(Case::Lower),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, field: &str) -> String {
+        let field = field.trim_start_matches("r#");
+        match self {
+            Case::EdnDerive => field
+                .replace("___", "/")
+                .replace("__", ".")
+                .replace('_', "-")
+                .to_lowercase(),
+            Case::Kebab => field.to_lowercase().replace('_', "-"),
+            Case::Snake => field.to_lowercase(),
+            Case::Lower => field.to_lowercase().replace('_', ""),
+            Case::Camel => field
+                .split('_')
+                .filter(|w| !w.is_empty())
+                .enumerate()
+                .map(|(i, w)| {
+                    let w = w.to_lowercase();
+                    if i == 0 {
+                        w
+                    } else {
+                        let mut chars = w.chars();
+                        chars
+                            .next()
+                            .map(|c| c.to_uppercase().chain(chars).collect())
+                            .unwrap_or_default()
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Keyword for `field`, `:namespace/field` when a namespace is defined.
+pub fn field_to_keyword(field: &str, case: Case, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(ns) => format!(":{}/{}", ns, case.apply(field)),
+        None => format!(":{}", case.apply(field)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edn_derive_case() {
+        assert_eq!(
+            field_to_keyword("crux__db___id", Case::EdnDerive, None),
+            ":crux.db/id"
+        );
+        assert_eq!(
+            field_to_keyword("first_name", Case::EdnDerive, None),
+            ":first-name"
+        );
+        assert_eq!(field_to_keyword("Name", Case::EdnDerive, None), ":name");
+    }
+
+    #[test]
+    fn rename_all() {
+        assert_eq!(Case::Kebab.apply("first_name"), "first-name");
+        assert_eq!(Case::Snake.apply("first_name"), "first_name");
+        assert_eq!(Case::Camel.apply("first_name"), "firstName");
+        assert_eq!(Case::Lower.apply("first_name"), "firstname");
+        assert_eq!(Case::Kebab.apply("r#type"), "type");
+    }
+
+    #[test]
+    fn namespace() {
+        assert_eq!(
+            field_to_keyword("first_name", Case::Kebab, Some("person")),
+            ":person/first-name"
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod document;
+mod keyword;
+
+/// Derives `edn_rs::Serialize` and `edn_rs::Deserialize` for a Crux document, so it can be used with `Actions::append_put` and read from `entity`.
+///
+/// Struct attributes:
+/// * `#[crux(rename_all = "kebab-case")]` renames every field key, available cases are `kebab-case`, `snake_case`, `camelCase` and `lowercase`.
+/// * `#[crux(namespace = "person")]` prefixes every field key with a namespace, `:person/name`.
+///
+/// Field attributes:
+/// * `#[crux(id)]` marks the field serialized as `:crux.db/id`. Exactly one field must be the id.
+/// * `#[crux(key = ":person/name")]` uses a custom keyword for the field.
+/// * `#[crux(skip_if_none)]` does not serialize an `Option` field when it is `None`.
+///
+/// Without `rename_all`, keys follow `edn-derive` naming, `crux__db___id` becomes `:crux.db/id` and `first_name` becomes `:first-name`.
+#[proc_macro_derive(CruxDocument, attributes(crux))]
+pub fn derive_crux_document(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    document::expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}